@@ -0,0 +1,222 @@
+use std::time::Duration;
+
+use smithay::{
+    desktop,
+    reexports::wayland_server::protocol::wl_surface::WlSurface,
+    utils::{Logical, Point, Rectangle},
+};
+
+/// Curve mapping the elapsed part of an animation to its progress
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Easing {
+    Linear,
+    EaseIn,
+    EaseOut,
+    EaseInOut,
+}
+
+impl Easing {
+    pub fn apply(self, t: f64) -> f64 {
+        let t = t.max(0.0).min(1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t * t,
+            Easing::EaseOut => 1.0 - (1.0 - t).powi(3),
+            Easing::EaseInOut => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    1.0 - (2.0 - 2.0 * t).powi(3) / 2.0
+                }
+            }
+        }
+    }
+}
+
+/// How long one kind of animation runs and how it eases
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Curve {
+    pub duration: Duration,
+    pub easing: Easing,
+}
+
+/// Running animation, sampled with the time elapsed since the compositor started
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Animation {
+    start: Duration,
+    curve: Curve,
+}
+
+impl Animation {
+    pub fn new(now: Duration, curve: Curve) -> Self {
+        Self { start: now, curve }
+    }
+
+    /// Eased progress, from 0.0 to 1.0
+    pub fn progress(&self, now: Duration) -> f64 {
+        if self.curve.duration.is_zero() {
+            return 1.0;
+        }
+
+        let elapsed = now.checked_sub(self.start).unwrap_or_default();
+        self.curve
+            .easing
+            .apply(elapsed.as_secs_f64() / self.curve.duration.as_secs_f64())
+    }
+
+    pub fn is_done(&self, now: Duration) -> bool {
+        now >= self.start + self.curve.duration
+    }
+}
+
+pub fn lerp(from: f64, to: f64, t: f64) -> f64 {
+    from + (to - from) * t
+}
+
+pub fn lerp_rect(
+    from: Rectangle<f64, Logical>,
+    to: Rectangle<f64, Logical>,
+    t: f64,
+) -> Rectangle<f64, Logical> {
+    Rectangle::from_loc_and_size(
+        (lerp(from.loc.x, to.loc.x, t), lerp(from.loc.y, to.loc.y, t)),
+        (
+            lerp(from.size.w, to.size.w, t),
+            lerp(from.size.h, to.size.h, t),
+        ),
+    )
+}
+
+/// `rect` scaled around its center
+pub fn scale_rect(rect: Rectangle<f64, Logical>, scale: f64) -> Rectangle<f64, Logical> {
+    let size = (rect.size.w * scale, rect.size.h * scale);
+    Rectangle::from_loc_and_size(
+        (
+            rect.loc.x + (rect.size.w - size.0) / 2.0,
+            rect.loc.y + (rect.size.h - size.1) / 2.0,
+        ),
+        size,
+    )
+}
+
+/// Where a window and its surfaces are drawn when it is not animated
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Placement {
+    pub geometry: Rectangle<i32, Logical>,
+    /// Area of the whole surface tree, client side shadows included
+    pub bbox: Rectangle<i32, Logical>,
+    /// Origin of the toplevel surface
+    pub surface_loc: Point<i32, Logical>,
+}
+
+impl Placement {
+    pub fn of(space: &desktop::Space, window: &desktop::Window) -> Option<Self> {
+        let geometry = space.window_geometry(window)?;
+        let surface_loc = geometry.loc - window.geometry().loc;
+
+        let mut bbox = window.bbox();
+        bbox.loc = bbox.loc + surface_loc;
+
+        Some(Self {
+            geometry,
+            bbox,
+            surface_loc,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Motion {
+    /// Grows and fades in from `scale` of its size
+    Open { scale: f64 },
+    /// Shrinks and fades out to `scale` of its size, the window is already gone from its space
+    Close {
+        scale: f64,
+        workspace: String,
+        placement: Placement,
+        /// Place it had in the stack of its space, counted from the bottom
+        index: usize,
+    },
+    /// Moves and resizes from `from` to `to`, the client catches up with the new size meanwhile
+    Resize {
+        from: Rectangle<i32, Logical>,
+        to: Rectangle<i32, Logical>,
+    },
+}
+
+/// Window drawn by its animation instead of its space
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transition {
+    pub window: desktop::Window,
+    pub motion: Motion,
+    pub animation: Animation,
+}
+
+/// What a sample draws
+#[derive(Debug, Clone)]
+pub enum Content {
+    /// Surface tree of a live window
+    Surface(WlSurface),
+    /// Closed window, its surfaces are gone so the snapshot the output took of it is drawn
+    Snapshot(desktop::Window),
+}
+
+/// Transition at one point in time, relative to the output it is drawn on
+#[derive(Debug, Clone)]
+pub struct Sample {
+    pub content: Content,
+    pub surface_loc: Point<i32, Logical>,
+    /// Part drawn without animation
+    pub area: Rectangle<i32, Logical>,
+    /// Where `area` ends up
+    pub target: Rectangle<f64, Logical>,
+    pub alpha: f32,
+}
+
+impl Sample {
+    /// The whole surface tree follows the window geometry onto `target`
+    pub fn new(
+        content: Content,
+        placement: Placement,
+        target: Rectangle<f64, Logical>,
+        alpha: f32,
+        origin: Point<i32, Logical>,
+    ) -> Self {
+        let Placement {
+            geometry,
+            bbox,
+            surface_loc,
+        } = placement;
+        let scale_x = target.size.w / geometry.size.w.max(1) as f64;
+        let scale_y = target.size.h / geometry.size.h.max(1) as f64;
+
+        let target = Rectangle::from_loc_and_size(
+            (
+                target.loc.x + (bbox.loc.x - geometry.loc.x) as f64 * scale_x - origin.x as f64,
+                target.loc.y + (bbox.loc.y - geometry.loc.y) as f64 * scale_y - origin.y as f64,
+            ),
+            (bbox.size.w as f64 * scale_x, bbox.size.h as f64 * scale_y),
+        );
+
+        Self {
+            content,
+            surface_loc: surface_loc - origin,
+            area: Rectangle::from_loc_and_size(bbox.loc - origin, bbox.size),
+            target,
+            alpha,
+        }
+    }
+
+    /// Area touched when drawing the sample
+    pub fn damage(&self) -> Rectangle<i32, Logical> {
+        let x = self.target.loc.x.floor();
+        let y = self.target.loc.y.floor();
+        Rectangle::from_loc_and_size(
+            (x as i32, y as i32),
+            (
+                (self.target.loc.x + self.target.size.w - x).ceil() as i32,
+                (self.target.loc.y + self.target.size.h - y).ceil() as i32,
+            ),
+        )
+    }
+}
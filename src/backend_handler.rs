@@ -30,17 +30,14 @@ impl OutputHandler for Anodium {
 
         self.config.output_new(output.clone());
 
-        if let Some(layout) = self
-            .config
-            .output_rearrange(self.output_manager.outputs().clone())
-        {
-            for (output, pos) in self.output_manager.outputs().iter().zip(layout.iter()) {
-                let scale = self.workspace.output_scale(output).unwrap_or(1.0);
+        self.rearrange_outputs();
+    }
 
-                let (x, y) = *pos;
-                self.workspace.map_output(output, scale, (x, y));
-            }
-        }
+    fn output_removed(&mut self, output: &Output) {
+        info!("OutputRemoved: {}", output.name());
+        self.output_manager.remove(&mut self.workspace, output);
+
+        self.rearrange_outputs();
     }
 
     fn output_mode_updated(&mut self, output: &crate::output_manager::Output, mode: output::Mode) {
@@ -60,6 +57,25 @@ impl OutputHandler for Anodium {
     }
 }
 
+impl Anodium {
+    /// Apply the layout returned by the rhai `on_rearrange` callback
+    pub fn rearrange_outputs(&mut self) {
+        if let Some(layout) = self
+            .config
+            .output_rearrange(self.output_manager.outputs().clone())
+        {
+            for (output, pos) in self.output_manager.outputs().iter().zip(layout.iter()) {
+                let scale = self.workspace.output_scale(output).unwrap_or(1.0);
+
+                let (x, y) = *pos;
+                self.workspace.map_output(output, scale, (x, y));
+            }
+        }
+
+        self.clamp_pointer();
+    }
+}
+
 impl BackendHandler for Anodium {
     fn anodium_protocol(&mut self) -> &mut AnodiumProtocol {
         &mut self.anodium_protocol
@@ -0,0 +1,208 @@
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use std::time::Duration;
+
+use rhai::plugin::*;
+use rhai::{FLOAT, INT};
+
+use crate::animation::{Curve, Easing};
+
+#[derive(Debug)]
+struct TimingInner {
+    enabled: bool,
+    duration: Duration,
+    easing: Easing,
+}
+
+/// Duration and easing of one kind of animation
+#[derive(Debug, Clone)]
+pub struct Timing {
+    inner: Rc<RefCell<TimingInner>>,
+}
+
+impl Timing {
+    fn new(duration: u64, easing: Easing) -> Self {
+        Self {
+            inner: Rc::new(RefCell::new(TimingInner {
+                enabled: true,
+                duration: Duration::from_millis(duration),
+                easing,
+            })),
+        }
+    }
+
+    fn curve(&self) -> Option<Curve> {
+        let inner = self.inner.borrow();
+        if inner.enabled {
+            Some(Curve {
+                duration: inner.duration,
+                easing: inner.easing,
+            })
+        } else {
+            None
+        }
+    }
+}
+
+/// Window and workspace animations, every getter returns `None` when they are disabled
+#[derive(Debug, Clone)]
+pub struct Animations {
+    enabled: Rc<Cell<bool>>,
+    /// Size windows open from and close to, relative to their own
+    scale: Rc<Cell<f64>>,
+    open: Timing,
+    close: Timing,
+    maximize: Timing,
+    workspace: Timing,
+}
+
+impl Animations {
+    pub fn new() -> Self {
+        Self {
+            enabled: Rc::new(Cell::new(true)),
+            scale: Rc::new(Cell::new(0.9)),
+            open: Timing::new(200, Easing::EaseOut),
+            close: Timing::new(150, Easing::EaseIn),
+            maximize: Timing::new(200, Easing::EaseInOut),
+            workspace: Timing::new(250, Easing::EaseOut),
+        }
+    }
+
+    fn curve(&self, timing: &Timing) -> Option<Curve> {
+        if self.enabled.get() {
+            timing.curve()
+        } else {
+            None
+        }
+    }
+
+    pub fn open(&self) -> Option<Curve> {
+        self.curve(&self.open)
+    }
+
+    pub fn close(&self) -> Option<Curve> {
+        self.curve(&self.close)
+    }
+
+    pub fn maximize(&self) -> Option<Curve> {
+        self.curve(&self.maximize)
+    }
+
+    pub fn workspace(&self) -> Option<Curve> {
+        self.curve(&self.workspace)
+    }
+
+    pub fn scale(&self) -> f64 {
+        self.scale.get()
+    }
+}
+
+impl Default for Animations {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[export_module]
+pub mod animations {
+    #[rhai_fn(get = "enabled", pure)]
+    pub fn enabled(animations: &mut Animations) -> bool {
+        animations.enabled.get()
+    }
+
+    /// false disables every animation
+    #[rhai_fn(set = "enabled", pure)]
+    pub fn set_enabled(animations: &mut Animations, enabled: bool) {
+        animations.enabled.set(enabled);
+    }
+
+    #[rhai_fn(get = "scale", pure)]
+    pub fn scale(animations: &mut Animations) -> FLOAT {
+        animations.scale.get() as _
+    }
+
+    /// Between 0.0 and 1.0, 1.0 only fades
+    #[rhai_fn(set = "scale", pure)]
+    pub fn set_scale(animations: &mut Animations, scale: FLOAT) {
+        animations.scale.set((scale as f64).max(0.0).min(1.0));
+    }
+
+    #[rhai_fn(get = "open", pure)]
+    pub fn open(animations: &mut Animations) -> Timing {
+        animations.open.clone()
+    }
+
+    #[rhai_fn(get = "close", pure)]
+    pub fn close(animations: &mut Animations) -> Timing {
+        animations.close.clone()
+    }
+
+    #[rhai_fn(get = "maximize", pure)]
+    pub fn maximize(animations: &mut Animations) -> Timing {
+        animations.maximize.clone()
+    }
+
+    #[rhai_fn(get = "workspace", pure)]
+    pub fn workspace(animations: &mut Animations) -> Timing {
+        animations.workspace.clone()
+    }
+
+    #[rhai_fn(get = "enabled", pure)]
+    pub fn timing_enabled(timing: &mut Timing) -> bool {
+        timing.inner.borrow().enabled
+    }
+
+    #[rhai_fn(set = "enabled", pure)]
+    pub fn set_timing_enabled(timing: &mut Timing, enabled: bool) {
+        timing.inner.borrow_mut().enabled = enabled;
+    }
+
+    /// In ms
+    #[rhai_fn(get = "duration", pure)]
+    pub fn duration(timing: &mut Timing) -> INT {
+        timing.inner.borrow().duration.as_millis() as _
+    }
+
+    #[rhai_fn(set = "duration", pure)]
+    pub fn set_duration(timing: &mut Timing, duration: INT) {
+        timing.inner.borrow_mut().duration = Duration::from_millis(duration.max(0) as _);
+    }
+
+    #[rhai_fn(get = "easing", pure)]
+    pub fn easing(timing: &mut Timing) -> Easing {
+        timing.inner.borrow().easing
+    }
+
+    #[rhai_fn(set = "easing", pure)]
+    pub fn set_easing(timing: &mut Timing, easing: Easing) {
+        timing.inner.borrow_mut().easing = easing;
+    }
+}
+
+#[export_module]
+pub mod easing {
+    pub fn linear() -> Easing {
+        Easing::Linear
+    }
+    pub fn ease_in() -> Easing {
+        Easing::EaseIn
+    }
+    pub fn ease_out() -> Easing {
+        Easing::EaseOut
+    }
+    pub fn ease_in_out() -> Easing {
+        Easing::EaseInOut
+    }
+}
+
+pub fn register(engine: &mut Engine) {
+    let animations_module = exported_module!(animations);
+    let easing_module = exported_module!(easing);
+
+    engine
+        .register_static_module("animations", animations_module.into())
+        .register_static_module("easing", easing_module.into())
+        .register_type::<Animations>()
+        .register_type::<Timing>()
+        .register_type::<Easing>();
+}
@@ -1,5 +1,5 @@
 use rhai::Dynamic;
-use rhai::{plugin::*, Scope};
+use rhai::{plugin::*, Scope, INT};
 
 use smithay::reexports::calloop::channel::Sender;
 use smithay::reexports::calloop::LoopHandle;
@@ -7,22 +7,37 @@ use smithay::reexports::calloop::LoopHandle;
 use crate::output_manager::OutputManager;
 use crate::state::Anodium;
 
+use super::animations::Animations;
+use super::borders::Borders;
 use super::eventloop::ConfigEvent;
+use super::gestures::Gestures;
+use super::input::Input;
 use super::keyboard::Keyboard;
 use super::log::Log;
+use super::notifications::{timeout_from_ms, Notification, Notifications};
+use super::osd::Osd;
+use super::outputs::shell::ticker;
 use super::outputs::Outputs;
 use super::system::System;
+use super::theme::Theme;
 use super::windows::Windows;
 use super::workspace::Workspace;
 
 #[derive(Debug, Clone)]
 pub struct Anodize {
     pub keyboard: Keyboard,
+    pub gestures: Gestures,
+    pub input: Input,
     system: System,
     workspace: Workspace,
     pub windows: Windows,
     log: Log,
     pub outputs: Outputs,
+    pub theme: Theme,
+    pub notifications: Notifications,
+    pub osd: Osd,
+    pub borders: Borders,
+    pub animations: Animations,
 }
 
 impl Anodize {
@@ -31,13 +46,22 @@ impl Anodize {
         output_map: OutputManager,
         loop_handle: LoopHandle<'static, Anodium>,
     ) -> Self {
+        ticker::init(&loop_handle);
+
         Self {
             keyboard: Keyboard::new(),
+            gestures: Gestures::new(),
+            input: Input::new(),
             system: System::new(event_sender.clone(), loop_handle),
             workspace: Workspace::new(event_sender.clone()),
             windows: Windows::new(event_sender),
             log: Log::new(),
-            outputs: Outputs::new(output_map),
+            outputs: Outputs::new(output_map.clone()),
+            theme: Theme::new(),
+            notifications: Notifications::new(),
+            osd: Osd::new(output_map),
+            borders: Borders::new(),
+            animations: Animations::new(),
         }
     }
 }
@@ -57,6 +81,16 @@ pub mod anodize_module {
         anodize.keyboard.clone()
     }
 
+    #[rhai_fn(get = "gestures", pure)]
+    pub fn get_gestures(anodize: &mut Anodize) -> Gestures {
+        anodize.gestures.clone()
+    }
+
+    #[rhai_fn(get = "input", pure)]
+    pub fn get_input(anodize: &mut Anodize) -> Input {
+        anodize.input.clone()
+    }
+
     #[rhai_fn(get = "system", pure)]
     pub fn get_system(anodize: &mut Anodize) -> System {
         anodize.system.clone()
@@ -81,6 +115,51 @@ pub mod anodize_module {
     pub fn get_outputs(anodize: &mut Anodize) -> Outputs {
         anodize.outputs.clone()
     }
+
+    #[rhai_fn(get = "theme", pure)]
+    pub fn get_theme(anodize: &mut Anodize) -> Theme {
+        anodize.theme.clone()
+    }
+
+    #[rhai_fn(get = "notifications", pure)]
+    pub fn get_notifications(anodize: &mut Anodize) -> Notifications {
+        anodize.notifications.clone()
+    }
+
+    #[rhai_fn(get = "borders", pure)]
+    pub fn get_borders(anodize: &mut Anodize) -> Borders {
+        anodize.borders.clone()
+    }
+
+    #[rhai_fn(get = "animations", pure)]
+    pub fn get_animations(anodize: &mut Anodize) -> Animations {
+        anodize.animations.clone()
+    }
+
+    /// Overlays drawn by the egui shell of every output, `anodize.shell.osd(text, value, timeout)`
+    #[rhai_fn(get = "shell", pure)]
+    pub fn get_shell(anodize: &mut Anodize) -> Osd {
+        anodize.osd.clone()
+    }
+
+    /// Same overlay, as `anodize.osd.show(text, value, timeout)`
+    #[rhai_fn(get = "osd", pure)]
+    pub fn get_osd(anodize: &mut Anodize) -> Osd {
+        anodize.osd.clone()
+    }
+
+    /// Shorthand for `anodize.notifications.notify`, the timeout is in ms, 0 never times out
+    #[rhai_fn(global)]
+    pub fn notify(
+        anodize: &mut Anodize,
+        summary: String,
+        body: String,
+        timeout: INT,
+    ) -> Notification {
+        anodize
+            .notifications
+            .notify(summary, body, timeout_from_ms(timeout))
+    }
 }
 
 pub fn register(
@@ -0,0 +1,191 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use egui::Color32;
+use rhai::plugin::*;
+use rhai::{Array, INT};
+
+use super::outputs::shell::logger::Logger;
+
+/// Width and colors of the border around one window
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BorderStyle {
+    pub width: i32,
+    pub active: [f32; 4],
+    pub inactive: [f32; 4],
+    pub urgent: [f32; 4],
+}
+
+impl Default for BorderStyle {
+    fn default() -> Self {
+        Self {
+            width: 2,
+            active: [26.0 / 255.0, 95.0 / 255.0, 205.0 / 255.0, 1.0],
+            inactive: [0.3, 0.3, 0.3, 1.0],
+            urgent: [205.0 / 255.0, 95.0 / 255.0, 26.0 / 255.0, 1.0],
+        }
+    }
+}
+
+fn to_rgba(color: Color32) -> [f32; 4] {
+    [
+        color.r() as f32 / 255.0,
+        color.g() as f32 / 255.0,
+        color.b() as f32 / 255.0,
+        color.a() as f32 / 255.0,
+    ]
+}
+
+/// Override of the border style for the windows it matches
+#[derive(Debug, Clone, Default)]
+pub struct BorderRule {
+    app_id: Option<String>,
+    /// Matches titles containing it
+    title: Option<String>,
+    width: Option<i32>,
+    active: Option<[f32; 4]>,
+    inactive: Option<[f32; 4]>,
+    urgent: Option<[f32; 4]>,
+}
+
+impl BorderRule {
+    fn matches(&self, app_id: &str, title: &str) -> bool {
+        self.app_id.as_ref().map(|a| a == app_id).unwrap_or(true)
+            && self
+                .title
+                .as_ref()
+                .map(|t| title.contains(t.as_str()))
+                .unwrap_or(true)
+    }
+
+    fn apply(&self, style: &mut BorderStyle) {
+        if let Some(width) = self.width {
+            style.width = width;
+        }
+        if let Some(active) = self.active {
+            style.active = active;
+        }
+        if let Some(inactive) = self.inactive {
+            style.inactive = inactive;
+        }
+        if let Some(urgent) = self.urgent {
+            style.urgent = urgent;
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct BordersInner {
+    style: BorderStyle,
+    rules: Vec<BorderRule>,
+}
+
+/// Borders drawn by the compositor around every window
+#[derive(Debug, Clone, Default)]
+pub struct Borders {
+    inner: Rc<RefCell<BordersInner>>,
+}
+
+impl Borders {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Style of a window, every matching rule is applied in the order they got added
+    pub fn style(&self, app_id: &str, title: &str) -> BorderStyle {
+        let inner = self.inner.borrow();
+        let mut style = inner.style;
+        for rule in inner.rules.iter().filter(|r| r.matches(app_id, title)) {
+            rule.apply(&mut style);
+        }
+        style
+    }
+}
+
+#[export_module]
+pub mod borders {
+    #[rhai_fn(get = "width", pure)]
+    pub fn width(borders: &mut Borders) -> INT {
+        borders.inner.borrow().style.width as _
+    }
+
+    /// 0 disables the borders
+    #[rhai_fn(set = "width", pure)]
+    pub fn set_width(borders: &mut Borders, width: INT) {
+        borders.inner.borrow_mut().style.width = width.max(0) as _;
+    }
+
+    #[rhai_fn(set = "active", pure)]
+    pub fn set_active(borders: &mut Borders, color: Array) {
+        if let Some(color) = Logger::parse_color(color) {
+            borders.inner.borrow_mut().style.active = to_rgba(color);
+        }
+    }
+
+    #[rhai_fn(set = "inactive", pure)]
+    pub fn set_inactive(borders: &mut Borders, color: Array) {
+        if let Some(color) = Logger::parse_color(color) {
+            borders.inner.borrow_mut().style.inactive = to_rgba(color);
+        }
+    }
+
+    #[rhai_fn(set = "urgent", pure)]
+    pub fn set_urgent(borders: &mut Borders, color: Array) {
+        if let Some(color) = Logger::parse_color(color) {
+            borders.inner.borrow_mut().style.urgent = to_rgba(color);
+        }
+    }
+
+    #[rhai_fn(global)]
+    pub fn add_rule(borders: &mut Borders, rule: BorderRule) {
+        borders.inner.borrow_mut().rules.push(rule);
+    }
+
+    #[rhai_fn(global)]
+    pub fn clear_rules(borders: &mut Borders) {
+        borders.inner.borrow_mut().rules.clear();
+    }
+
+    /// Rule matching every window, narrowed down with `app_id` and `title`
+    pub fn rule() -> BorderRule {
+        BorderRule::default()
+    }
+
+    #[rhai_fn(set = "app_id")]
+    pub fn set_rule_app_id(rule: &mut BorderRule, app_id: String) {
+        rule.app_id = Some(app_id);
+    }
+
+    #[rhai_fn(set = "title")]
+    pub fn set_rule_title(rule: &mut BorderRule, title: String) {
+        rule.title = Some(title);
+    }
+
+    #[rhai_fn(set = "width")]
+    pub fn set_rule_width(rule: &mut BorderRule, width: INT) {
+        rule.width = Some(width.max(0) as _);
+    }
+
+    #[rhai_fn(set = "active")]
+    pub fn set_rule_active(rule: &mut BorderRule, color: Array) {
+        rule.active = Logger::parse_color(color).map(to_rgba);
+    }
+
+    #[rhai_fn(set = "inactive")]
+    pub fn set_rule_inactive(rule: &mut BorderRule, color: Array) {
+        rule.inactive = Logger::parse_color(color).map(to_rgba);
+    }
+
+    #[rhai_fn(set = "urgent")]
+    pub fn set_rule_urgent(rule: &mut BorderRule, color: Array) {
+        rule.urgent = Logger::parse_color(color).map(to_rgba);
+    }
+}
+
+pub fn register(engine: &mut Engine) {
+    let borders_module = exported_module!(borders);
+    engine
+        .register_static_module("borders", borders_module.into())
+        .register_type::<Borders>()
+        .register_type::<BorderRule>();
+}
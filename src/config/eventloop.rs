@@ -1,13 +1,23 @@
-use rhai::FnPtr;
+use rhai::{Dynamic, FnPtr};
 
 use crate::window::Window;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum ConfigEvent {
     SwitchWorkspace(String),
     Close(Window),
+    Focus(Window),
+    Minimize(Window),
     Maximize(Window),
     Unmaximize(Window),
+    Fullscreen(Window),
+    Unfullscreen(Window),
+    /// Move or resize the window with the pointer until the next click
+    Move(Window),
+    Resize(Window),
+    AlwaysOnTop(Window, bool),
+    MoveToWorkspace(Window, String),
     OutputsRearrange,
     Shell(FnPtr),
+    ShellWithArgs(FnPtr, Vec<Dynamic>),
 }
@@ -0,0 +1,250 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use rhai::plugin::*;
+use rhai::{Array, FnPtr, INT};
+
+use smithay::utils::{Logical, Point};
+
+use super::ConfigVM;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SwipeDirection {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl SwipeDirection {
+    pub fn from_delta(delta: Point<f64, Logical>) -> Self {
+        if delta.x.abs() > delta.y.abs() {
+            if delta.x < 0.0 {
+                Self::Left
+            } else {
+                Self::Right
+            }
+        } else if delta.y < 0.0 {
+            Self::Up
+        } else {
+            Self::Down
+        }
+    }
+
+    fn parse(direction: &str) -> Option<Self> {
+        match direction {
+            "up" => Some(Self::Up),
+            "down" => Some(Self::Down),
+            "left" => Some(Self::Left),
+            "right" => Some(Self::Right),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PinchDirection {
+    In,
+    Out,
+}
+
+impl PinchDirection {
+    pub fn from_scale(scale: f64) -> Self {
+        if scale < 1.0 {
+            Self::In
+        } else {
+            Self::Out
+        }
+    }
+
+    fn parse(direction: &str) -> Option<Self> {
+        match direction {
+            "in" => Some(Self::In),
+            "out" => Some(Self::Out),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GestureKind {
+    Swipe,
+    Pinch,
+    Hold,
+}
+
+/// Finished gesture that can be bound to a rhai callback
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Gesture {
+    Swipe {
+        fingers: u32,
+        direction: SwipeDirection,
+    },
+    Pinch {
+        fingers: u32,
+        direction: PinchDirection,
+    },
+    Hold {
+        fingers: u32,
+    },
+}
+
+impl Gesture {
+    pub fn kind(&self) -> GestureKind {
+        match self {
+            Self::Swipe { .. } => GestureKind::Swipe,
+            Self::Pinch { .. } => GestureKind::Pinch,
+            Self::Hold { .. } => GestureKind::Hold,
+        }
+    }
+
+    pub fn fingers(&self) -> u32 {
+        match self {
+            Self::Swipe { fingers, .. } | Self::Pinch { fingers, .. } | Self::Hold { fingers } => {
+                *fingers
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Callback {
+    gesture: Gesture,
+    fnptr: FnPtr,
+}
+
+#[derive(Debug)]
+struct Inner {
+    callbacks: Vec<Callback>,
+    workspace_swipe: Vec<u32>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Gestures {
+    inner: Rc<RefCell<Inner>>,
+}
+
+impl Gestures {
+    pub fn new() -> Self {
+        Self {
+            inner: Rc::new(RefCell::new(Inner {
+                callbacks: Vec::new(),
+                workspace_swipe: vec![3, 4],
+            })),
+        }
+    }
+
+    fn insert(&self, gesture: Gesture, fnptr: FnPtr) {
+        self.inner
+            .borrow_mut()
+            .callbacks
+            .push(Callback { gesture, fnptr });
+    }
+
+    /// Check if any callback is registered for gestures of this kind,
+    /// in that case the gesture is not forwarded to clients
+    pub fn is_bound(&self, kind: GestureKind, fingers: u32) -> bool {
+        self.inner
+            .borrow()
+            .callbacks
+            .iter()
+            .any(|c| c.gesture.kind() == kind && c.gesture.fingers() == fingers)
+    }
+
+    /// Check if horizontal swipes with this amount of fingers switch workspaces
+    pub fn is_workspace_swipe(&self, fingers: u32) -> bool {
+        self.inner.borrow().workspace_swipe.contains(&fingers)
+    }
+
+    pub fn gesture_action(&self, config: &ConfigVM, gesture: Gesture) -> bool {
+        let callbacks: Vec<FnPtr> = self
+            .inner
+            .borrow()
+            .callbacks
+            .iter()
+            .filter(|c| c.gesture == gesture)
+            .map(|c| c.fnptr.clone())
+            .collect();
+
+        for fnptr in callbacks.iter() {
+            config.execute_fnptr(fnptr.clone(), ());
+        }
+
+        !callbacks.is_empty()
+    }
+}
+
+#[export_module]
+pub mod gestures {
+    #[rhai_fn(global)]
+    pub fn register_swipe(gestures: &mut Gestures, fnptr: FnPtr, fingers: INT, direction: &str) {
+        if let Some(direction) = SwipeDirection::parse(direction) {
+            gestures.insert(
+                Gesture::Swipe {
+                    fingers: fingers as _,
+                    direction,
+                },
+                fnptr,
+            );
+        } else {
+            slog_scope::warn!("unknown swipe direction: {}, ignoring", direction);
+        }
+    }
+
+    #[rhai_fn(global)]
+    pub fn register_pinch(gestures: &mut Gestures, fnptr: FnPtr, fingers: INT, direction: &str) {
+        if let Some(direction) = PinchDirection::parse(direction) {
+            gestures.insert(
+                Gesture::Pinch {
+                    fingers: fingers as _,
+                    direction,
+                },
+                fnptr,
+            );
+        } else {
+            slog_scope::warn!("unknown pinch direction: {}, ignoring", direction);
+        }
+    }
+
+    #[rhai_fn(global)]
+    pub fn register_hold(gestures: &mut Gestures, fnptr: FnPtr, fingers: INT) {
+        gestures.insert(
+            Gesture::Hold {
+                fingers: fingers as _,
+            },
+            fnptr,
+        );
+    }
+
+    #[rhai_fn(global)]
+    pub fn clear(gestures: &mut Gestures) {
+        gestures.inner.borrow_mut().callbacks.clear();
+    }
+
+    #[rhai_fn(get = "workspace_swipe", pure)]
+    pub fn workspace_swipe(gestures: &mut Gestures) -> Array {
+        gestures
+            .inner
+            .borrow()
+            .workspace_swipe
+            .iter()
+            .map(|f| Dynamic::from(*f as INT))
+            .collect()
+    }
+
+    #[rhai_fn(set = "workspace_swipe", pure)]
+    pub fn set_workspace_swipe(gestures: &mut Gestures, fingers: Array) {
+        gestures.inner.borrow_mut().workspace_swipe = fingers
+            .iter()
+            .filter_map(|f| f.as_int().ok())
+            .map(|f| f as u32)
+            .collect();
+    }
+}
+
+pub fn register(engine: &mut Engine) {
+    let gestures_module = exported_module!(gestures);
+    engine
+        .register_static_module("gestures", gestures_module.into())
+        .register_type::<Gestures>();
+}
@@ -0,0 +1,77 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use rhai::plugin::*;
+use rhai::Dynamic;
+
+#[derive(Debug, Default)]
+struct Inner {
+    touch_output: Option<String>,
+    tablet_output: Option<String>,
+}
+
+/// Input device settings
+#[derive(Debug, Default, Clone)]
+pub struct Input {
+    inner: Rc<RefCell<Inner>>,
+}
+
+impl Input {
+    pub fn new() -> Self {
+        Self {
+            inner: Default::default(),
+        }
+    }
+
+    /// Name of the output touchscreens are mapped to
+    pub fn touch_output(&self) -> Option<String> {
+        self.inner.borrow().touch_output.clone()
+    }
+
+    /// Name of the output drawing tablets are mapped to
+    pub fn tablet_output(&self) -> Option<String> {
+        self.inner.borrow().tablet_output.clone()
+    }
+}
+
+fn to_dynamic(value: Option<String>) -> Dynamic {
+    value.map(Dynamic::from).unwrap_or(Dynamic::UNIT)
+}
+
+fn from_str(value: &str) -> Option<String> {
+    if value.is_empty() {
+        None
+    } else {
+        Some(value.to_owned())
+    }
+}
+
+#[export_module]
+pub mod input {
+    #[rhai_fn(get = "touch_output", pure)]
+    pub fn touch_output(input: &mut Input) -> Dynamic {
+        to_dynamic(input.touch_output())
+    }
+
+    #[rhai_fn(set = "touch_output", pure)]
+    pub fn set_touch_output(input: &mut Input, output: &str) {
+        input.inner.borrow_mut().touch_output = from_str(output);
+    }
+
+    #[rhai_fn(get = "tablet_output", pure)]
+    pub fn tablet_output(input: &mut Input) -> Dynamic {
+        to_dynamic(input.tablet_output())
+    }
+
+    #[rhai_fn(set = "tablet_output", pure)]
+    pub fn set_tablet_output(input: &mut Input, output: &str) {
+        input.inner.borrow_mut().tablet_output = from_str(output);
+    }
+}
+
+pub fn register(engine: &mut Engine) {
+    let input_module = exported_module!(input);
+    engine
+        .register_static_module("input", input_module.into())
+        .register_type::<Input>();
+}
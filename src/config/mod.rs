@@ -5,13 +5,20 @@ use std::rc::Rc;
 
 use rhai::{Dynamic, Engine, EvalAltResult, FnPtr, FuncArgs, Scope, AST};
 
+pub mod animations;
 mod anodize;
+pub mod borders;
 pub mod eventloop;
+pub mod gestures;
+mod input;
 pub mod keyboard;
 mod log;
+pub mod notifications;
+pub mod osd;
 pub mod outputs;
-mod system;
-mod windows;
+pub mod system;
+pub mod theme;
+pub mod windows;
 mod workspace;
 
 use smithay::backend::input::KeyState;
@@ -24,6 +31,9 @@ use crate::state::Anodium;
 
 use self::anodize::Anodize;
 use self::eventloop::ConfigEvent;
+use self::gestures::{Gesture, GestureKind};
+use self::outputs::shell::menu::Menu;
+use self::windows::Decorations;
 
 #[derive(Debug)]
 struct Inner {
@@ -52,11 +62,18 @@ impl ConfigVM {
         let mut scope = Scope::new();
 
         keyboard::register(&mut engine);
+        gestures::register(&mut engine);
+        input::register(&mut engine);
         log::register(&mut engine);
         system::register(&mut engine);
         workspace::register(&mut engine);
         windows::register(&mut engine);
         outputs::register(&mut engine);
+        theme::register(&mut engine);
+        notifications::register(&mut engine);
+        osd::register(&mut engine);
+        borders::register(&mut engine);
+        animations::register(&mut engine);
 
         let anodize = anodize::register(
             &mut scope,
@@ -93,6 +110,26 @@ impl ConfigVM {
             .key_action(self, key, state, keys_pressed)
     }
 
+    pub fn gesture_bound(&self, kind: GestureKind, fingers: u32) -> bool {
+        self.anodize.gestures.is_bound(kind, fingers)
+    }
+
+    pub fn workspace_swipe(&self, fingers: u32) -> bool {
+        self.anodize.gestures.is_workspace_swipe(fingers)
+    }
+
+    pub fn gesture_action(&self, gesture: Gesture) -> bool {
+        self.anodize.gestures.gesture_action(self, gesture)
+    }
+
+    pub fn touch_output(&self) -> Option<String> {
+        self.anodize.input.touch_output()
+    }
+
+    pub fn tablet_output(&self) -> Option<String> {
+        self.anodize.input.tablet_output()
+    }
+
     pub fn output_rearrange(&self, outputs: Vec<Output>) -> Option<Vec<(i32, i32)>> {
         let inner = &*self.inner.borrow();
         self.anodize
@@ -107,6 +144,17 @@ impl ConfigVM {
             .on_mode_select(&inner.engine, &inner.ast, desc, modes)
     }
 
+    pub fn decorations(&self) -> Decorations {
+        self.anodize.windows.decorations()
+    }
+
+    pub fn extend_window_menu(&self, window: &crate::window::Window, menu: &Menu) {
+        let inner = &*self.inner.borrow();
+        self.anodize
+            .windows
+            .on_menu(&inner.engine, &inner.ast, window, menu);
+    }
+
     pub fn output_new(&self, output: Output) {
         let inner = &*self.inner.borrow();
         self.anodize
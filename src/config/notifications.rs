@@ -0,0 +1,470 @@
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use calloop::channel::Sender;
+use egui::{Align2, CtxRef};
+use rhai::plugin::*;
+use rhai::{Array, FnPtr, INT};
+
+use super::eventloop::ConfigEvent;
+
+const DEFAULT_TIMEOUT: u64 = 5000;
+const DEFAULT_MAX_HISTORY: usize = 50;
+const TOAST_WIDTH: f32 = 300.0;
+const MARGIN: f32 = 10.0;
+
+/// Corner of the output the toasts are stacked in
+#[derive(Debug, Clone, Copy)]
+pub enum Corner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl Corner {
+    fn anchor(&self) -> (Align2, egui::Vec2) {
+        match self {
+            Corner::TopLeft => (Align2::LEFT_TOP, egui::vec2(MARGIN, MARGIN)),
+            Corner::TopRight => (Align2::RIGHT_TOP, egui::vec2(-MARGIN, MARGIN)),
+            Corner::BottomLeft => (Align2::LEFT_BOTTOM, egui::vec2(MARGIN, -MARGIN)),
+            Corner::BottomRight => (Align2::RIGHT_BOTTOM, egui::vec2(-MARGIN, -MARGIN)),
+        }
+    }
+
+    fn bottom(&self) -> bool {
+        matches!(self, Corner::BottomLeft | Corner::BottomRight)
+    }
+}
+
+#[derive(Debug)]
+struct NotificationInner {
+    id: u32,
+    summary: String,
+    body: String,
+    /// Stays until closed when `None`
+    timeout: Option<Duration>,
+    created: Instant,
+    actions: Vec<(String, FnPtr)>,
+    closed: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct Notification(Rc<RefCell<NotificationInner>>);
+
+impl Notification {
+    fn expired(&self, now: Instant) -> bool {
+        let inner = self.0.borrow();
+        inner.closed
+            || inner
+                .timeout
+                .map(|timeout| now.duration_since(inner.created) >= timeout)
+                .unwrap_or(false)
+    }
+
+    fn render(&self, ui: &mut egui::Ui, config_tx: &Sender<ConfigEvent>) {
+        let mut inner = self.0.borrow_mut();
+
+        egui::Frame::popup(ui.style()).show(ui, |ui| {
+            ui.set_width(TOAST_WIDTH);
+
+            ui.horizontal(|ui| {
+                ui.strong(inner.summary.as_str());
+                ui.with_layout(egui::Layout::right_to_left(), |ui| {
+                    if ui.small_button("x").clicked() {
+                        inner.closed = true;
+                    }
+                });
+            });
+
+            if !inner.body.is_empty() {
+                ui.label(inner.body.as_str());
+            }
+
+            if !inner.actions.is_empty() {
+                let mut invoked = false;
+                ui.horizontal(|ui| {
+                    for (label, action) in inner.actions.iter() {
+                        if ui.button(label).clicked() {
+                            config_tx.send(ConfigEvent::Shell(action.clone())).unwrap();
+                            invoked = true;
+                        }
+                    }
+                });
+                inner.closed |= invoked;
+            }
+        });
+    }
+}
+
+#[derive(Debug)]
+struct NotificationsInner {
+    next_id: u32,
+    active: Vec<Notification>,
+    /// Newest first
+    history: VecDeque<Notification>,
+    max_history: usize,
+    corner: Corner,
+    /// Name of the output showing the toasts, the first output when `None`
+    output: Option<String>,
+}
+
+/// Notifications sent from the config, shown as toasts by the egui shell.
+/// The shell only renders them, so they can be used without any notification bus.
+#[derive(Debug, Clone)]
+pub struct Notifications {
+    inner: Rc<RefCell<NotificationsInner>>,
+}
+
+impl Notifications {
+    pub fn new() -> Self {
+        Self {
+            inner: Rc::new(RefCell::new(NotificationsInner {
+                next_id: 1,
+                active: Vec::new(),
+                history: VecDeque::new(),
+                max_history: DEFAULT_MAX_HISTORY,
+                corner: Corner::TopRight,
+                output: None,
+            })),
+        }
+    }
+
+    pub fn notify(&self, summary: String, body: String, timeout: Option<Duration>) -> Notification {
+        let mut inner = self.inner.borrow_mut();
+
+        let notification = Notification(Rc::new(RefCell::new(NotificationInner {
+            id: inner.next_id,
+            summary,
+            body,
+            timeout,
+            created: Instant::now(),
+            actions: Vec::new(),
+            closed: false,
+        })));
+        inner.next_id += 1;
+        inner.active.push(notification.clone());
+
+        notification
+    }
+
+    /// Whether the toasts are shown on the output, `first` tells if it is the first output
+    pub fn shown_on(&self, output: &str, first: bool) -> bool {
+        match &self.inner.borrow().output {
+            Some(name) => name == output,
+            None => first,
+        }
+    }
+
+    /// Move closed and timed out notifications to the history
+    fn expire(&self) {
+        self.expire_at(Instant::now());
+    }
+
+    fn expire_at(&self, now: Instant) {
+        let mut inner = self.inner.borrow_mut();
+
+        let (expired, active): (Vec<_>, Vec<_>) =
+            inner.active.drain(..).partition(|n| n.expired(now));
+        inner.active = active;
+
+        for notification in expired {
+            inner.history.push_front(notification);
+        }
+        let max_history = inner.max_history;
+        inner.history.truncate(max_history);
+    }
+
+    pub fn render(&self, ctx: &CtxRef, config_tx: &Sender<ConfigEvent>) {
+        self.expire();
+
+        let inner = self.inner.borrow();
+        if inner.active.is_empty() {
+            return;
+        }
+
+        let (align, offset) = inner.corner.anchor();
+        egui::Area::new("notifications")
+            .anchor(align, offset)
+            .order(egui::Order::Foreground)
+            .show(ctx, |ui| {
+                // Newest notification is the one closest to the corner
+                if inner.corner.bottom() {
+                    for notification in inner.active.iter() {
+                        notification.render(ui, config_tx);
+                    }
+                } else {
+                    for notification in inner.active.iter().rev() {
+                        notification.render(ui, config_tx);
+                    }
+                }
+            });
+
+        // Keep rendering so timeouts are noticed
+        ctx.request_repaint();
+    }
+}
+
+impl Default for Notifications {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Timeout given in ms by scripts, 0 or less means no timeout
+pub fn timeout_from_ms(timeout: INT) -> Option<Duration> {
+    if timeout > 0 {
+        Some(Duration::from_millis(timeout as _))
+    } else {
+        None
+    }
+}
+
+#[export_module]
+pub mod notifications {
+    /// Show a notification with the default timeout
+    #[rhai_fn(global)]
+    pub fn notify(
+        notifications: &mut Notifications,
+        summary: String,
+        body: String,
+    ) -> Notification {
+        notifications.notify(summary, body, Some(Duration::from_millis(DEFAULT_TIMEOUT)))
+    }
+
+    /// Show a notification, it stays until closed when the timeout (in ms) is 0
+    #[rhai_fn(global, name = "notify")]
+    pub fn notify_with_timeout(
+        notifications: &mut Notifications,
+        summary: String,
+        body: String,
+        timeout: INT,
+    ) -> Notification {
+        notifications.notify(summary, body, timeout_from_ms(timeout))
+    }
+
+    #[rhai_fn(set = "corner", pure)]
+    pub fn set_corner(notifications: &mut Notifications, corner: Corner) {
+        notifications.inner.borrow_mut().corner = corner;
+    }
+
+    /// Name of the output to show the toasts on, empty for the first output
+    #[rhai_fn(get = "output", pure)]
+    pub fn output(notifications: &mut Notifications) -> String {
+        notifications
+            .inner
+            .borrow()
+            .output
+            .clone()
+            .unwrap_or_default()
+    }
+
+    #[rhai_fn(set = "output", pure)]
+    pub fn set_output(notifications: &mut Notifications, output: String) {
+        notifications.inner.borrow_mut().output = if output.is_empty() {
+            None
+        } else {
+            Some(output)
+        };
+    }
+
+    #[rhai_fn(get = "active", pure)]
+    pub fn active(notifications: &mut Notifications) -> Array {
+        notifications
+            .inner
+            .borrow()
+            .active
+            .iter()
+            .cloned()
+            .map(Dynamic::from)
+            .collect()
+    }
+
+    /// Closed and timed out notifications, newest first
+    #[rhai_fn(get = "history", pure)]
+    pub fn history(notifications: &mut Notifications) -> Array {
+        notifications
+            .inner
+            .borrow()
+            .history
+            .iter()
+            .cloned()
+            .map(Dynamic::from)
+            .collect()
+    }
+
+    #[rhai_fn(get = "max_history", pure)]
+    pub fn max_history(notifications: &mut Notifications) -> INT {
+        notifications.inner.borrow().max_history as _
+    }
+
+    #[rhai_fn(set = "max_history", pure)]
+    pub fn set_max_history(notifications: &mut Notifications, max_history: INT) {
+        let mut inner = notifications.inner.borrow_mut();
+        inner.max_history = max_history.max(0) as _;
+        inner.history.truncate(max_history.max(0) as _);
+    }
+
+    #[rhai_fn(global)]
+    pub fn clear_history(notifications: &mut Notifications) {
+        notifications.inner.borrow_mut().history.clear();
+    }
+
+    #[rhai_fn(global)]
+    pub fn close_all(notifications: &mut Notifications) {
+        for notification in notifications.inner.borrow().active.iter() {
+            notification.0.borrow_mut().closed = true;
+        }
+    }
+
+    #[rhai_fn(get = "id", pure)]
+    pub fn id(notification: &mut Notification) -> INT {
+        notification.0.borrow().id as _
+    }
+
+    #[rhai_fn(get = "summary", pure)]
+    pub fn summary(notification: &mut Notification) -> String {
+        notification.0.borrow().summary.clone()
+    }
+
+    #[rhai_fn(get = "body", pure)]
+    pub fn body(notification: &mut Notification) -> String {
+        notification.0.borrow().body.clone()
+    }
+
+    /// Button shown on the notification, clicking it calls the function and closes the notification
+    #[rhai_fn(global)]
+    pub fn add_action(notification: &mut Notification, label: String, action: FnPtr) {
+        notification.0.borrow_mut().actions.push((label, action));
+    }
+
+    #[rhai_fn(global)]
+    pub fn close(notification: &mut Notification) {
+        notification.0.borrow_mut().closed = true;
+    }
+}
+
+#[export_module]
+pub mod corner {
+    pub fn top_left() -> Corner {
+        Corner::TopLeft
+    }
+
+    pub fn top_right() -> Corner {
+        Corner::TopRight
+    }
+
+    pub fn bottom_left() -> Corner {
+        Corner::BottomLeft
+    }
+
+    pub fn bottom_right() -> Corner {
+        Corner::BottomRight
+    }
+}
+
+pub fn register(engine: &mut Engine) {
+    let notifications_module = exported_module!(notifications);
+    let corner_module = exported_module!(corner);
+    engine
+        .register_static_module("notifications", notifications_module.into())
+        .register_static_module("corner", corner_module.into())
+        .register_type::<Notifications>()
+        .register_type::<Notification>()
+        .register_type::<Corner>();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::notifications::{
+        close, close_all, max_history, output, set_max_history, set_output,
+    };
+    use super::*;
+
+    fn ids<'a>(notifications: impl Iterator<Item = &'a Notification>) -> Vec<u32> {
+        notifications.map(|n| n.0.borrow().id).collect()
+    }
+
+    fn active(notifications: &Notifications) -> Vec<u32> {
+        ids(notifications.inner.borrow().active.iter())
+    }
+
+    fn history(notifications: &Notifications) -> Vec<u32> {
+        ids(notifications.inner.borrow().history.iter())
+    }
+
+    #[test]
+    fn timeout_expiry() {
+        let notifications = Notifications::new();
+        let timed = notifications.notify("a".into(), "".into(), timeout_from_ms(100));
+        let sticky = notifications.notify("b".into(), "".into(), timeout_from_ms(0));
+        let created = timed.0.borrow().created;
+
+        notifications.expire_at(created + Duration::from_millis(99));
+        assert_eq!(active(&notifications), vec![1, 2]);
+
+        notifications.expire_at(created + Duration::from_millis(100));
+        assert_eq!(active(&notifications), vec![2]);
+        assert_eq!(history(&notifications), vec![1]);
+
+        notifications.expire_at(sticky.0.borrow().created + Duration::from_secs(3600));
+        assert_eq!(active(&notifications), vec![2]);
+    }
+
+    #[test]
+    fn closed_move_to_history() {
+        let mut notifications = Notifications::new();
+        let mut first = notifications.notify("a".into(), "".into(), None);
+        notifications.notify("b".into(), "".into(), None);
+        let mut third = notifications.notify("c".into(), "".into(), None);
+
+        close(&mut first);
+        close(&mut third);
+        notifications.expire();
+        assert_eq!(active(&notifications), vec![2]);
+        // Newest first
+        assert_eq!(history(&notifications), vec![3, 1]);
+
+        close_all(&mut notifications);
+        notifications.expire();
+        assert!(active(&notifications).is_empty());
+        assert_eq!(history(&notifications), vec![2, 3, 1]);
+    }
+
+    #[test]
+    fn max_history_truncation() {
+        let mut notifications = Notifications::new();
+        set_max_history(&mut notifications, 2);
+
+        for _ in 0..3 {
+            notifications.notify("a".into(), "".into(), None);
+        }
+        close_all(&mut notifications);
+        notifications.expire();
+        assert_eq!(history(&notifications), vec![3, 2]);
+
+        set_max_history(&mut notifications, 1);
+        assert_eq!(history(&notifications), vec![3]);
+
+        set_max_history(&mut notifications, -1);
+        assert_eq!(max_history(&mut notifications), 0);
+        assert!(history(&notifications).is_empty());
+    }
+
+    #[test]
+    fn shown_on_output() {
+        let mut notifications = Notifications::new();
+        assert!(notifications.shown_on("eDP-1", true));
+        assert!(!notifications.shown_on("HDMI-A-1", false));
+
+        set_output(&mut notifications, "HDMI-A-1".into());
+        assert!(!notifications.shown_on("eDP-1", true));
+        assert!(notifications.shown_on("HDMI-A-1", false));
+
+        set_output(&mut notifications, "".into());
+        assert_eq!(output(&mut notifications), "");
+        assert!(notifications.shown_on("eDP-1", true));
+    }
+}
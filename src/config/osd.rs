@@ -0,0 +1,149 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use egui::{Align2, CtxRef};
+use rhai::plugin::*;
+use rhai::{FLOAT, INT};
+
+use crate::output_manager::OutputManager;
+
+/// Part of the timeout during which the overlay fades out
+const FADE_OUT: Duration = Duration::from_millis(300);
+const DEFAULT_TIMEOUT: u64 = 1500;
+const WIDTH: f32 = 250.0;
+
+#[derive(Debug)]
+struct Overlay {
+    text: String,
+    /// Between 0.0 and 1.0, shown as a bar
+    value: Option<f32>,
+    until: Instant,
+}
+
+/// Transient overlay in the middle of every output, a new one replaces the current one.
+/// It is not interactable, so the pointer goes through it.
+#[derive(Debug, Clone)]
+pub struct Osd {
+    overlay: Rc<RefCell<Option<Overlay>>>,
+    output_map: OutputManager,
+}
+
+impl Osd {
+    pub fn new(output_map: OutputManager) -> Self {
+        Self {
+            overlay: Default::default(),
+            output_map,
+        }
+    }
+
+    pub fn show(&self, text: String, value: Option<f32>, timeout: Duration) {
+        *self.overlay.borrow_mut() = Some(Overlay {
+            text,
+            value: value.map(|value| value.max(0.0).min(1.0)),
+            until: Instant::now() + timeout,
+        });
+        self.request_repaint();
+    }
+
+    pub fn hide(&self) {
+        if self.overlay.borrow_mut().take().is_some() {
+            self.request_repaint();
+        }
+    }
+
+    /// The overlay is usually shown from a keybinding, which gives egui no input to run on
+    fn request_repaint(&self) {
+        for output in self.output_map.outputs().iter() {
+            output.egui().context().request_repaint();
+        }
+    }
+
+    pub fn render(&self, ctx: &CtxRef) {
+        let now = Instant::now();
+
+        let mut overlay = self.overlay.borrow_mut();
+        if overlay.as_ref().map(|o| o.until <= now).unwrap_or(false) {
+            *overlay = None;
+        }
+
+        let overlay = match overlay.as_ref() {
+            Some(overlay) => overlay,
+            None => return,
+        };
+
+        let remaining = overlay.until.duration_since(now);
+        let alpha = (remaining.as_secs_f32() / FADE_OUT.as_secs_f32()).min(1.0);
+
+        let mut frame = egui::Frame::popup(&ctx.style());
+        frame.fill = frame.fill.linear_multiply(alpha);
+        frame.stroke.color = frame.stroke.color.linear_multiply(alpha);
+        frame.shadow.color = frame.shadow.color.linear_multiply(alpha);
+
+        egui::Area::new("osd")
+            .anchor(Align2::CENTER_CENTER, (0.0, 0.0))
+            .order(egui::Order::Foreground)
+            .interactable(false)
+            .show(ctx, |ui| {
+                frame.show(ui, |ui| {
+                    let visuals = ui.visuals_mut();
+                    visuals.override_text_color = Some(visuals.text_color().linear_multiply(alpha));
+                    visuals.selection.bg_fill = visuals.selection.bg_fill.linear_multiply(alpha);
+                    visuals.extreme_bg_color = visuals.extreme_bg_color.linear_multiply(alpha);
+
+                    ui.set_width(WIDTH);
+                    ui.vertical_centered(|ui| {
+                        ui.heading(overlay.text.as_str());
+                        if let Some(value) = overlay.value {
+                            ui.add(egui::ProgressBar::new(value));
+                        }
+                    });
+                });
+            });
+
+        // Keep rendering until the overlay is gone
+        ctx.request_repaint();
+    }
+}
+
+fn timeout_from_ms(timeout: INT) -> Duration {
+    Duration::from_millis(timeout.max(0) as _)
+}
+
+#[export_module]
+pub mod osd {
+    /// Show the text with a bar filled up to `value`, between 0.0 and 1.0, for `timeout` ms
+    #[rhai_fn(global, name = "osd", name = "show")]
+    pub fn osd(osd: &mut Osd, text: String, value: FLOAT, timeout: INT) {
+        osd.show(text, Some(value as _), timeout_from_ms(timeout));
+    }
+
+    /// Same with the value given as a percentage
+    #[rhai_fn(global, name = "osd", name = "show")]
+    pub fn osd_percent(osd: &mut Osd, text: String, value: INT, timeout: INT) {
+        osd.show(text, Some(value as f32 / 100.0), timeout_from_ms(timeout));
+    }
+
+    /// Show only the text, for `timeout` ms
+    #[rhai_fn(global, name = "osd", name = "show")]
+    pub fn osd_text(osd: &mut Osd, text: String, timeout: INT) {
+        osd.show(text, None, timeout_from_ms(timeout));
+    }
+
+    #[rhai_fn(global, name = "osd", name = "show")]
+    pub fn osd_text_default(osd: &mut Osd, text: String) {
+        osd.show(text, None, Duration::from_millis(DEFAULT_TIMEOUT));
+    }
+
+    #[rhai_fn(global, name = "hide_osd", name = "hide")]
+    pub fn hide_osd(osd: &mut Osd) {
+        osd.hide();
+    }
+}
+
+pub fn register(engine: &mut Engine) {
+    let osd_module = exported_module!(osd);
+    engine
+        .register_static_module("osd", osd_module.into())
+        .register_type::<Osd>();
+}
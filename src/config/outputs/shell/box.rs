@@ -7,8 +7,9 @@ use rhai::Engine;
 use rhai::{plugin::*, FLOAT, INT};
 
 use crate::config::eventloop::ConfigEvent;
+use crate::config::theme::StyleOverride;
 
-use super::container::{Container, Layout};
+use super::container::{Align, Arrangement, Container, Layout, OutputState};
 use super::widget::Widget;
 
 thread_local! {
@@ -23,11 +24,15 @@ pub struct BoxInner {
     x: f32,
     y: f32,
     layout: Layout,
+    arrangement: Arrangement,
+    style: StyleOverride,
     widgets: Vec<Rc<dyn Widget>>,
     alpha: f32,
     background: bool,
     visable: bool,
     scroll: bool,
+    /// Bumped on every restack, a new egui id puts the window on top of the existing ones
+    stacking: Cell<u64>,
 }
 
 #[derive(Clone)]
@@ -43,17 +48,20 @@ impl Box {
 
             Self {
                 inner: Rc::new(RefCell::new(BoxInner {
-                    id: format!("{}", id),
+                    id: format!("box-{}", id),
                     w,
                     h,
                     x,
                     y,
                     layout,
+                    arrangement: Arrangement::new(),
+                    style: StyleOverride::default(),
                     widgets: vec![],
                     alpha: 1.0,
                     background: true,
                     visable: true,
                     scroll: true,
+                    stacking: Cell::new(0),
                 })),
             }
         })
@@ -61,7 +69,7 @@ impl Box {
 }
 
 impl Container for Box {
-    fn render(&self, ctx: &CtxRef, config_tx: &Sender<ConfigEvent>) {
+    fn render(&self, ctx: &CtxRef, _output: &OutputState, config_tx: &Sender<ConfigEvent>) {
         let inner = self.inner.borrow();
         if inner.visable {
             let mut frame = egui::Frame::window(&ctx.style());
@@ -69,9 +77,11 @@ impl Container for Box {
                 frame.fill = Color32::TRANSPARENT;
                 frame.stroke.width = 0.0;
             } else {
+                inner.style.apply_frame(&mut frame);
                 frame.fill[3] = (inner.alpha * 255.0) as u8;
             }
             egui::containers::Window::new(&inner.id)
+                .id(egui::Id::new(&inner.id).with(inner.stacking.get()))
                 .resize(|r| r.with_stroke(true)) //BUG : https://github.com/emilk/egui/issues/498, this work arounds it
                 .frame(frame)
                 .fixed_pos([inner.x, inner.y])
@@ -79,24 +89,42 @@ impl Container for Box {
                 .title_bar(false)
                 .collapsible(false)
                 .scroll2([inner.scroll, inner.scroll])
-                .show(ctx, |ui| match inner.layout {
-                    Layout::Horizontal => ui.horizontal(|ui| {
-                        for widget in &inner.widgets {
-                            widget.render(ui, config_tx);
-                        }
-                    }),
-                    Layout::Vertical => ui.vertical(|ui| {
-                        for widget in &inner.widgets {
-                            widget.render(ui, config_tx);
-                        }
-                    }),
+                .show(ctx, |ui| {
+                    inner.style.apply(ui);
+                    inner
+                        .layout
+                        .render(ui, &inner.widgets, &inner.arrangement, config_tx);
                 });
         }
     }
+
+    fn id(&self) -> String {
+        self.inner.borrow().id.clone()
+    }
+
+    fn to_dynamic(&self) -> Dynamic {
+        Dynamic::from(self.clone())
+    }
+
+    fn restack(&self) {
+        let inner = self.inner.borrow();
+        inner.stacking.set(inner.stacking.get() + 1);
+    }
 }
 
 #[export_module]
 pub mod r#box {
+    /// Used by the shell to find, replace or remove the box
+    #[rhai_fn(get = "id", pure)]
+    pub fn id(r#box: &mut Box) -> String {
+        r#box.inner.borrow().id.clone()
+    }
+
+    #[rhai_fn(set = "id", pure)]
+    pub fn set_id(r#box: &mut Box, id: String) {
+        r#box.inner.borrow_mut().id = id;
+    }
+
     #[rhai_fn(get = "w", pure)]
     pub fn w(r#box: &mut Box) -> INT {
         r#box.inner.borrow().w as _
@@ -147,6 +175,41 @@ pub mod r#box {
         r#box.inner.borrow_mut().layout = layout;
     }
 
+    #[rhai_fn(get = "align", pure)]
+    pub fn align(r#box: &mut Box) -> Align {
+        r#box.inner.borrow().arrangement.align
+    }
+
+    #[rhai_fn(set = "align", pure)]
+    pub fn set_align(r#box: &mut Box, align: Align) {
+        r#box.inner.borrow_mut().arrangement.align = align;
+    }
+
+    #[rhai_fn(set = "spacing", pure)]
+    pub fn set_spacing(r#box: &mut Box, spacing: FLOAT) {
+        r#box.inner.borrow_mut().arrangement.spacing = Some(spacing as _);
+    }
+
+    #[rhai_fn(get = "padding", pure)]
+    pub fn padding(r#box: &mut Box) -> FLOAT {
+        r#box.inner.borrow().arrangement.padding as _
+    }
+
+    #[rhai_fn(set = "padding", pure)]
+    pub fn set_padding(r#box: &mut Box, padding: FLOAT) {
+        r#box.inner.borrow_mut().arrangement.padding = padding as _;
+    }
+
+    #[rhai_fn(get = "style", pure)]
+    pub fn style(r#box: &mut Box) -> StyleOverride {
+        r#box.inner.borrow().style.clone()
+    }
+
+    #[rhai_fn(set = "style", pure)]
+    pub fn set_style(r#box: &mut Box, style: StyleOverride) {
+        r#box.inner.borrow_mut().style = style;
+    }
+
     #[rhai_fn(get = "alpha", pure)]
     pub fn alpha(r#box: &mut Box) -> INT {
         r#box.inner.borrow_mut().alpha as _
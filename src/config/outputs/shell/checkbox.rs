@@ -0,0 +1,155 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use egui::{Response, Sense, Ui};
+use rhai::plugin::*;
+use rhai::{Engine, FnPtr};
+
+use super::widget::*;
+
+/// iOS style on/off switch, same as the one in the egui demo
+fn toggle_switch(ui: &mut Ui, on: &mut bool) -> Response {
+    let desired_size = ui.spacing().interact_size.y * egui::vec2(2.0, 1.0);
+    let (rect, mut response) = ui.allocate_exact_size(desired_size, Sense::click());
+
+    if response.clicked() {
+        *on = !*on;
+        response.mark_changed();
+    }
+
+    let how_on = ui.ctx().animate_bool(response.id, *on);
+    let visuals = ui.style().interact_selectable(&response, *on);
+    let rect = rect.expand(visuals.expansion);
+    let radius = 0.5 * rect.height();
+
+    ui.painter()
+        .rect(rect, radius, visuals.bg_fill, visuals.bg_stroke);
+
+    let circle_x = egui::lerp((rect.left() + radius)..=(rect.right() - radius), how_on);
+    let center = egui::pos2(circle_x, rect.center().y);
+    ui.painter()
+        .circle(center, 0.75 * radius, visuals.bg_fill, visuals.fg_stroke);
+
+    response
+}
+
+#[derive(Debug)]
+struct CheckboxInner {
+    label: String,
+    checked: bool,
+    toggle: bool,
+    on_change: Option<FnPtr>,
+    updated: bool,
+}
+
+/// Boolean widget, rendered either as a checkbox or as a toggle switch
+#[derive(Debug, Clone)]
+pub struct Checkbox(Rc<RefCell<CheckboxInner>>);
+
+impl Checkbox {
+    pub fn new(label: String, checked: bool) -> Self {
+        Self::with_style(label, checked, false)
+    }
+
+    pub fn toggle(label: String, checked: bool) -> Self {
+        Self::with_style(label, checked, true)
+    }
+
+    fn with_style(label: String, checked: bool, toggle: bool) -> Self {
+        Self(Rc::new(RefCell::new(CheckboxInner {
+            label,
+            checked,
+            toggle,
+            on_change: None,
+            updated: true,
+        })))
+    }
+}
+
+impl Widget for Checkbox {
+    fn render(&self, ui: &mut Ui, config_tx: &Sender<ConfigEvent>) {
+        let mut inner = self.0.borrow_mut();
+        let inner = &mut *inner;
+
+        if inner.updated {
+            ui.ctx().request_repaint();
+            inner.updated = false;
+        }
+
+        let response = if inner.toggle {
+            let checked = &mut inner.checked;
+            let label = &inner.label;
+            ui.horizontal(|ui| {
+                let response = toggle_switch(ui, checked);
+                if !label.is_empty() {
+                    ui.label(label);
+                }
+                response
+            })
+            .inner
+        } else {
+            ui.checkbox(&mut inner.checked, inner.label.as_str())
+        };
+
+        if response.changed() {
+            if let Some(on_change) = &inner.on_change {
+                config_tx
+                    .send(ConfigEvent::ShellWithArgs(
+                        on_change.clone(),
+                        vec![inner.checked.into()],
+                    ))
+                    .unwrap();
+            }
+        }
+    }
+}
+
+#[export_module]
+pub mod checkbox {
+    #[rhai_fn(get = "checked", pure)]
+    pub fn checked(checkbox: &mut Checkbox) -> bool {
+        checkbox.0.borrow().checked
+    }
+
+    #[rhai_fn(set = "checked", pure)]
+    pub fn set_checked(checkbox: &mut Checkbox, checked: bool) {
+        update(checkbox, checked);
+    }
+
+    /// Set the state without calling `on_change`
+    #[rhai_fn(global)]
+    pub fn update(checkbox: &mut Checkbox, checked: bool) {
+        let mut inner = checkbox.0.borrow_mut();
+        inner.checked = checked;
+        inner.updated = true;
+    }
+
+    #[rhai_fn(get = "label", pure)]
+    pub fn label(checkbox: &mut Checkbox) -> String {
+        checkbox.0.borrow().label.clone()
+    }
+
+    #[rhai_fn(set = "label", pure)]
+    pub fn set_label(checkbox: &mut Checkbox, label: String) {
+        let mut inner = checkbox.0.borrow_mut();
+        inner.label = label;
+        inner.updated = true;
+    }
+
+    #[rhai_fn(global)]
+    pub fn on_change(checkbox: &mut Checkbox, on_change: FnPtr) {
+        checkbox.0.borrow_mut().on_change = Some(on_change);
+    }
+
+    #[rhai_fn(global)]
+    pub fn convert(checkbox: &mut Checkbox) -> Rc<dyn Widget> {
+        Rc::new(checkbox.clone())
+    }
+}
+
+pub fn register(engine: &mut Engine) {
+    let checkbox_module = exported_module!(checkbox);
+    engine
+        .register_static_module("checkbox", checkbox_module.into())
+        .register_type::<Checkbox>();
+}
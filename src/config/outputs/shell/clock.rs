@@ -0,0 +1,195 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use chrono::format::{Fixed, Item, Numeric, StrftimeItems};
+use chrono::{FixedOffset, Local, Utc};
+use egui::Ui;
+use rhai::plugin::*;
+use rhai::Engine;
+
+use super::ticker::{self, Tick};
+use super::widget::*;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Timezone {
+    Local,
+    Utc,
+    Fixed(FixedOffset),
+}
+
+impl Timezone {
+    /// Parse `local`, `utc` or a fixed offset like `+02:00`
+    fn parse(timezone: &str) -> Option<Self> {
+        match timezone.to_lowercase().as_str() {
+            "local" => Some(Self::Local),
+            "utc" => Some(Self::Utc),
+            offset => {
+                let (sign, offset) = match offset.split_at(1) {
+                    ("+", offset) => (1, offset),
+                    ("-", offset) => (-1, offset),
+                    _ => return None,
+                };
+
+                let (hours, minutes) = offset.split_once(':').unwrap_or((offset, "0"));
+                let seconds = hours.parse::<i32>().ok()? * 3600 + minutes.parse::<i32>().ok()? * 60;
+
+                FixedOffset::east_opt(sign * seconds).map(Self::Fixed)
+            }
+        }
+    }
+
+    fn name(&self) -> String {
+        match self {
+            Self::Local => "local".into(),
+            Self::Utc => "utc".into(),
+            Self::Fixed(offset) => format!("{}", offset),
+        }
+    }
+
+    fn format(&self, format: &str) -> String {
+        match self {
+            Self::Local => Local::now().format(format).to_string(),
+            Self::Utc => Utc::now().format(format).to_string(),
+            Self::Fixed(offset) => Utc::now().with_timezone(offset).format(format).to_string(),
+        }
+    }
+}
+
+const DEFAULT_FORMAT: &str = "%H:%M";
+
+/// chrono panics while formatting with an invalid format string, so check it up front
+fn valid_format(format: String) -> Option<String> {
+    if StrftimeItems::new(&format).any(|item| matches!(item, Item::Error)) {
+        warn!("invalid clock format: {}", format);
+        None
+    } else {
+        Some(format)
+    }
+}
+
+/// Formats showing seconds change every second, the others every minute
+fn shows_seconds(format: &str) -> bool {
+    StrftimeItems::new(format).any(|item| {
+        matches!(
+            item,
+            Item::Numeric(
+                Numeric::Second | Numeric::Nanosecond | Numeric::Timestamp,
+                _
+            ) | Item::Fixed(
+                Fixed::Nanosecond
+                    | Fixed::Nanosecond3
+                    | Fixed::Nanosecond6
+                    | Fixed::Nanosecond9
+                    | Fixed::RFC2822
+                    | Fixed::RFC3339
+            )
+        )
+    })
+}
+
+#[derive(Debug)]
+struct ClockInner {
+    format: String,
+    timezone: Timezone,
+    text: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct Clock(Rc<RefCell<ClockInner>>);
+
+impl ClockInner {
+    /// Format the current time, returns whether the text changed
+    fn update(&mut self) -> bool {
+        let text = self.timezone.format(&self.format);
+        let changed = text != self.text;
+        self.text = text;
+        changed
+    }
+}
+
+impl Tick for RefCell<ClockInner> {
+    fn tick(&self) -> (bool, Duration) {
+        let mut inner = self.borrow_mut();
+        let changed = inner.update();
+
+        // Wake up right when the text changes again
+        let unit = if shows_seconds(&inner.format) {
+            1000
+        } else {
+            60_000
+        };
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+
+        (changed, Duration::from_millis((unit - now % unit) as u64))
+    }
+}
+
+impl Clock {
+    pub fn new(format: String) -> Self {
+        let mut inner = ClockInner {
+            format: valid_format(format).unwrap_or_else(|| DEFAULT_FORMAT.into()),
+            timezone: Timezone::Local,
+            text: String::new(),
+        };
+        inner.update();
+
+        let clock = Self(Rc::new(RefCell::new(inner)));
+        ticker::start(clock.0.clone());
+        clock
+    }
+}
+
+impl Widget for Clock {
+    fn render(&self, ui: &mut Ui, _config_tx: &Sender<ConfigEvent>) {
+        ui.label(&self.0.borrow().text);
+    }
+}
+
+#[export_module]
+pub mod clock {
+    #[rhai_fn(get = "format", pure)]
+    pub fn format(clock: &mut Clock) -> String {
+        clock.0.borrow().format.clone()
+    }
+
+    #[rhai_fn(set = "format", pure)]
+    pub fn set_format(clock: &mut Clock, format: String) {
+        if let Some(format) = valid_format(format) {
+            let mut inner = clock.0.borrow_mut();
+            inner.format = format;
+            inner.update();
+        }
+    }
+
+    #[rhai_fn(get = "timezone", pure)]
+    pub fn timezone(clock: &mut Clock) -> String {
+        clock.0.borrow().timezone.name()
+    }
+
+    #[rhai_fn(set = "timezone", pure)]
+    pub fn set_timezone(clock: &mut Clock, timezone: &str) {
+        if let Some(timezone) = Timezone::parse(timezone) {
+            let mut inner = clock.0.borrow_mut();
+            inner.timezone = timezone;
+            inner.update();
+        } else {
+            warn!("unknown timezone: {}, ignoring", timezone);
+        }
+    }
+
+    #[rhai_fn(global)]
+    pub fn convert(clock: &mut Clock) -> Rc<dyn Widget> {
+        Rc::new(clock.clone())
+    }
+}
+
+pub fn register(engine: &mut Engine) {
+    let clock_module = exported_module!(clock);
+    engine
+        .register_static_module("clock", clock_module.into())
+        .register_type::<Clock>();
+}
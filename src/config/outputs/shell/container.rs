@@ -1,3 +1,4 @@
+use std::cell::Cell;
 use std::rc::Rc;
 
 pub use calloop::channel::Sender;
@@ -10,31 +11,167 @@ use super::panel::{Panel as PanelContainer, PanelPosition};
 use super::r#box::Box as BoxContainer;
 use super::widget::Widget;
 
+thread_local! {
+    static ARRANGEMENT_ID: Cell<u64> = Cell::new(0);
+}
+
 #[derive(Debug, Clone)]
 pub enum Layout {
     Vertical,
     Horizontal,
+    Grid(usize),
+    /// Equal width columns, widget `i` goes into column `i % columns`
+    Columns(usize),
+}
+
+/// Position of the content along the main axis of the layout
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Align {
+    Start,
+    Center,
+    End,
+}
+
+/// Alignment, spacing and padding of the widgets in a container
+#[derive(Debug)]
+pub struct Arrangement {
+    id: egui::Id,
+    pub align: Align,
+    pub spacing: Option<f32>,
+    pub padding: f32,
+    /// Main axis size of the content in the last frame, used to center or end align it
+    content_size: Cell<f32>,
+}
+
+impl Arrangement {
+    pub fn new() -> Self {
+        let id = ARRANGEMENT_ID.with(|id| {
+            let current = id.get();
+            id.set(current + 1);
+            current
+        });
+
+        Self {
+            id: egui::Id::new("arrangement").with(id),
+            align: Align::Start,
+            spacing: None,
+            padding: 0.0,
+            content_size: Cell::new(0.0),
+        }
+    }
+
+    fn aligned(&self, ui: &mut Ui, horizontal: bool, add_contents: impl FnOnce(&mut Ui)) {
+        let available = if horizontal {
+            ui.available_width()
+        } else {
+            ui.available_height()
+        };
+
+        let offset = match self.align {
+            Align::Start => 0.0,
+            Align::Center => (available - self.content_size.get()) / 2.0,
+            Align::End => available - self.content_size.get(),
+        };
+        if offset > 0.0 {
+            ui.add_space(offset);
+        }
+
+        let size = ui.scope(add_contents).response.rect.size();
+        let size = if horizontal { size.x } else { size.y };
+
+        // The offset is based on the previous frame, so render again once the size is known
+        if self.align != Align::Start && (size - self.content_size.get()).abs() > 0.5 {
+            self.content_size.set(size);
+            ui.ctx().request_repaint();
+        }
+    }
+}
+
+impl Default for Arrangement {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Layout {
-    pub fn render(&self, ui: &mut Ui, widgets: &[Rc<dyn Widget>], config_tx: &Sender<ConfigEvent>) {
-        match self {
-            Layout::Horizontal => ui.horizontal(|ui| {
-                for widget in widgets {
-                    widget.render(ui, config_tx);
+    pub fn render(
+        &self,
+        ui: &mut Ui,
+        widgets: &[Rc<dyn Widget>],
+        arrangement: &Arrangement,
+        config_tx: &Sender<ConfigEvent>,
+    ) {
+        let render_widgets = |ui: &mut Ui| {
+            for widget in widgets {
+                widget.render(ui, config_tx);
+            }
+        };
+
+        egui::Frame::none()
+            .margin(egui::vec2(arrangement.padding, arrangement.padding))
+            .show(ui, |ui| {
+                if let Some(spacing) = arrangement.spacing {
+                    ui.spacing_mut().item_spacing = egui::vec2(spacing, spacing);
                 }
-            }),
-            Layout::Vertical => ui.vertical(|ui| {
-                for widget in widgets {
-                    widget.render(ui, config_tx);
+
+                match self {
+                    Layout::Horizontal => {
+                        ui.horizontal(|ui| arrangement.aligned(ui, true, render_widgets));
+                    }
+                    Layout::Vertical => {
+                        ui.vertical(|ui| arrangement.aligned(ui, false, render_widgets));
+                    }
+                    Layout::Grid(columns) => {
+                        egui::Grid::new(arrangement.id)
+                            .num_columns(*columns)
+                            .show(ui, |ui| {
+                                for (i, widget) in widgets.iter().enumerate() {
+                                    widget.render(ui, config_tx);
+                                    if (i + 1) % columns == 0 {
+                                        ui.end_row();
+                                    }
+                                }
+                            });
+                    }
+                    Layout::Columns(columns) => {
+                        ui.columns(*columns, |uis| {
+                            for (i, widget) in widgets.iter().enumerate() {
+                                widget.render(&mut uis[i % columns], config_tx);
+                            }
+                        });
+                    }
                 }
-            }),
-        };
+            });
     }
 }
 
+/// State of the output the shell is rendered on, updated every frame
+#[derive(Debug, Clone, Default)]
+pub struct OutputState {
+    /// Name of the workspace active on the output
+    pub workspace: String,
+    /// Pointer location relative to the output, if the pointer is on it
+    pub pointer: Option<egui::Pos2>,
+    /// Some window is fullscreen on the output
+    pub fullscreen: bool,
+}
+
 pub trait Container {
-    fn render(&self, ctx: &CtxRef, config_tx: &Sender<ConfigEvent>);
+    fn render(&self, ctx: &CtxRef, output: &OutputState, config_tx: &Sender<ConfigEvent>);
+
+    fn id(&self) -> String;
+
+    /// The container as a script value, for `shell.get(id)`
+    fn to_dynamic(&self) -> Dynamic;
+
+    /// Called after the shell order changed, floating containers have to be put
+    /// on top again in the new order
+    fn restack(&self) {}
+
+    /// Screen edge and thickness reserved by the container, windows are kept out of it
+    fn exclusive_zone(&self) -> Option<(PanelPosition, f32)> {
+        None
+    }
 }
 
 impl std::fmt::Debug for Box<dyn Container> {
@@ -70,13 +207,35 @@ pub mod layout {
     pub fn horizontal() -> Layout {
         Layout::Horizontal
     }
+    pub fn grid(columns: INT) -> Layout {
+        Layout::Grid(columns.max(1) as _)
+    }
+    pub fn columns(columns: INT) -> Layout {
+        Layout::Columns(columns.max(1) as _)
+    }
+}
+
+#[export_module]
+pub mod align {
+    pub fn start() -> Align {
+        Align::Start
+    }
+    pub fn center() -> Align {
+        Align::Center
+    }
+    pub fn end() -> Align {
+        Align::End
+    }
 }
 
 pub fn register(engine: &mut Engine) {
     let container_module = exported_module!(container);
     let layout_module = exported_module!(layout);
+    let align_module = exported_module!(align);
     engine
         .register_static_module("container", container_module.into())
         .register_static_module("layout", layout_module.into())
-        .register_type::<Layout>();
+        .register_static_module("align", align_module.into())
+        .register_type::<Layout>()
+        .register_type::<Align>();
 }
@@ -0,0 +1,263 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use calloop::channel::Sender;
+use egui::{CtxRef, Key, Pos2, Rect, Sense};
+
+use crate::config::eventloop::ConfigEvent;
+
+use super::menu::item::{Entry, MenuItem};
+use super::menu::Menu;
+
+const MIN_WIDTH: f32 = 150.0;
+
+struct Open {
+    pos: Pos2,
+    /// Selected entry of every open level, the last one has the keyboard focus
+    path: Vec<usize>,
+    /// Area of every open level in the last frame
+    rects: Vec<Rect>,
+}
+
+/// Popup menu rendered by the shell, like the one opened by a right click on the desktop.
+/// Arrow keys move the selection, enter picks it and escape closes the menu.
+pub struct ContextMenu {
+    id: egui::Id,
+    menu: RefCell<Option<Menu>>,
+    open: RefCell<Option<Open>>,
+}
+
+fn selectable(entries: &[Entry], index: usize) -> bool {
+    !matches!(entries.get(index), Some(Entry::Separator) | None)
+}
+
+/// Next selectable entry after `from`, wraps around
+fn step(entries: &[Entry], from: usize, forward: bool) -> usize {
+    let len = entries.len();
+    for i in 1..=len {
+        let index = if forward {
+            (from + i) % len
+        } else {
+            (from + len - i) % len
+        };
+        if selectable(entries, index) {
+            return index;
+        }
+    }
+    from
+}
+
+fn first(entries: &[Entry]) -> usize {
+    if selectable(entries, 0) || entries.is_empty() {
+        0
+    } else {
+        step(entries, 0, true)
+    }
+}
+
+fn entries(items: &[Rc<dyn MenuItem>]) -> Vec<Entry> {
+    items.iter().map(|item| item.entry()).collect()
+}
+
+impl ContextMenu {
+    pub fn new(id_source: &str) -> Self {
+        Self {
+            id: egui::Id::new(id_source),
+            menu: Default::default(),
+            open: Default::default(),
+        }
+    }
+
+    pub fn set_menu(&self, menu: Option<Menu>) {
+        *self.menu.borrow_mut() = menu;
+        self.open.borrow_mut().take();
+    }
+
+    pub fn has_menu(&self) -> bool {
+        self.menu.borrow().is_some()
+    }
+
+    /// Open the menu with its top left corner at `pos`
+    pub fn open(&self, pos: Pos2) {
+        let path = match self.menu.borrow().as_ref() {
+            Some(menu) => vec![first(&entries(&menu.items()))],
+            None => return,
+        };
+
+        *self.open.borrow_mut() = Some(Open {
+            pos,
+            path,
+            rects: Vec::new(),
+        });
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open.borrow().is_some()
+    }
+
+    /// Items of every open level
+    fn levels(root: Vec<Rc<dyn MenuItem>>, path: &[usize]) -> Vec<Vec<Rc<dyn MenuItem>>> {
+        let mut levels = vec![root];
+        for selected in path.iter().take(path.len().saturating_sub(1)) {
+            let next = match levels.last().and_then(|items| items.get(*selected)) {
+                Some(item) => match item.entry() {
+                    Entry::Submenu(_, items) => items,
+                    _ => break,
+                },
+                None => break,
+            };
+            levels.push(next);
+        }
+        levels
+    }
+
+    pub fn render(&self, ctx: &CtxRef, config_tx: &Sender<ConfigEvent>) {
+        let menu = match self.menu.borrow().clone() {
+            Some(menu) => menu,
+            None => return,
+        };
+        let id = self.id;
+        let mut open = self.open.borrow_mut();
+
+        let input = ctx.input();
+        let pointer = input.pointer.interact_pos();
+
+        // A click outside of the menu closes it
+        if input.pointer.any_pressed() {
+            let inside = open
+                .as_ref()
+                .zip(pointer)
+                .map(|(open, pointer)| open.rects.iter().any(|r| r.contains(pointer)))
+                .unwrap_or(false);
+            if !inside && open.take().is_some() {
+                ctx.memory().surrender_focus(id);
+            }
+        }
+
+        let state = match open.as_mut() {
+            Some(state) => state,
+            None => return,
+        };
+
+        let mut activated: Option<Rc<dyn MenuItem>> = None;
+        let mut close = input.key_pressed(Key::Escape);
+
+        // Keyboard navigation in the deepest open level
+        {
+            let levels = Self::levels(menu.items(), &state.path);
+            let depth = levels.len() - 1;
+            state.path.truncate(levels.len());
+            let current = entries(&levels[depth]);
+            let selected = state.path[depth];
+
+            if input.key_pressed(Key::ArrowDown) {
+                state.path[depth] = step(&current, selected, true);
+            } else if input.key_pressed(Key::ArrowUp) {
+                state.path[depth] = step(&current, selected, false);
+            } else if input.key_pressed(Key::ArrowLeft) && depth > 0 {
+                state.path.pop();
+            } else if input.key_pressed(Key::ArrowRight)
+                || input.key_pressed(Key::Enter)
+                || input.key_pressed(Key::Space)
+            {
+                match current.get(selected) {
+                    Some(Entry::Submenu(_, items)) => state.path.push(first(&entries(items))),
+                    Some(Entry::Action(_)) if !input.key_pressed(Key::ArrowRight) => {
+                        activated = levels[depth].get(selected).cloned();
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        let levels = Self::levels(menu.items(), &state.path);
+        let moved = input.pointer.delta() != egui::Vec2::ZERO;
+        let mut new_path = None;
+        let mut rects = Vec::new();
+        let mut pos = state.pos;
+
+        for (level, items) in levels.iter().enumerate() {
+            let current = entries(items);
+            let mut next_pos = None;
+
+            let response = egui::Area::new(id.with(level))
+                .order(egui::Order::Foreground)
+                .fixed_pos(pos)
+                .show(ctx, |ui| {
+                    if level == 0 {
+                        // Keep the keyboard focus on the menu while it is open
+                        ui.interact(ui.min_rect(), id, Sense::hover());
+                        ui.memory().request_focus(id);
+                    }
+
+                    egui::Frame::menu(ui.style()).show(ui, |ui| {
+                        ui.set_min_width(MIN_WIDTH);
+                        ui.with_layout(egui::Layout::top_down_justified(egui::Align::Min), |ui| {
+                            for (index, entry) in current.iter().enumerate() {
+                                let (label, submenu) = match entry {
+                                    Entry::Separator => {
+                                        ui.separator();
+                                        continue;
+                                    }
+                                    Entry::Action(label) => (label.clone(), false),
+                                    Entry::Submenu(label, _) => (format!("{}  >", label), true),
+                                };
+
+                                let selected = state.path.get(level) == Some(&index);
+                                let row = ui.selectable_label(selected, label);
+
+                                if selected && submenu && state.path.len() > level + 1 {
+                                    next_pos = Some(row.rect.right_top());
+                                }
+
+                                let mut path = state.path[..level].to_vec();
+                                path.push(index);
+                                if submenu {
+                                    if let Entry::Submenu(_, items) = entry {
+                                        path.push(first(&entries(items)));
+                                    }
+                                }
+
+                                if row.clicked() {
+                                    if submenu {
+                                        new_path = Some(path);
+                                    } else {
+                                        activated = items.get(index).cloned();
+                                    }
+                                } else if row.hovered() && moved {
+                                    // Select the hovered entry, opening it if it is a submenu
+                                    // and closing the deeper levels otherwise
+                                    let open_below = state.path.len() > level + 1;
+                                    if !selected || submenu != open_below {
+                                        new_path = Some(path);
+                                    }
+                                }
+                            }
+                        });
+                    });
+                })
+                .response;
+
+            rects.push(response.rect);
+            pos = match next_pos {
+                Some(next_pos) => next_pos,
+                None => break,
+            };
+        }
+
+        state.rects = rects;
+        if let Some(path) = new_path {
+            state.path = path;
+        }
+
+        if let Some(item) = activated {
+            item.activate(config_tx);
+            close = true;
+        }
+
+        if close {
+            open.take();
+            ctx.memory().surrender_focus(id);
+        }
+    }
+}
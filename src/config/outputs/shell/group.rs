@@ -0,0 +1,137 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use egui::Ui;
+use rhai::plugin::*;
+use rhai::{Engine, FLOAT};
+
+use super::container::{Align, Arrangement, Layout};
+use super::widget::*;
+
+struct GroupInner {
+    layout: Layout,
+    arrangement: Arrangement,
+    widgets: Vec<Rc<dyn Widget>>,
+    frame: bool,
+}
+
+/// Container that is itself a widget, allows nesting layouts
+#[derive(Clone)]
+pub struct Group(Rc<RefCell<GroupInner>>);
+
+impl Group {
+    pub fn new(layout: Layout) -> Self {
+        Self(Rc::new(RefCell::new(GroupInner {
+            layout,
+            arrangement: Arrangement::new(),
+            widgets: Vec::new(),
+            frame: false,
+        })))
+    }
+}
+
+impl std::fmt::Debug for Group {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Group")
+    }
+}
+
+impl Widget for Group {
+    fn render(&self, ui: &mut Ui, config_tx: &Sender<ConfigEvent>) {
+        let inner = self.0.borrow();
+
+        let add_contents = |ui: &mut Ui| {
+            inner
+                .layout
+                .render(ui, &inner.widgets, &inner.arrangement, config_tx);
+        };
+
+        if inner.frame {
+            ui.group(add_contents);
+        } else {
+            add_contents(ui);
+        }
+    }
+}
+
+/// Horizontal or vertical line between widgets
+#[derive(Debug, Clone)]
+pub struct Separator;
+
+impl Widget for Separator {
+    fn render(&self, ui: &mut Ui, _config_tx: &Sender<ConfigEvent>) {
+        ui.separator();
+    }
+}
+
+#[export_module]
+pub mod group {
+    #[rhai_fn(get = "layout", pure)]
+    pub fn layout(group: &mut Group) -> Layout {
+        group.0.borrow().layout.clone()
+    }
+
+    #[rhai_fn(set = "layout", pure)]
+    pub fn set_layout(group: &mut Group, layout: Layout) {
+        group.0.borrow_mut().layout = layout;
+    }
+
+    #[rhai_fn(get = "align", pure)]
+    pub fn align(group: &mut Group) -> Align {
+        group.0.borrow().arrangement.align
+    }
+
+    #[rhai_fn(set = "align", pure)]
+    pub fn set_align(group: &mut Group, align: Align) {
+        group.0.borrow_mut().arrangement.align = align;
+    }
+
+    #[rhai_fn(set = "spacing", pure)]
+    pub fn set_spacing(group: &mut Group, spacing: FLOAT) {
+        group.0.borrow_mut().arrangement.spacing = Some(spacing as _);
+    }
+
+    #[rhai_fn(get = "padding", pure)]
+    pub fn padding(group: &mut Group) -> FLOAT {
+        group.0.borrow().arrangement.padding as _
+    }
+
+    #[rhai_fn(set = "padding", pure)]
+    pub fn set_padding(group: &mut Group, padding: FLOAT) {
+        group.0.borrow_mut().arrangement.padding = padding as _;
+    }
+
+    /// Draw a frame around the group
+    #[rhai_fn(get = "frame", pure)]
+    pub fn frame(group: &mut Group) -> bool {
+        group.0.borrow().frame
+    }
+
+    #[rhai_fn(set = "frame", pure)]
+    pub fn set_frame(group: &mut Group, frame: bool) {
+        group.0.borrow_mut().frame = frame;
+    }
+
+    #[rhai_fn(global)]
+    pub fn add_widget(group: &mut Group, widget: Rc<dyn Widget>) {
+        group.0.borrow_mut().widgets.push(widget);
+    }
+
+    #[rhai_fn(global)]
+    pub fn convert(group: &mut Group) -> Rc<dyn Widget> {
+        Rc::new(group.clone())
+    }
+
+    #[rhai_fn(global, name = "convert")]
+    pub fn convert_separator(separator: &mut Separator) -> Rc<dyn Widget> {
+        Rc::new(separator.clone())
+    }
+}
+
+pub fn register(engine: &mut Engine) {
+    let group_module = exported_module!(group);
+    engine
+        .register_static_module("group", group_module.into())
+        .register_type::<Group>()
+        .register_type::<Separator>();
+}
@@ -0,0 +1,246 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use rhai::plugin::*;
+use rhai::{Engine, INT};
+
+use super::image::Raster;
+
+pub const DEFAULT_SIZE: u32 = 24;
+
+const FALLBACK_THEME: &str = "hicolor";
+
+/// Png icons of a theme directory by name, with the nominal size of the directory they are in
+type ThemeIndex = HashMap<String, Vec<(u32, PathBuf)>>;
+
+thread_local! {
+    static THEME: RefCell<String> = RefCell::new(FALLBACK_THEME.into());
+    static CACHE: RefCell<HashMap<(String, u32), Option<Rc<Raster>>>> = Default::default();
+    /// Every directory of the current theme chain, in lookup order, scanned once
+    static INDEX: RefCell<Option<Rc<Vec<ThemeIndex>>>> = Default::default();
+}
+
+/// Base directories of icon themes, in order of precedence
+fn base_dirs() -> Vec<PathBuf> {
+    let home = env::var_os("HOME").map(PathBuf::from);
+
+    let data_home = env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .or_else(|| home.as_ref().map(|home| home.join(".local/share")));
+
+    let data_dirs = env::var("XDG_DATA_DIRS")
+        .ok()
+        .filter(|dirs| !dirs.is_empty())
+        .unwrap_or_else(|| "/usr/local/share:/usr/share".into());
+
+    home.map(|home| home.join(".icons"))
+        .into_iter()
+        .chain(
+            data_home
+                .into_iter()
+                .chain(env::split_paths(&data_dirs))
+                .map(|dir| dir.join("icons")),
+        )
+        .collect()
+}
+
+/// Themes listed in the `Inherits` key of the theme's `index.theme`
+fn parents(theme: &str, base_dirs: &[PathBuf]) -> Vec<String> {
+    base_dirs
+        .iter()
+        .filter_map(|dir| fs::read_to_string(dir.join(theme).join("index.theme")).ok())
+        .flat_map(|index| {
+            index
+                .lines()
+                .find_map(|line| line.trim().strip_prefix("Inherits="))
+                .map(|parents| {
+                    parents
+                        .split(',')
+                        .map(|p| p.trim().to_owned())
+                        .filter(|p| !p.is_empty())
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+/// The theme followed by all the themes it inherits from, always ending with hicolor
+fn theme_chain(theme: &str, base_dirs: &[PathBuf]) -> Vec<String> {
+    let mut chain = vec![theme.to_owned()];
+
+    let mut i = 0;
+    while i < chain.len() {
+        for parent in parents(&chain[i], base_dirs) {
+            if !chain.contains(&parent) {
+                chain.push(parent);
+            }
+        }
+        i += 1;
+    }
+
+    chain.retain(|t| t != FALLBACK_THEME);
+    chain.push(FALLBACK_THEME.into());
+    chain
+}
+
+/// Nominal size of a theme subdirectory like `48x48` or `48x48@2`
+fn dir_size(name: &str) -> Option<u32> {
+    name.split(|c| c == 'x' || c == '@').next()?.parse().ok()
+}
+
+/// Png icons below the size directories of a theme, or directly in `dir` when `flat`.
+///
+/// `scalable` directories have no nominal size and svg icons are skipped,
+/// the image crate only decodes raster formats.
+fn index_dir(dir: &Path, flat: bool) -> ThemeIndex {
+    let mut index = ThemeIndex::new();
+
+    let icon_dirs: Vec<(u32, PathBuf)> = if flat {
+        vec![(0, dir.to_owned())]
+    } else {
+        fs::read_dir(dir)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter_map(|size_dir| {
+                let dir_size = dir_size(&size_dir.file_name().to_string_lossy())?;
+                Some((dir_size, size_dir.path()))
+            })
+            .flat_map(|(dir_size, size_dir)| {
+                fs::read_dir(size_dir)
+                    .into_iter()
+                    .flatten()
+                    .flatten()
+                    .map(move |category| (dir_size, category.path()))
+            })
+            .collect()
+    };
+
+    for (dir_size, icon_dir) in icon_dirs {
+        for file in fs::read_dir(icon_dir).into_iter().flatten().flatten() {
+            let path = file.path();
+            if path.extension().map_or(true, |ext| ext != "png") {
+                continue;
+            }
+            if let Some(name) = path.file_stem().and_then(|name| name.to_str()) {
+                index
+                    .entry(name.to_owned())
+                    .or_default()
+                    .push((dir_size, path.clone()));
+            }
+        }
+    }
+
+    index
+}
+
+/// Indexes of the current theme chain followed by the pixmaps directory
+fn indexes() -> Rc<Vec<ThemeIndex>> {
+    if let Some(indexes) = INDEX.with(|index| index.borrow().clone()) {
+        return indexes;
+    }
+
+    let base_dirs = base_dirs();
+    let theme = THEME.with(|theme| theme.borrow().clone());
+
+    let indexes: Vec<ThemeIndex> = theme_chain(&theme, &base_dirs)
+        .iter()
+        .flat_map(|theme| base_dirs.iter().map(move |dir| dir.join(theme)))
+        .map(|theme_dir| index_dir(&theme_dir, false))
+        .chain(std::iter::once(index_dir(
+            Path::new("/usr/share/pixmaps"),
+            true,
+        )))
+        .filter(|index| !index.is_empty())
+        .collect();
+
+    let indexes = Rc::new(indexes);
+    INDEX.with(|index| *index.borrow_mut() = Some(indexes.clone()));
+    indexes
+}
+
+/// Resolve a freedesktop icon name to a png file of the current theme,
+/// preferring the smallest size not below the requested one
+pub fn lookup(name: &str, size: u32) -> Option<PathBuf> {
+    if name.is_empty() {
+        return None;
+    }
+
+    let path = Path::new(name);
+    if path.is_absolute() {
+        return Some(path.to_owned()).filter(|p| p.is_file());
+    }
+
+    indexes().iter().find_map(|index| {
+        index
+            .get(name)?
+            .iter()
+            .min_by_key(|(dir_size, _)| {
+                if *dir_size >= size {
+                    (0, *dir_size - size)
+                } else {
+                    (1, size - *dir_size)
+                }
+            })
+            .map(|(_, path)| path.clone())
+    })
+}
+
+/// Icon scaled to `size`, cached so widgets can call it every frame,
+/// misses included
+pub fn load(name: &str, size: u32) -> Option<Rc<Raster>> {
+    let key = (name.to_owned(), size);
+
+    if let Some(raster) = CACHE.with(|cache| cache.borrow().get(&key).cloned()) {
+        return raster;
+    }
+
+    let raster = lookup(name, size)
+        .and_then(|path| Raster::load(&path, Some((size, size))))
+        .map(Rc::new);
+
+    CACHE.with(|cache| cache.borrow_mut().insert(key, raster.clone()));
+    raster
+}
+
+/// Icon of an application, tries the app_id as is and lowercased
+pub fn load_app_icon(app_id: &str, size: u32) -> Option<Rc<Raster>> {
+    load(app_id, size).or_else(|| {
+        let lowercase = app_id.to_lowercase();
+        if lowercase != app_id {
+            load(&lowercase, size)
+        } else {
+            None
+        }
+    })
+}
+
+#[export_module]
+pub mod icon_theme {
+    pub fn theme() -> String {
+        THEME.with(|theme| theme.borrow().clone())
+    }
+
+    pub fn set_theme(name: String) {
+        THEME.with(|theme| *theme.borrow_mut() = name);
+        INDEX.with(|index| index.borrow_mut().take());
+        CACHE.with(|cache| cache.borrow_mut().clear());
+    }
+
+    /// Path of the icon in the current theme, or an empty string
+    pub fn lookup(name: &str, size: INT) -> String {
+        super::lookup(name, size.max(1) as _)
+            .map(|path| path.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    }
+}
+
+pub fn register(engine: &mut Engine) {
+    let icon_theme_module = exported_module!(icon_theme);
+    engine.register_static_module("icon_theme", icon_theme_module.into());
+}
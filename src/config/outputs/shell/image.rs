@@ -0,0 +1,237 @@
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use egui::{Rect, Response, Sense, TextureId, Ui};
+use rhai::plugin::*;
+use rhai::{Engine, FLOAT};
+
+use super::icon_theme;
+use super::widget::*;
+
+static NEXT_TEXTURE_ID: AtomicU64 = AtomicU64::new(0);
+
+thread_local! {
+    static PLACED: RefCell<Vec<Placement>> = Default::default();
+}
+
+/// Decoded image, already scaled to the size it is displayed at.
+///
+/// smithay-egui only uploads the egui font texture, so egui can't draw it.
+/// Instead `paint` reserves its space and records where it went, and the output
+/// draws it after the egui frame from a texture that is uploaded once per `id`.
+/// Images are therefore always on top of egui, even above menus and tooltips.
+pub struct Raster {
+    id: u64,
+    width: u32,
+    height: u32,
+    /// Unmultiplied RGBA
+    rgba: Vec<u8>,
+}
+
+/// Raster painted in the current egui frame
+pub struct Placement {
+    pub raster: Rc<Raster>,
+    pub rect: Rect,
+    pub clip: Rect,
+}
+
+/// Rasters painted since the last call, in paint order
+pub fn take_placed() -> Vec<Placement> {
+    PLACED.with(|placed| placed.borrow_mut().drain(..).collect())
+}
+
+impl Raster {
+    #[cfg(feature = "image")]
+    pub fn load(path: &Path, size: Option<(u32, u32)>) -> Option<Self> {
+        // The image module below shadows the crate name, hence the leading `::`
+        use ::image::imageops::{self, FilterType};
+
+        let image = match ::image::open(path) {
+            Ok(image) => image.to_rgba8(),
+            Err(err) => {
+                warn!("failed to load image {:?}: {}", path, err);
+                return None;
+            }
+        };
+
+        let image = match size {
+            Some((width, height)) if (width, height) != image.dimensions() => {
+                imageops::resize(&image, width.max(1), height.max(1), FilterType::Triangle)
+            }
+            _ => image,
+        };
+
+        Some(Self {
+            id: NEXT_TEXTURE_ID.fetch_add(1, Ordering::Relaxed),
+            width: image.width(),
+            height: image.height(),
+            rgba: image.into_raw(),
+        })
+    }
+
+    #[cfg(not(feature = "image"))]
+    pub fn load(path: &Path, _size: Option<(u32, u32)>) -> Option<Self> {
+        warn!("can't load image {:?}, image feature not enabled", path);
+        None
+    }
+
+    /// Texture of this raster, the same for as long as it lives
+    pub fn texture_id(&self) -> TextureId {
+        TextureId::User(self.id)
+    }
+
+    pub fn dimensions(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    pub fn rgba(&self) -> &[u8] {
+        &self.rgba
+    }
+
+    pub fn size(&self) -> egui::Vec2 {
+        egui::vec2(self.width as f32, self.height as f32)
+    }
+
+    pub fn paint(self: &Rc<Self>, ui: &mut Ui) -> Response {
+        let (rect, response) = ui.allocate_exact_size(self.size(), Sense::click());
+
+        let clip = ui.clip_rect();
+        if clip.intersects(rect) {
+            PLACED.with(|placed| {
+                placed.borrow_mut().push(Placement {
+                    raster: self.clone(),
+                    rect,
+                    clip,
+                })
+            });
+        }
+
+        response
+    }
+}
+
+impl std::fmt::Debug for Raster {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Raster({}x{})", self.width, self.height)
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Source {
+    Path(PathBuf),
+    Icon(String),
+}
+
+#[derive(Debug)]
+struct ImageInner {
+    source: Source,
+    width: Option<u32>,
+    height: Option<u32>,
+    /// `None` until the first render, or after a property changed
+    raster: Option<Option<Rc<Raster>>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Image(Rc<RefCell<ImageInner>>);
+
+impl Image {
+    pub fn new(path: String) -> Self {
+        Self::with_source(Source::Path(path.into()), None)
+    }
+
+    /// Image of a freedesktop icon, looked up in the current icon theme
+    pub fn icon(name: String, size: u32) -> Self {
+        Self::with_source(Source::Icon(name), Some(size))
+    }
+
+    fn with_source(source: Source, size: Option<u32>) -> Self {
+        Self(Rc::new(RefCell::new(ImageInner {
+            source,
+            width: size,
+            height: size,
+            raster: None,
+        })))
+    }
+}
+
+impl ImageInner {
+    fn load(&self) -> Option<Rc<Raster>> {
+        match &self.source {
+            Source::Path(path) => {
+                let size = match (self.width, self.height) {
+                    (Some(width), Some(height)) => Some((width, height)),
+                    _ => None,
+                };
+                Raster::load(path, size).map(Rc::new)
+            }
+            Source::Icon(name) => {
+                let size = self
+                    .width
+                    .or(self.height)
+                    .unwrap_or(icon_theme::DEFAULT_SIZE);
+                icon_theme::load(name, size)
+            }
+        }
+    }
+}
+
+impl Widget for Image {
+    fn render(&self, ui: &mut Ui, _config_tx: &Sender<ConfigEvent>) {
+        let mut inner = self.0.borrow_mut();
+
+        if inner.raster.is_none() {
+            inner.raster = Some(inner.load());
+            ui.ctx().request_repaint();
+        }
+
+        if let Some(Some(raster)) = &inner.raster {
+            raster.paint(ui);
+        }
+    }
+}
+
+#[export_module]
+pub mod image {
+    #[rhai_fn(get = "path", pure)]
+    pub fn path(image: &mut Image) -> String {
+        match &image.0.borrow().source {
+            Source::Path(path) => path.to_string_lossy().into_owned(),
+            Source::Icon(name) => name.clone(),
+        }
+    }
+
+    #[rhai_fn(set = "path", pure)]
+    pub fn set_path(image: &mut Image, path: String) {
+        let mut inner = image.0.borrow_mut();
+        inner.source = Source::Path(path.into());
+        inner.raster = None;
+    }
+
+    #[rhai_fn(set = "width", pure)]
+    pub fn set_width(image: &mut Image, width: FLOAT) {
+        let mut inner = image.0.borrow_mut();
+        inner.width = Some(width.max(1.0) as _);
+        inner.raster = None;
+    }
+
+    #[rhai_fn(set = "height", pure)]
+    pub fn set_height(image: &mut Image, height: FLOAT) {
+        let mut inner = image.0.borrow_mut();
+        inner.height = Some(height.max(1.0) as _);
+        inner.raster = None;
+    }
+
+    #[rhai_fn(global)]
+    pub fn convert(image: &mut Image) -> Rc<dyn Widget> {
+        Rc::new(image.clone())
+    }
+}
+
+pub fn register(engine: &mut Engine) {
+    let image_module = exported_module!(image);
+    engine
+        .register_static_module("image", image_module.into())
+        .register_type::<Image>();
+}
@@ -0,0 +1,442 @@
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::env;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+
+use egui::Ui;
+use rhai::plugin::*;
+use rhai::{Engine, FnPtr, INT};
+
+use crate::config::system;
+
+use super::icon_theme;
+use super::widget::*;
+
+const ICON_SIZE: u32 = 16;
+
+/// Single program that can be started from the launcher
+#[derive(Debug, Clone)]
+struct Entry {
+    name: String,
+    command: String,
+    icon: Option<String>,
+}
+
+/// Directories with `.desktop` files, in order of precedence
+fn applications_dirs() -> Vec<PathBuf> {
+    let data_home = env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".local/share")));
+
+    let data_dirs = env::var("XDG_DATA_DIRS")
+        .ok()
+        .filter(|dirs| !dirs.is_empty())
+        .unwrap_or_else(|| "/usr/local/share:/usr/share".into());
+
+    data_home
+        .into_iter()
+        .chain(env::split_paths(&data_dirs))
+        .map(|dir| dir.join("applications"))
+        .collect()
+}
+
+/// Remove the field codes like `%f` or `%U` from a desktop entry Exec key
+fn strip_field_codes(exec: &str) -> String {
+    let mut command = String::with_capacity(exec.len());
+    let mut chars = exec.chars();
+
+    while let Some(c) = chars.next() {
+        if c == '%' {
+            if let Some('%') = chars.next() {
+                command.push('%');
+            }
+        } else {
+            command.push(c);
+        }
+    }
+
+    command.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn parse_desktop_entry(content: &str) -> Option<Entry> {
+    let mut in_entry = false;
+    let mut name = None;
+    let mut exec = None;
+    let mut icon = None;
+
+    for line in content.lines() {
+        let line = line.trim();
+
+        if line.starts_with('[') {
+            in_entry = line == "[Desktop Entry]";
+            continue;
+        }
+
+        if !in_entry {
+            continue;
+        }
+
+        if let Some((key, value)) = line.split_once('=') {
+            match key.trim() {
+                "Name" => name = Some(value.trim().to_owned()),
+                "Exec" => exec = Some(strip_field_codes(value.trim())),
+                "Icon" => icon = Some(value.trim().to_owned()),
+                "Type" if value.trim() != "Application" => return None,
+                "NoDisplay" | "Hidden" if value.trim() == "true" => return None,
+                _ => {}
+            }
+        }
+    }
+
+    Some(Entry {
+        name: name?,
+        command: exec.filter(|exec| !exec.is_empty())?,
+        icon,
+    })
+}
+
+fn scan_desktop_entries(entries: &mut Vec<Entry>) {
+    // Desktop files with the same id are shadowed by the ones in earlier dirs
+    let mut ids = HashSet::new();
+
+    for dir in applications_dirs() {
+        let read_dir = match fs::read_dir(&dir) {
+            Ok(read_dir) => read_dir,
+            Err(_) => continue,
+        };
+
+        for file in read_dir.flatten() {
+            let path = file.path();
+            if path.extension().map(|e| e != "desktop").unwrap_or(true) {
+                continue;
+            }
+
+            if !ids.insert(file.file_name()) {
+                continue;
+            }
+
+            if let Some(entry) = fs::read_to_string(&path)
+                .ok()
+                .and_then(|content| parse_desktop_entry(&content))
+            {
+                entries.push(entry);
+            }
+        }
+    }
+}
+
+fn scan_path(entries: &mut Vec<Entry>) {
+    let path = match env::var_os("PATH") {
+        Some(path) => path,
+        None => return,
+    };
+
+    let mut names: HashSet<String> = entries.iter().map(|e| e.command.clone()).collect();
+
+    for dir in env::split_paths(&path) {
+        let read_dir = match fs::read_dir(&dir) {
+            Ok(read_dir) => read_dir,
+            Err(_) => continue,
+        };
+
+        for file in read_dir.flatten() {
+            let executable = file
+                .metadata()
+                .map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+                .unwrap_or(false);
+
+            if !executable {
+                continue;
+            }
+
+            if let Ok(name) = file.file_name().into_string() {
+                if names.insert(name.clone()) {
+                    entries.push(Entry {
+                        command: name.clone(),
+                        name,
+                        icon: None,
+                    });
+                }
+            }
+        }
+    }
+}
+
+fn scan_entries() -> Vec<Entry> {
+    let mut entries = Vec::new();
+    scan_desktop_entries(&mut entries);
+    scan_path(&mut entries);
+    entries
+}
+
+/// Scan on another thread, reading every `$PATH` dir would stall the render loop
+fn start_scan() -> Receiver<Vec<Entry>> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        // The launcher may be gone by the time the scan finishes
+        tx.send(scan_entries()).ok();
+    });
+    rx
+}
+
+/// Score how well the query matches the candidate, `None` if not all query characters
+/// are found in order. Consecutive characters and word starts are ranked higher.
+fn fuzzy_score(query: &str, candidate: &str) -> Option<i64> {
+    let candidate: Vec<char> = candidate.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut position = 0;
+    let mut previous: Option<usize> = None;
+
+    for q in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let found = (position..candidate.len()).find(|i| candidate[*i] == q)?;
+
+        score += 1;
+        if previous.map(|p| p + 1 == found).unwrap_or(false) {
+            score += 5;
+        }
+        if found == 0 || matches!(candidate[found - 1], ' ' | '-' | '_' | '.') {
+            score += 8;
+        }
+        score -= (found - position) as i64;
+
+        previous = Some(found);
+        position = found + 1;
+    }
+
+    // Prefer shorter candidates when everything else is equal
+    Some(score * 100 - candidate.len() as i64)
+}
+
+struct LauncherInner {
+    entries: Vec<Entry>,
+    /// Running scan, the current entries are kept until it finishes
+    scan: Option<Receiver<Vec<Entry>>>,
+    query: String,
+    matches: Vec<usize>,
+    selected: usize,
+    max_results: usize,
+    show_icons: bool,
+    request_focus: bool,
+    on_launch: Option<FnPtr>,
+}
+
+impl LauncherInner {
+    /// Take the entries of a finished scan and match them against the query
+    fn poll_scan(&mut self) {
+        let entries = match self.scan.as_ref().map(|scan| scan.try_recv()) {
+            Some(Ok(entries)) => entries,
+            Some(Err(TryRecvError::Disconnected)) => {
+                self.scan = None;
+                return;
+            }
+            Some(Err(TryRecvError::Empty)) | None => return,
+        };
+
+        self.scan = None;
+        self.entries = entries;
+        self.update_matches();
+    }
+
+    fn update_matches(&mut self) {
+        self.selected = 0;
+
+        if self.query.trim().is_empty() {
+            self.matches.clear();
+            return;
+        }
+
+        let query = self.query.clone();
+        let max_results = self.max_results;
+
+        let mut matches: Vec<(i64, usize)> = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter_map(|(i, entry)| {
+                let by_name = fuzzy_score(&query, &entry.name);
+                let by_command = fuzzy_score(&query, &entry.command);
+                by_name.max(by_command).map(|score| (score, i))
+            })
+            .collect();
+
+        matches.sort_by(|a, b| b.0.cmp(&a.0));
+        matches.truncate(max_results);
+
+        self.matches = matches.into_iter().map(|(_, i)| i).collect();
+    }
+
+    fn launch(&mut self, index: usize, config_tx: &Sender<ConfigEvent>) {
+        let entry = self.entries[index].clone();
+        info!("launcher: starting {}", entry.command);
+        system::spawn(&entry.command);
+
+        self.query.clear();
+        self.matches.clear();
+        self.selected = 0;
+
+        if let Some(on_launch) = &self.on_launch {
+            config_tx
+                .send(ConfigEvent::ShellWithArgs(
+                    on_launch.clone(),
+                    vec![entry.command.into()],
+                ))
+                .unwrap();
+        }
+    }
+}
+
+/// Fuzzy finder over `$PATH` executables and desktop entries
+#[derive(Clone)]
+pub struct Launcher(Rc<RefCell<LauncherInner>>);
+
+impl Launcher {
+    pub fn new() -> Self {
+        Self(Rc::new(RefCell::new(LauncherInner {
+            entries: Vec::new(),
+            scan: Some(start_scan()),
+            query: String::new(),
+            matches: Vec::new(),
+            selected: 0,
+            max_results: 10,
+            show_icons: true,
+            request_focus: false,
+            on_launch: None,
+        })))
+    }
+}
+
+impl Default for Launcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for Launcher {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Launcher")
+    }
+}
+
+impl Widget for Launcher {
+    fn render(&self, ui: &mut Ui, config_tx: &Sender<ConfigEvent>) {
+        let mut inner = self.0.borrow_mut();
+        inner.poll_scan();
+
+        ui.vertical(|ui| {
+            let response = ui.add(egui::TextEdit::singleline(&mut inner.query).hint_text("Run..."));
+
+            if inner.request_focus {
+                response.request_focus();
+                inner.request_focus = false;
+            }
+
+            if response.changed() {
+                inner.update_matches();
+            }
+
+            if response.has_focus() && !inner.matches.is_empty() {
+                let input = ui.input();
+                if input.key_pressed(egui::Key::ArrowDown) {
+                    inner.selected = (inner.selected + 1).min(inner.matches.len() - 1);
+                }
+                if input.key_pressed(egui::Key::ArrowUp) {
+                    inner.selected = inner.selected.saturating_sub(1);
+                }
+            }
+
+            let mut launch = None;
+
+            if response.lost_focus() && ui.input().key_pressed(egui::Key::Enter) {
+                launch = inner.matches.get(inner.selected).copied();
+            }
+
+            if inner.scan.is_some() && inner.matches.is_empty() && !inner.query.is_empty() {
+                ui.label(egui::RichText::new("Searching...").weak());
+            }
+
+            let show_icons = inner.show_icons;
+            for (i, index) in inner.matches.clone().into_iter().enumerate() {
+                let entry = inner.entries[index].clone();
+                let selected = i == inner.selected;
+
+                let clicked = ui
+                    .horizontal(|ui| {
+                        if let Some(icon) = entry
+                            .icon
+                            .filter(|_| show_icons)
+                            .and_then(|icon| icon_theme::load(&icon, ICON_SIZE))
+                        {
+                            icon.paint(ui);
+                        }
+                        ui.selectable_label(selected, entry.name).clicked()
+                    })
+                    .inner;
+
+                if clicked {
+                    launch = Some(index);
+                }
+            }
+
+            if let Some(index) = launch {
+                inner.launch(index, config_tx);
+            }
+        });
+    }
+}
+
+#[export_module]
+pub mod launcher {
+    #[rhai_fn(get = "max_results", pure)]
+    pub fn max_results(launcher: &mut Launcher) -> INT {
+        launcher.0.borrow().max_results as _
+    }
+
+    #[rhai_fn(set = "max_results", pure)]
+    pub fn set_max_results(launcher: &mut Launcher, max_results: INT) {
+        launcher.0.borrow_mut().max_results = max_results.max(1) as _;
+    }
+
+    #[rhai_fn(get = "show_icons", pure)]
+    pub fn show_icons(launcher: &mut Launcher) -> bool {
+        launcher.0.borrow().show_icons
+    }
+
+    #[rhai_fn(set = "show_icons", pure)]
+    pub fn set_show_icons(launcher: &mut Launcher, show_icons: bool) {
+        launcher.0.borrow_mut().show_icons = show_icons;
+    }
+
+    #[rhai_fn(global)]
+    pub fn focus(launcher: &mut Launcher) {
+        launcher.0.borrow_mut().request_focus = true;
+    }
+
+    /// Rescan `$PATH` and desktop entries, e.g. after installing new programs,
+    /// the new entries show up once the scan finished
+    #[rhai_fn(global)]
+    pub fn refresh(launcher: &mut Launcher) {
+        launcher.0.borrow_mut().scan = Some(start_scan());
+    }
+
+    #[rhai_fn(global)]
+    pub fn on_launch(launcher: &mut Launcher, on_launch: FnPtr) {
+        launcher.0.borrow_mut().on_launch = Some(on_launch);
+    }
+
+    #[rhai_fn(global)]
+    pub fn convert(launcher: &mut Launcher) -> Rc<dyn Widget> {
+        Rc::new(launcher.clone())
+    }
+}
+
+pub fn register(engine: &mut Engine) {
+    let launcher_module = exported_module!(launcher);
+    engine
+        .register_static_module("launcher", launcher_module.into())
+        .register_type::<Launcher>();
+}
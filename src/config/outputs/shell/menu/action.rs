@@ -0,0 +1,35 @@
+use egui::Ui;
+
+use super::item::*;
+
+/// Item sending a compositor event, used by the menus built by the compositor itself
+#[derive(Debug, Clone)]
+pub struct Action {
+    label: String,
+    event: ConfigEvent,
+}
+
+impl Action {
+    pub fn new(label: impl Into<String>, event: ConfigEvent) -> Self {
+        Self {
+            label: label.into(),
+            event,
+        }
+    }
+}
+
+impl MenuItem for Action {
+    fn render(&self, ui: &mut Ui, config_tx: &Sender<ConfigEvent>) {
+        if ui.button(&self.label).clicked() {
+            self.activate(config_tx);
+        }
+    }
+
+    fn entry(&self) -> Entry {
+        Entry::Action(self.label.clone())
+    }
+
+    fn activate(&self, config_tx: &Sender<ConfigEvent>) {
+        config_tx.send(self.event.clone()).unwrap();
+    }
+}
@@ -1,11 +1,25 @@
+use std::rc::Rc;
+
 pub use calloop::channel::Sender;
 pub use egui::Ui;
 
 pub use crate::config::eventloop::ConfigEvent;
 pub use crate::config::outputs::shell::widget::Widget;
 
+/// What a keyboard navigable menu shows for an item
+pub enum Entry {
+    Action(String),
+    Submenu(String, Vec<Rc<dyn MenuItem>>),
+    Separator,
+}
+
 pub trait MenuItem {
     fn render(&self, ui: &mut Ui, config_tx: &Sender<ConfigEvent>);
+
+    fn entry(&self) -> Entry;
+
+    /// Called when the item gets picked outside of `render`
+    fn activate(&self, _config_tx: &Sender<ConfigEvent>) {}
 }
 
 impl std::fmt::Debug for Box<dyn MenuItem> {
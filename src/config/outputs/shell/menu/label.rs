@@ -24,13 +24,21 @@ impl Label {
 
 impl MenuItem for Label {
     fn render(&self, ui: &mut Ui, config_tx: &Sender<ConfigEvent>) {
-        let inner = self.0.borrow();
-        if ui.button(&inner.label).clicked() {
-            config_tx
-                .send(ConfigEvent::Shell(inner.fnptr.clone()))
-                .unwrap();
+        let clicked = ui.button(&self.0.borrow().label).clicked();
+        if clicked {
+            self.activate(config_tx);
         }
     }
+
+    fn entry(&self) -> Entry {
+        Entry::Action(self.0.borrow().label.clone())
+    }
+
+    fn activate(&self, config_tx: &Sender<ConfigEvent>) {
+        config_tx
+            .send(ConfigEvent::Shell(self.0.borrow().fnptr.clone()))
+            .unwrap();
+    }
 }
 
 #[export_module]
@@ -7,10 +7,11 @@ use rhai::{Engine, FnPtr};
 
 use super::widget::*;
 
-use self::item::MenuItem;
+use self::item::{Entry, MenuItem};
 use self::label::Label;
 
-mod item;
+pub mod action;
+pub mod item;
 mod label;
 
 struct MenuInner {
@@ -28,6 +29,18 @@ impl Menu {
             items: vec![],
         })))
     }
+
+    pub fn items(&self) -> Vec<Rc<dyn MenuItem>> {
+        self.0.borrow().items.clone()
+    }
+
+    pub fn push(&self, item: Rc<dyn MenuItem>) {
+        self.0.borrow_mut().items.push(item);
+    }
+
+    pub fn push_separator(&self) {
+        self.push(Rc::new(Separator));
+    }
 }
 
 impl Widget for Menu {
@@ -41,6 +54,31 @@ impl Widget for Menu {
     }
 }
 
+/// Nested menus open to the side of their parent
+impl MenuItem for Menu {
+    fn render(&self, ui: &mut Ui, config_tx: &Sender<ConfigEvent>) {
+        Widget::render(self, ui, config_tx);
+    }
+
+    fn entry(&self) -> Entry {
+        let inner = self.0.borrow();
+        Entry::Submenu(inner.label.clone(), inner.items.clone())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Separator;
+
+impl MenuItem for Separator {
+    fn render(&self, ui: &mut Ui, _config_tx: &Sender<ConfigEvent>) {
+        ui.separator();
+    }
+
+    fn entry(&self) -> Entry {
+        Entry::Separator
+    }
+}
+
 #[export_module]
 pub mod menu {
     #[rhai_fn(global)]
@@ -50,7 +88,18 @@ pub mod menu {
 
     #[rhai_fn(global)]
     pub fn add_item(menu: &mut Menu, item: Rc<dyn MenuItem>) {
-        menu.0.borrow_mut().items.push(item);
+        menu.push(item);
+    }
+
+    /// Add a menu as a submenu, it is shown under its label
+    #[rhai_fn(global, name = "add_item")]
+    pub fn add_submenu(menu: &mut Menu, submenu: Menu) {
+        menu.push(Rc::new(submenu));
+    }
+
+    #[rhai_fn(global)]
+    pub fn add_separator(menu: &mut Menu) {
+        menu.push_separator();
     }
 
     pub fn label(text: String, fnptr: FnPtr) -> Label {
@@ -3,47 +3,161 @@ use std::rc::Rc;
 
 use egui::CtxRef;
 use rhai::plugin::*;
-use rhai::Engine;
+use rhai::{Array, Engine};
 
 pub mod r#box;
 mod button;
+mod checkbox;
+mod clock;
 pub mod container;
+mod context_menu;
 mod fps;
+mod group;
+mod icon_theme;
+pub mod image;
+mod launcher;
 pub mod logger;
-mod menu;
+pub mod menu;
 mod output;
 pub mod panel;
+mod progress;
+mod slider;
+mod sysinfo;
+mod taskbar;
 mod text;
+mod text_input;
+pub mod ticker;
 mod widget;
 mod workspace;
 
-use container::Container;
+use container::{Container, OutputState};
+use context_menu::ContextMenu;
+use panel::PanelPosition;
 use widget::*;
 
-#[derive(Clone, Default)]
+#[derive(Clone)]
 pub struct Shell {
     boxes: Rc<RefCell<Vec<Box<dyn Container>>>>,
+    context_menu: Rc<ContextMenu>,
+    window_menu: Rc<ContextMenu>,
 }
 
 impl Shell {
     pub fn new() -> Self {
         Self {
             boxes: Default::default(),
+            context_menu: Rc::new(ContextMenu::new("context_menu")),
+            window_menu: Rc::new(ContextMenu::new("window_menu")),
         }
     }
 
+    /// Show the menu of a window at `pos`, relative to the output
+    pub fn open_window_menu(&self, menu: menu::Menu, pos: egui::Pos2) {
+        self.window_menu.set_menu(Some(menu));
+        self.window_menu.open(pos);
+    }
+
+    /// While a menu is open, the shell gets all pointer input of its output
+    pub fn menu_open(&self) -> bool {
+        self.context_menu.is_open() || self.window_menu.is_open()
+    }
+
     pub fn add_box(&self, r#box: r#box::Box) {
-        self.boxes.borrow_mut().push(Box::new(r#box));
+        self.add(Box::new(r#box));
     }
 
     pub fn add_panel(&self, panel: panel::Panel) {
-        self.boxes.borrow_mut().push(Box::new(panel));
+        self.add(Box::new(panel));
+    }
+
+    /// Containers are added on top, one with the same id gets replaced in place
+    fn add(&self, container: Box<dyn Container>) {
+        let mut boxes = self.boxes.borrow_mut();
+        let id = container.id();
+        match boxes.iter_mut().find(|b| b.id() == id) {
+            Some(existing) => *existing = container,
+            None => boxes.push(container),
+        }
+    }
+
+    /// Ids of the containers, from the bottom to the top
+    pub fn ids(&self) -> Vec<String> {
+        self.boxes.borrow().iter().map(|b| b.id()).collect()
+    }
+
+    pub fn get(&self, id: &str) -> Option<Dynamic> {
+        self.boxes
+            .borrow()
+            .iter()
+            .find(|b| b.id() == id)
+            .map(|b| b.to_dynamic())
+    }
+
+    pub fn remove(&self, id: &str) -> bool {
+        let mut boxes = self.boxes.borrow_mut();
+        let len = boxes.len();
+        boxes.retain(|b| b.id() != id);
+        boxes.len() != len
     }
 
-    pub fn render(&self, ctx: &CtxRef, config_tx: &Sender<ConfigEvent>) {
+    pub fn clear(&self) {
+        self.boxes.borrow_mut().clear();
+    }
+
+    /// Move the container to the top, or to the bottom when `top` is false.
+    /// Panels that come first are laid out closer to the screen edge.
+    pub fn restack(&self, id: &str, top: bool) -> bool {
+        let mut boxes = self.boxes.borrow_mut();
+        let index = match boxes.iter().position(|b| b.id() == id) {
+            Some(index) => index,
+            None => return false,
+        };
+
+        let container = boxes.remove(index);
+        if top {
+            boxes.push(container);
+        } else {
+            boxes.insert(0, container);
+        }
+
+        for container in boxes.iter() {
+            container.restack();
+        }
+        true
+    }
+
+    pub fn exclusive_zones(&self) -> Vec<(PanelPosition, f32)> {
+        self.boxes
+            .borrow()
+            .iter()
+            .filter_map(|r#box| r#box.exclusive_zone())
+            .collect()
+    }
+
+    pub fn render(&self, ctx: &CtxRef, output: &OutputState, config_tx: &Sender<ConfigEvent>) {
         for r#box in self.boxes.borrow().iter() {
-            r#box.render(ctx, config_tx);
+            r#box.render(ctx, output, config_tx);
         }
+
+        // The shell only gets pointer events where there is no window,
+        // so a right click outside of shell containers is a click on the desktop
+        if self.context_menu.has_menu() && !ctx.is_pointer_over_area() {
+            let input = ctx.input();
+            if input.pointer.secondary_clicked() {
+                if let Some(pos) = input.pointer.interact_pos() {
+                    self.context_menu.open(pos);
+                }
+            }
+        }
+
+        self.context_menu.render(ctx, config_tx);
+        self.window_menu.render(ctx, config_tx);
+    }
+}
+
+impl Default for Shell {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -64,6 +178,48 @@ pub mod shell {
     pub fn add_panel(shell: &mut Shell, panel: panel::Panel) {
         shell.add_panel(panel);
     }
+
+    /// Menu opened by a right click on the desktop, its label is not shown
+    #[rhai_fn(set = "context_menu", pure)]
+    pub fn set_context_menu(shell: &mut Shell, menu: menu::Menu) {
+        shell.context_menu.set_menu(Some(menu));
+    }
+
+    #[rhai_fn(global)]
+    pub fn remove_context_menu(shell: &mut Shell) {
+        shell.context_menu.set_menu(None);
+    }
+
+    #[rhai_fn(global)]
+    pub fn ids(shell: &mut Shell) -> Array {
+        shell.ids().into_iter().map(Dynamic::from).collect()
+    }
+
+    /// Box or panel with the id, `()` if there is none
+    #[rhai_fn(global)]
+    pub fn get(shell: &mut Shell, id: &str) -> Dynamic {
+        shell.get(id).unwrap_or(Dynamic::UNIT)
+    }
+
+    #[rhai_fn(global)]
+    pub fn remove(shell: &mut Shell, id: &str) -> bool {
+        shell.remove(id)
+    }
+
+    #[rhai_fn(global)]
+    pub fn clear(shell: &mut Shell) {
+        shell.clear();
+    }
+
+    #[rhai_fn(global)]
+    pub fn raise(shell: &mut Shell, id: &str) -> bool {
+        shell.restack(id, true)
+    }
+
+    #[rhai_fn(global)]
+    pub fn lower(shell: &mut Shell, id: &str) -> bool {
+        shell.restack(id, false)
+    }
 }
 
 pub fn register(engine: &mut Engine) {
@@ -77,10 +233,21 @@ pub fn register(engine: &mut Engine) {
     r#box::register(engine);
     panel::register(engine);
     text::register(engine);
+    text_input::register(engine);
+    launcher::register(engine);
     logger::register(engine);
     fps::register(engine);
     workspace::register(engine);
     output::register(engine);
     button::register(engine);
+    clock::register(engine);
+    sysinfo::register(engine);
+    taskbar::register(engine);
+    image::register(engine);
+    icon_theme::register(engine);
+    slider::register(engine);
+    checkbox::register(engine);
+    progress::register(engine);
+    group::register(engine);
     menu::register(engine);
 }
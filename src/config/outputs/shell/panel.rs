@@ -1,20 +1,24 @@
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::rc::Rc;
 
 use calloop::channel::Sender;
-use egui::{Color32, CtxRef};
+use egui::{Color32, CtxRef, Ui};
 use rhai::Engine;
-use rhai::{plugin::*, FLOAT, INT};
+use rhai::{plugin::*, Array, FLOAT, INT};
 
 use crate::config::eventloop::ConfigEvent;
+use crate::config::theme::StyleOverride;
 
-use super::container::{Container, Layout};
+use super::container::{Align, Arrangement, Container, Layout, OutputState};
 use super::widget::Widget;
 
 thread_local! {
     static PANEL_ID: RefCell<i32> = RefCell::new(0);
 }
 
+/// Distance from the screen edge at which an auto-hidden panel slides in
+const EDGE_TRIGGER: f32 = 2.0;
+
 #[derive(Debug, Clone)]
 pub enum PanelPosition {
     Top,
@@ -28,11 +32,21 @@ pub struct PanelInner {
     id: String,
     size: f32,
     layout: Layout,
+    arrangement: Arrangement,
+    style: StyleOverride,
     position: PanelPosition,
     widgets: Vec<Rc<dyn Widget>>,
     alpha: f32,
     background: bool,
     visable: bool,
+    exclusive: bool,
+    autohide: bool,
+    /// Workspaces the panel is shown on, all of them when empty
+    workspaces: Vec<String>,
+    /// Shown on the current workspace and output, as of the last frame
+    shown: Cell<bool>,
+    /// How far the panel is slid in, between 0.0 and 1.0
+    reveal: Cell<f32>,
 }
 
 #[derive(Clone)]
@@ -46,14 +60,21 @@ impl Panel {
             let mut id = id.borrow_mut();
             let panel = Self {
                 inner: Rc::new(RefCell::new(PanelInner {
-                    id: format!("{}", id),
+                    id: format!("panel-{}", id),
                     size,
                     layout,
+                    arrangement: Arrangement::new(),
+                    style: StyleOverride::default(),
                     position,
                     widgets: vec![],
                     alpha: 1.0,
                     background: true,
                     visable: true,
+                    exclusive: true,
+                    autohide: false,
+                    workspaces: Vec::new(),
+                    shown: Cell::new(true),
+                    reveal: Cell::new(1.0),
                 })),
             };
             *id += 1;
@@ -62,61 +83,120 @@ impl Panel {
         })
     }
 }
+impl PanelInner {
+    /// Pointer touches the screen edge, or is over the revealed part of the panel
+    fn pointer_near(&self, screen: egui::Rect, pointer: Option<egui::Pos2>) -> bool {
+        let pointer = match pointer {
+            Some(pointer) => pointer,
+            None => return false,
+        };
+        let reach = (self.size * self.reveal.get()).max(EDGE_TRIGGER);
+
+        match self.position {
+            PanelPosition::Top => pointer.y <= screen.top() + reach,
+            PanelPosition::Bottom => pointer.y >= screen.bottom() - reach,
+            PanelPosition::Left => pointer.x <= screen.left() + reach,
+            PanelPosition::Right => pointer.x >= screen.right() - reach,
+        }
+    }
+}
+
 impl Container for Panel {
-    fn render(&self, ctx: &CtxRef, config_tx: &Sender<ConfigEvent>) {
+    fn render(&self, ctx: &CtxRef, output: &OutputState, config_tx: &Sender<ConfigEvent>) {
         let inner = self.inner.borrow();
-        if inner.visable {
+
+        let shown = inner.visable
+            && !output.fullscreen
+            && (inner.workspaces.is_empty() || inner.workspaces.contains(&output.workspace));
+        inner.shown.set(shown);
+
+        let target = shown
+            && (!inner.autohide || inner.pointer_near(ctx.input().screen_rect(), output.pointer));
+        let reveal = ctx.animate_bool(egui::Id::new(&inner.id).with("reveal"), target);
+        inner.reveal.set(reveal);
+
+        if reveal > 0.0 {
+            let size = inner.size * reveal;
             let mut frame = egui::Frame::window(&ctx.style());
             if !inner.background {
                 frame.fill = Color32::TRANSPARENT;
                 frame.stroke.width = 0.0;
             } else {
+                inner.style.apply_frame(&mut frame);
                 frame.fill[3] = (inner.alpha * 255.0) as u8;
             }
+
+            let add_contents = |ui: &mut Ui| {
+                inner.style.apply(ui);
+                inner
+                    .layout
+                    .render(ui, &inner.widgets, &inner.arrangement, config_tx);
+            };
+
             match inner.position {
                 PanelPosition::Top => {
                     egui::TopBottomPanel::top(&inner.id)
-                        .max_height(inner.size)
-                        .min_height(inner.size)
+                        .max_height(size)
+                        .min_height(size)
                         .frame(frame)
-                        .show(ctx, |ui| {
-                            inner.layout.render(ui, &inner.widgets, config_tx);
-                        });
+                        .show(ctx, add_contents);
                 }
                 PanelPosition::Bottom => {
                     egui::TopBottomPanel::bottom(&inner.id)
-                        .max_height(inner.size)
-                        .min_height(inner.size)
+                        .max_height(size)
+                        .min_height(size)
                         .frame(frame)
-                        .show(ctx, |ui| {
-                            inner.layout.render(ui, &inner.widgets, config_tx);
-                        });
+                        .show(ctx, add_contents);
                 }
                 PanelPosition::Left => {
                     egui::SidePanel::left(&inner.id)
-                        .max_width(inner.size)
-                        .min_width(inner.size)
+                        .max_width(size)
+                        .min_width(size)
                         .frame(frame)
-                        .show(ctx, |ui| {
-                            inner.layout.render(ui, &inner.widgets, config_tx);
-                        });
+                        .show(ctx, add_contents);
                 }
                 PanelPosition::Right => {
                     egui::SidePanel::right(&inner.id)
-                        .max_width(inner.size)
-                        .min_width(inner.size)
+                        .max_width(size)
+                        .min_width(size)
                         .frame(frame)
-                        .show(ctx, |ui| {
-                            inner.layout.render(ui, &inner.widgets, config_tx);
-                        });
+                        .show(ctx, add_contents);
                 }
             };
         }
     }
+
+    fn id(&self) -> String {
+        self.inner.borrow().id.clone()
+    }
+
+    fn to_dynamic(&self) -> Dynamic {
+        Dynamic::from(self.clone())
+    }
+
+    fn exclusive_zone(&self) -> Option<(PanelPosition, f32)> {
+        let inner = self.inner.borrow();
+        if inner.shown.get() && inner.exclusive && !inner.autohide {
+            Some((inner.position.clone(), inner.size))
+        } else {
+            None
+        }
+    }
 }
 
 #[export_module]
 pub mod panel {
+    /// Used by the shell to find, replace or remove the panel
+    #[rhai_fn(get = "id", pure)]
+    pub fn id(panel: &mut Panel) -> String {
+        panel.inner.borrow().id.clone()
+    }
+
+    #[rhai_fn(set = "id", pure)]
+    pub fn set_id(panel: &mut Panel, id: String) {
+        panel.inner.borrow_mut().id = id;
+    }
+
     #[rhai_fn(get = "size", pure)]
     pub fn size(panel: &mut Panel) -> INT {
         panel.inner.borrow().size as _
@@ -137,6 +217,41 @@ pub mod panel {
         panel.inner.borrow_mut().layout = layout;
     }
 
+    #[rhai_fn(get = "align", pure)]
+    pub fn align(panel: &mut Panel) -> Align {
+        panel.inner.borrow().arrangement.align
+    }
+
+    #[rhai_fn(set = "align", pure)]
+    pub fn set_align(panel: &mut Panel, align: Align) {
+        panel.inner.borrow_mut().arrangement.align = align;
+    }
+
+    #[rhai_fn(set = "spacing", pure)]
+    pub fn set_spacing(panel: &mut Panel, spacing: FLOAT) {
+        panel.inner.borrow_mut().arrangement.spacing = Some(spacing as _);
+    }
+
+    #[rhai_fn(get = "padding", pure)]
+    pub fn padding(panel: &mut Panel) -> FLOAT {
+        panel.inner.borrow().arrangement.padding as _
+    }
+
+    #[rhai_fn(set = "padding", pure)]
+    pub fn set_padding(panel: &mut Panel, padding: FLOAT) {
+        panel.inner.borrow_mut().arrangement.padding = padding as _;
+    }
+
+    #[rhai_fn(get = "style", pure)]
+    pub fn style(panel: &mut Panel) -> StyleOverride {
+        panel.inner.borrow().style.clone()
+    }
+
+    #[rhai_fn(set = "style", pure)]
+    pub fn set_style(panel: &mut Panel, style: StyleOverride) {
+        panel.inner.borrow_mut().style = style;
+    }
+
     #[rhai_fn(get = "alpha", pure)]
     pub fn alpha(panel: &mut Panel) -> INT {
         panel.inner.borrow_mut().alpha as _
@@ -167,6 +282,46 @@ pub mod panel {
         panel.inner.borrow_mut().visable = visable
     }
 
+    /// Keep windows from being placed or maximized under the panel, auto-hidden panels never do
+    #[rhai_fn(get = "exclusive", pure)]
+    pub fn exclusive(panel: &mut Panel) -> bool {
+        panel.inner.borrow().exclusive
+    }
+
+    #[rhai_fn(set = "exclusive", pure)]
+    pub fn set_exclusive(panel: &mut Panel, exclusive: bool) {
+        panel.inner.borrow_mut().exclusive = exclusive
+    }
+
+    /// Slide the panel in only when the pointer touches its screen edge
+    #[rhai_fn(get = "autohide", pure)]
+    pub fn autohide(panel: &mut Panel) -> bool {
+        panel.inner.borrow().autohide
+    }
+
+    #[rhai_fn(set = "autohide", pure)]
+    pub fn set_autohide(panel: &mut Panel, autohide: bool) {
+        panel.inner.borrow_mut().autohide = autohide
+    }
+
+    /// Names of the workspaces to show the panel on, an empty array shows it on all of them
+    #[rhai_fn(get = "workspaces", pure)]
+    pub fn workspaces(panel: &mut Panel) -> Array {
+        panel
+            .inner
+            .borrow()
+            .workspaces
+            .iter()
+            .cloned()
+            .map(Dynamic::from)
+            .collect()
+    }
+
+    #[rhai_fn(set = "workspaces", pure)]
+    pub fn set_workspaces(panel: &mut Panel, workspaces: Array) {
+        panel.inner.borrow_mut().workspaces = workspaces.iter().map(|w| w.to_string()).collect();
+    }
+
     #[rhai_fn(global)]
     pub fn add_widget(panel: &mut Panel, widget: Rc<dyn Widget>) {
         panel.inner.borrow_mut().widgets.push(widget);
@@ -0,0 +1,120 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use egui::Ui;
+use rhai::plugin::*;
+use rhai::{Engine, FLOAT};
+
+use super::widget::*;
+
+#[derive(Debug)]
+struct ProgressInner {
+    /// Between 0.0 and 1.0
+    progress: f32,
+    text: String,
+    show_percentage: bool,
+    width: Option<f32>,
+    updated: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct Progress(Rc<RefCell<ProgressInner>>);
+
+impl Progress {
+    pub fn new(progress: FLOAT) -> Self {
+        Self(Rc::new(RefCell::new(ProgressInner {
+            progress: progress.max(0.0).min(1.0) as _,
+            text: String::new(),
+            show_percentage: false,
+            width: None,
+            updated: true,
+        })))
+    }
+}
+
+impl Widget for Progress {
+    fn render(&self, ui: &mut Ui, _config_tx: &Sender<ConfigEvent>) {
+        let mut inner = self.0.borrow_mut();
+
+        if inner.updated {
+            ui.ctx().request_repaint();
+            inner.updated = false;
+        }
+
+        let mut bar = egui::ProgressBar::new(inner.progress);
+        if !inner.text.is_empty() {
+            bar = bar.text(inner.text.as_str());
+        } else if inner.show_percentage {
+            bar = bar.show_percentage();
+        }
+        if let Some(width) = inner.width {
+            bar = bar.desired_width(width);
+        }
+
+        ui.add(bar);
+    }
+}
+
+#[export_module]
+pub mod progress {
+    #[rhai_fn(get = "progress", pure)]
+    pub fn progress(progress: &mut Progress) -> FLOAT {
+        progress.0.borrow().progress as _
+    }
+
+    #[rhai_fn(set = "progress", pure)]
+    pub fn set_progress(progress: &mut Progress, value: FLOAT) {
+        update(progress, value);
+    }
+
+    #[rhai_fn(global)]
+    pub fn update(progress: &mut Progress, value: FLOAT) {
+        let mut inner = progress.0.borrow_mut();
+        inner.progress = value.max(0.0).min(1.0) as _;
+        inner.updated = true;
+    }
+
+    /// Text shown inside the bar, takes precedence over `show_percentage`
+    #[rhai_fn(get = "text", pure)]
+    pub fn text(progress: &mut Progress) -> String {
+        progress.0.borrow().text.clone()
+    }
+
+    #[rhai_fn(set = "text", pure)]
+    pub fn set_text(progress: &mut Progress, text: String) {
+        let mut inner = progress.0.borrow_mut();
+        inner.text = text;
+        inner.updated = true;
+    }
+
+    #[rhai_fn(get = "show_percentage", pure)]
+    pub fn show_percentage(progress: &mut Progress) -> bool {
+        progress.0.borrow().show_percentage
+    }
+
+    #[rhai_fn(set = "show_percentage", pure)]
+    pub fn set_show_percentage(progress: &mut Progress, show_percentage: bool) {
+        let mut inner = progress.0.borrow_mut();
+        inner.show_percentage = show_percentage;
+        inner.updated = true;
+    }
+
+    #[rhai_fn(set = "width", pure)]
+    pub fn set_width(progress: &mut Progress, width: FLOAT) {
+        let mut inner = progress.0.borrow_mut();
+        inner.width = Some(width as _);
+        inner.updated = true;
+    }
+
+    #[rhai_fn(global)]
+    pub fn convert(progress: &mut Progress) -> Rc<dyn Widget> {
+        Rc::new(progress.clone())
+    }
+}
+
+pub fn register(engine: &mut Engine) {
+    let progress_module = exported_module!(progress);
+    engine
+        .register_static_module("progress", progress_module.into())
+        .register_type::<Progress>();
+}
@@ -0,0 +1,134 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use egui::Ui;
+use rhai::plugin::*;
+use rhai::{Engine, FnPtr, FLOAT};
+
+use super::widget::*;
+
+#[derive(Debug)]
+struct SliderInner {
+    min: FLOAT,
+    max: FLOAT,
+    value: FLOAT,
+    label: String,
+    on_change: Option<FnPtr>,
+    updated: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct Slider(Rc<RefCell<SliderInner>>);
+
+impl Slider {
+    pub fn new(min: FLOAT, max: FLOAT, value: FLOAT, on_change: Option<FnPtr>) -> Self {
+        Self(Rc::new(RefCell::new(SliderInner {
+            min,
+            max,
+            value: value.max(min).min(max),
+            label: String::new(),
+            on_change,
+            updated: true,
+        })))
+    }
+}
+
+impl Widget for Slider {
+    fn render(&self, ui: &mut Ui, config_tx: &Sender<ConfigEvent>) {
+        let mut inner = self.0.borrow_mut();
+        let inner = &mut *inner;
+
+        if inner.updated {
+            ui.ctx().request_repaint();
+            inner.updated = false;
+        }
+
+        let slider =
+            egui::Slider::new(&mut inner.value, inner.min..=inner.max).text(inner.label.as_str());
+
+        if ui.add(slider).changed() {
+            if let Some(on_change) = &inner.on_change {
+                config_tx
+                    .send(ConfigEvent::ShellWithArgs(
+                        on_change.clone(),
+                        vec![inner.value.into()],
+                    ))
+                    .unwrap();
+            }
+        }
+    }
+}
+
+#[export_module]
+pub mod slider {
+    #[rhai_fn(get = "value", pure)]
+    pub fn value(slider: &mut Slider) -> FLOAT {
+        slider.0.borrow().value
+    }
+
+    #[rhai_fn(set = "value", pure)]
+    pub fn set_value(slider: &mut Slider, value: FLOAT) {
+        update(slider, value);
+    }
+
+    /// Set the value without calling `on_change`, e.g. when the volume changed elsewhere
+    #[rhai_fn(global)]
+    pub fn update(slider: &mut Slider, value: FLOAT) {
+        let mut inner = slider.0.borrow_mut();
+        inner.value = value.max(inner.min).min(inner.max);
+        inner.updated = true;
+    }
+
+    #[rhai_fn(get = "min", pure)]
+    pub fn min(slider: &mut Slider) -> FLOAT {
+        slider.0.borrow().min
+    }
+
+    #[rhai_fn(set = "min", pure)]
+    pub fn set_min(slider: &mut Slider, min: FLOAT) {
+        let mut inner = slider.0.borrow_mut();
+        inner.min = min;
+        inner.updated = true;
+    }
+
+    #[rhai_fn(get = "max", pure)]
+    pub fn max(slider: &mut Slider) -> FLOAT {
+        slider.0.borrow().max
+    }
+
+    #[rhai_fn(set = "max", pure)]
+    pub fn set_max(slider: &mut Slider, max: FLOAT) {
+        let mut inner = slider.0.borrow_mut();
+        inner.max = max;
+        inner.updated = true;
+    }
+
+    #[rhai_fn(get = "label", pure)]
+    pub fn label(slider: &mut Slider) -> String {
+        slider.0.borrow().label.clone()
+    }
+
+    #[rhai_fn(set = "label", pure)]
+    pub fn set_label(slider: &mut Slider, label: String) {
+        let mut inner = slider.0.borrow_mut();
+        inner.label = label;
+        inner.updated = true;
+    }
+
+    #[rhai_fn(global)]
+    pub fn on_change(slider: &mut Slider, on_change: FnPtr) {
+        slider.0.borrow_mut().on_change = Some(on_change);
+    }
+
+    #[rhai_fn(global)]
+    pub fn convert(slider: &mut Slider) -> Rc<dyn Widget> {
+        Rc::new(slider.clone())
+    }
+}
+
+pub fn register(engine: &mut Engine) {
+    let slider_module = exported_module!(slider);
+    engine
+        .register_static_module("slider", slider_module.into())
+        .register_type::<Slider>();
+}
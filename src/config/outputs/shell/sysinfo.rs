@@ -0,0 +1,632 @@
+use std::cell::RefCell;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use egui::{Color32, Ui};
+use rhai::plugin::*;
+use rhai::{Engine, FLOAT, INT};
+
+use super::ticker::{self, Tick};
+use super::widget::*;
+
+const WARNING_COLOR: Color32 = Color32::from_rgb(230, 80, 80);
+/// Reads are done on the event loop, so do not let a tiny interval keep it busy
+const MIN_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Values read from the system, `value` is the number compared with the warning threshold
+#[derive(Debug, Default)]
+struct Reading {
+    value: Option<f64>,
+    fields: Vec<(&'static str, String)>,
+}
+
+impl Reading {
+    fn field(mut self, name: &'static str, value: impl ToString) -> Self {
+        self.fields.push((name, value.to_string()));
+        self
+    }
+
+    /// Replace every `{name}` in the format with the matching field
+    fn format(&self, format: &str) -> String {
+        self.fields
+            .iter()
+            .fold(format.to_owned(), |text, (name, value)| {
+                text.replace(&format!("{{{}}}", name), value)
+            })
+    }
+}
+
+fn read_trimmed(path: &Path) -> Option<String> {
+    fs::read_to_string(path).ok().map(|s| s.trim().to_owned())
+}
+
+fn read_number(path: &Path) -> Option<f64> {
+    read_trimmed(path)?.parse().ok()
+}
+
+fn human_bytes(bytes: f64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+
+    let mut value = bytes;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+
+    format!("{:.1}{}", value, UNITS[unit])
+}
+
+/// Source of a system status widget, holds the state needed between polls
+#[derive(Debug, Clone)]
+enum Source {
+    Battery,
+    Cpu {
+        previous: Option<(u64, u64)>,
+    },
+    Memory,
+    Temperature {
+        sensor: Option<String>,
+    },
+    Network {
+        interface: Option<String>,
+        previous: Option<(u64, u64, Instant)>,
+    },
+}
+
+impl Source {
+    fn default_format(&self) -> &'static str {
+        match self {
+            Source::Battery => "BAT {percent}% {status}",
+            Source::Cpu { .. } => "CPU {percent}%",
+            Source::Memory => "MEM {percent}%",
+            Source::Temperature { .. } => "{temp}°C",
+            Source::Network { .. } => "{interface} ↓{rx} ↑{tx}",
+        }
+    }
+
+    /// Battery warns when the value drops below the threshold, everything else when it rises above
+    fn warn_below(&self) -> bool {
+        matches!(self, Source::Battery)
+    }
+
+    fn read(&mut self, root: &Path) -> Option<Reading> {
+        match self {
+            Source::Battery => read_battery(root),
+            Source::Cpu { previous } => read_cpu(root, previous),
+            Source::Memory => read_memory(root),
+            Source::Temperature { sensor } => read_temperature(root, sensor.as_deref()),
+            Source::Network {
+                interface,
+                previous,
+            } => read_network(root, interface.as_deref(), previous),
+        }
+    }
+}
+
+fn read_battery(root: &Path) -> Option<Reading> {
+    let supplies = fs::read_dir(root.join("sys/class/power_supply")).ok()?;
+
+    let battery = supplies
+        .flatten()
+        .map(|entry| entry.path())
+        .find(|path| read_trimmed(&path.join("type")).as_deref() == Some("Battery"))?;
+
+    let percent = read_number(&battery.join("capacity"))?;
+    let status = read_trimmed(&battery.join("status")).unwrap_or_else(|| "Unknown".into());
+
+    Some(
+        Reading {
+            value: Some(percent),
+            ..Default::default()
+        }
+        .field("percent", percent)
+        .field("status", status),
+    )
+}
+
+/// Busy and total jiffies of all cpus from the first line of `/proc/stat`
+fn read_cpu_times(root: &Path) -> Option<(u64, u64)> {
+    let stat = fs::read_to_string(root.join("proc/stat")).ok()?;
+    let line = stat.lines().find(|line| line.starts_with("cpu "))?;
+
+    let times: Vec<u64> = line
+        .split_whitespace()
+        .skip(1)
+        .filter_map(|t| t.parse().ok())
+        .collect();
+
+    // idle + iowait
+    let idle = times.get(3)? + times.get(4).unwrap_or(&0);
+    let total: u64 = times.iter().sum();
+
+    Some((total - idle, total))
+}
+
+fn read_cpu(root: &Path, previous: &mut Option<(u64, u64)>) -> Option<Reading> {
+    let (busy, total) = read_cpu_times(root)?;
+
+    let percent = match previous.replace((busy, total)) {
+        Some((prev_busy, prev_total)) if total > prev_total => {
+            (busy.saturating_sub(prev_busy)) as f64 / (total - prev_total) as f64 * 100.0
+        }
+        _ => busy as f64 / total.max(1) as f64 * 100.0,
+    };
+    let percent = percent.round();
+
+    Some(
+        Reading {
+            value: Some(percent),
+            ..Default::default()
+        }
+        .field("percent", percent),
+    )
+}
+
+fn read_memory(root: &Path) -> Option<Reading> {
+    let meminfo = fs::read_to_string(root.join("proc/meminfo")).ok()?;
+
+    // Values in /proc/meminfo are in KiB
+    let value = |key: &str| -> Option<f64> {
+        meminfo
+            .lines()
+            .find(|line| line.starts_with(key))?
+            .split_whitespace()
+            .nth(1)?
+            .parse::<f64>()
+            .ok()
+            .map(|kib| kib * 1024.0)
+    };
+
+    let total = value("MemTotal:")?;
+    let available = value("MemAvailable:").or_else(|| value("MemFree:"))?;
+    let used = total - available;
+    let percent = (used / total.max(1.0) * 100.0).round();
+
+    Some(
+        Reading {
+            value: Some(percent),
+            ..Default::default()
+        }
+        .field("percent", percent)
+        .field("used", human_bytes(used))
+        .field("total", human_bytes(total)),
+    )
+}
+
+fn read_temperature(root: &Path, sensor: Option<&str>) -> Option<Reading> {
+    let hwmons = fs::read_dir(root.join("sys/class/hwmon")).ok()?;
+
+    let mut hwmons: Vec<PathBuf> = hwmons.flatten().map(|entry| entry.path()).collect();
+    hwmons.sort();
+
+    let hwmon = hwmons.into_iter().find(|path| match sensor {
+        Some(sensor) => read_trimmed(&path.join("name")).as_deref() == Some(sensor),
+        None => path.join("temp1_input").exists(),
+    })?;
+
+    // Temperatures are reported in millidegree Celsius
+    let temp = (read_number(&hwmon.join("temp1_input"))? / 1000.0).round();
+    let name = read_trimmed(&hwmon.join("name")).unwrap_or_default();
+
+    Some(
+        Reading {
+            value: Some(temp),
+            ..Default::default()
+        }
+        .field("temp", temp)
+        .field("sensor", name),
+    )
+}
+
+fn read_network(
+    root: &Path,
+    interface: Option<&str>,
+    previous: &mut Option<(u64, u64, Instant)>,
+) -> Option<Reading> {
+    let net = root.join("sys/class/net");
+
+    let interface = match interface {
+        Some(interface) => interface.to_owned(),
+        None => {
+            let mut interfaces: Vec<String> = fs::read_dir(&net)
+                .ok()?
+                .flatten()
+                .filter_map(|entry| entry.file_name().into_string().ok())
+                .filter(|name| name != "lo")
+                .collect();
+            interfaces.sort();
+
+            interfaces.into_iter().find(|name| {
+                read_trimmed(&net.join(name).join("operstate")).as_deref() == Some("up")
+            })?
+        }
+    };
+
+    let path = net.join(&interface);
+    let state = read_trimmed(&path.join("operstate")).unwrap_or_else(|| "unknown".into());
+    let rx = read_number(&path.join("statistics/rx_bytes"))? as u64;
+    let tx = read_number(&path.join("statistics/tx_bytes"))? as u64;
+
+    let now = Instant::now();
+    let (rx_rate, tx_rate) = match previous.replace((rx, tx, now)) {
+        Some((prev_rx, prev_tx, prev_time)) => {
+            let elapsed = now.duration_since(prev_time).as_secs_f64().max(0.001);
+            (
+                rx.saturating_sub(prev_rx) as f64 / elapsed,
+                tx.saturating_sub(prev_tx) as f64 / elapsed,
+            )
+        }
+        None => (0.0, 0.0),
+    };
+
+    Some(
+        Reading::default()
+            .field("interface", interface)
+            .field("state", state)
+            .field("rx", format!("{}/s", human_bytes(rx_rate)))
+            .field("tx", format!("{}/s", human_bytes(tx_rate)))
+            .field("rx_total", human_bytes(rx as f64))
+            .field("tx_total", human_bytes(tx as f64)),
+    )
+}
+
+#[derive(Debug)]
+struct SysInfoInner {
+    source: Source,
+    root: PathBuf,
+    interval: Duration,
+    format: String,
+    warning: Option<f64>,
+
+    text: String,
+    warn: bool,
+}
+
+/// Widget that periodically reads system status from procfs and sysfs
+#[derive(Debug, Clone)]
+pub struct SysInfo(Rc<RefCell<SysInfoInner>>);
+
+impl SysInfo {
+    fn new(source: Source, interval: Duration) -> Self {
+        let sysinfo = Self(Rc::new(RefCell::new(SysInfoInner {
+            format: source.default_format().into(),
+            source,
+            root: PathBuf::from("/"),
+            interval,
+            warning: None,
+            text: String::new(),
+            warn: false,
+        })));
+        // The first read happens once the config is done setting the widget up
+        ticker::start(sysinfo.0.clone());
+        sysinfo
+    }
+
+    pub fn battery() -> Self {
+        Self::new(Source::Battery, Duration::from_secs(30))
+    }
+
+    pub fn cpu() -> Self {
+        Self::new(Source::Cpu { previous: None }, Duration::from_secs(2))
+    }
+
+    pub fn memory() -> Self {
+        Self::new(Source::Memory, Duration::from_secs(5))
+    }
+
+    pub fn temperature() -> Self {
+        Self::new(Source::Temperature { sensor: None }, Duration::from_secs(2))
+    }
+
+    pub fn network() -> Self {
+        Self::new(
+            Source::Network {
+                interface: None,
+                previous: None,
+            },
+            Duration::from_secs(2),
+        )
+    }
+}
+
+impl SysInfoInner {
+    fn poll(&mut self) -> bool {
+        let (text, warn) = match self.source.read(&self.root) {
+            Some(reading) => {
+                let warn = match (reading.value, self.warning) {
+                    (Some(value), Some(warning)) if self.source.warn_below() => value <= warning,
+                    (Some(value), Some(warning)) => value >= warning,
+                    _ => false,
+                };
+                (reading.format(&self.format), warn)
+            }
+            None => ("N/A".into(), false),
+        };
+
+        let changed = text != self.text || warn != self.warn;
+        self.text = text;
+        self.warn = warn;
+        changed
+    }
+}
+
+impl Tick for RefCell<SysInfoInner> {
+    fn tick(&self) -> (bool, Duration) {
+        let mut inner = self.borrow_mut();
+        (inner.poll(), inner.interval.max(MIN_INTERVAL))
+    }
+}
+
+impl Widget for SysInfo {
+    fn render(&self, ui: &mut Ui, _config_tx: &Sender<ConfigEvent>) {
+        let inner = self.0.borrow();
+
+        if inner.warn {
+            ui.colored_label(WARNING_COLOR, &inner.text);
+        } else {
+            ui.label(&inner.text);
+        }
+    }
+}
+
+#[export_module]
+pub mod sysinfo {
+    /// Interval between reads in milliseconds
+    #[rhai_fn(get = "interval", pure)]
+    pub fn interval(sysinfo: &mut SysInfo) -> INT {
+        sysinfo.0.borrow().interval.as_millis() as _
+    }
+
+    #[rhai_fn(set = "interval", pure)]
+    pub fn set_interval(sysinfo: &mut SysInfo, interval: INT) {
+        sysinfo.0.borrow_mut().interval = Duration::from_millis(interval.max(0) as _);
+    }
+
+    #[rhai_fn(get = "format", pure)]
+    pub fn format(sysinfo: &mut SysInfo) -> String {
+        sysinfo.0.borrow().format.clone()
+    }
+
+    #[rhai_fn(set = "format", pure)]
+    pub fn set_format(sysinfo: &mut SysInfo, format: String) {
+        sysinfo.0.borrow_mut().format = format;
+    }
+
+    #[rhai_fn(get = "warning", pure)]
+    pub fn warning(sysinfo: &mut SysInfo) -> Dynamic {
+        sysinfo
+            .0
+            .borrow()
+            .warning
+            .map(Dynamic::from)
+            .unwrap_or(Dynamic::UNIT)
+    }
+
+    #[rhai_fn(set = "warning", pure)]
+    pub fn set_warning(sysinfo: &mut SysInfo, warning: FLOAT) {
+        sysinfo.0.borrow_mut().warning = Some(warning);
+    }
+
+    #[rhai_fn(set = "warning", pure)]
+    pub fn set_warning_int(sysinfo: &mut SysInfo, warning: INT) {
+        sysinfo.0.borrow_mut().warning = Some(warning as _);
+    }
+
+    /// Directory used in place of `/`, allows reading from a fake sysfs and procfs
+    #[rhai_fn(get = "root", pure)]
+    pub fn root(sysinfo: &mut SysInfo) -> String {
+        sysinfo.0.borrow().root.to_string_lossy().into_owned()
+    }
+
+    #[rhai_fn(set = "root", pure)]
+    pub fn set_root(sysinfo: &mut SysInfo, root: &str) {
+        sysinfo.0.borrow_mut().root = PathBuf::from(root);
+    }
+
+    /// hwmon name of the temperature sensor, or interface name for network widgets
+    #[rhai_fn(set = "device", pure)]
+    pub fn set_device(sysinfo: &mut SysInfo, device: &str) {
+        let mut inner = sysinfo.0.borrow_mut();
+        let device = Some(device.to_owned()).filter(|d| !d.is_empty());
+
+        match &mut inner.source {
+            Source::Temperature { sensor } => *sensor = device,
+            Source::Network {
+                interface,
+                previous,
+            } => {
+                *interface = device;
+                *previous = None;
+            }
+            _ => warn!("device is not supported by this widget, ignoring"),
+        }
+    }
+
+    #[rhai_fn(global)]
+    pub fn convert(sysinfo: &mut SysInfo) -> Rc<dyn Widget> {
+        Rc::new(sysinfo.clone())
+    }
+}
+
+pub fn register(engine: &mut Engine) {
+    let sysinfo_module = exported_module!(sysinfo);
+    engine
+        .register_static_module("sysinfo", sysinfo_module.into())
+        .register_type::<SysInfo>();
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    /// Directory standing in for `/`, removed again once dropped
+    struct FakeRoot(PathBuf);
+
+    impl FakeRoot {
+        fn new() -> Self {
+            static COUNT: AtomicUsize = AtomicUsize::new(0);
+
+            let path = std::env::temp_dir().join(format!(
+                "anodium-sysinfo-{}-{}",
+                std::process::id(),
+                COUNT.fetch_add(1, Ordering::SeqCst)
+            ));
+            fs::create_dir_all(&path).unwrap();
+            Self(path)
+        }
+
+        fn write(&self, path: &str, content: &str) -> &Self {
+            let path = self.0.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+            self
+        }
+    }
+
+    impl Drop for FakeRoot {
+        fn drop(&mut self) {
+            fs::remove_dir_all(&self.0).ok();
+        }
+    }
+
+    #[test]
+    fn battery() {
+        let root = FakeRoot::new();
+        root.write("sys/class/power_supply/AC/type", "Mains\n")
+            .write("sys/class/power_supply/BAT0/type", "Battery\n")
+            .write("sys/class/power_supply/BAT0/capacity", "42\n")
+            .write("sys/class/power_supply/BAT0/status", "Discharging\n");
+
+        let reading = read_battery(&root.0).unwrap();
+        assert_eq!(reading.value, Some(42.0));
+        assert_eq!(reading.format("{percent}% {status}"), "42% Discharging");
+    }
+
+    #[test]
+    fn battery_missing_files() {
+        let root = FakeRoot::new();
+        assert!(read_battery(&root.0).is_none());
+
+        root.write("sys/class/power_supply/BAT0/type", "Battery\n");
+        assert!(read_battery(&root.0).is_none());
+
+        root.write("sys/class/power_supply/BAT0/capacity", "80\n");
+        let reading = read_battery(&root.0).unwrap();
+        assert_eq!(reading.format("{status}"), "Unknown");
+    }
+
+    #[test]
+    fn cpu() {
+        let root = FakeRoot::new();
+        root.write(
+            "proc/stat",
+            "cpu  100 0 100 700 100 0 0 0 0 0\ncpu0 1 2 3 4\n",
+        );
+
+        let mut previous = None;
+        let reading = read_cpu(&root.0, &mut previous).unwrap();
+        assert_eq!(reading.value, Some(20.0));
+
+        // Later readings only look at the time passed since the previous one
+        root.write("proc/stat", "cpu  150 0 150 750 150 0 0 0 0 0\n");
+        let reading = read_cpu(&root.0, &mut previous).unwrap();
+        assert_eq!(reading.value, Some(50.0));
+        assert_eq!(reading.format("{percent}%"), "50%");
+    }
+
+    #[test]
+    fn cpu_missing_files() {
+        let root = FakeRoot::new();
+        assert!(read_cpu(&root.0, &mut None).is_none());
+
+        root.write("proc/stat", "intr 0\n");
+        assert!(read_cpu(&root.0, &mut None).is_none());
+    }
+
+    #[test]
+    fn memory() {
+        let root = FakeRoot::new();
+        root.write(
+            "proc/meminfo",
+            "MemTotal:        1048576 kB\nMemFree:          131072 kB\nMemAvailable:     262144 kB\n",
+        );
+
+        let reading = read_memory(&root.0).unwrap();
+        assert_eq!(reading.value, Some(75.0));
+        assert_eq!(reading.format("{used}/{total}"), "768.0MiB/1.0GiB");
+    }
+
+    #[test]
+    fn memory_missing_files() {
+        let root = FakeRoot::new();
+        assert!(read_memory(&root.0).is_none());
+
+        // Older kernels have no MemAvailable
+        root.write(
+            "proc/meminfo",
+            "MemTotal:        1048576 kB\nMemFree:          524288 kB\n",
+        );
+        assert_eq!(read_memory(&root.0).unwrap().value, Some(50.0));
+
+        root.write("proc/meminfo", "MemFree:          524288 kB\n");
+        assert!(read_memory(&root.0).is_none());
+    }
+
+    #[test]
+    fn temperature() {
+        let root = FakeRoot::new();
+        root.write("sys/class/hwmon/hwmon0/name", "acpitz\n")
+            .write("sys/class/hwmon/hwmon0/temp1_input", "45000\n")
+            .write("sys/class/hwmon/hwmon1/name", "k10temp\n")
+            .write("sys/class/hwmon/hwmon1/temp1_input", "61500\n");
+
+        let reading = read_temperature(&root.0, None).unwrap();
+        assert_eq!(reading.value, Some(45.0));
+        assert_eq!(reading.format("{sensor}"), "acpitz");
+
+        let reading = read_temperature(&root.0, Some("k10temp")).unwrap();
+        assert_eq!(reading.value, Some(62.0));
+    }
+
+    #[test]
+    fn temperature_missing_files() {
+        let root = FakeRoot::new();
+        assert!(read_temperature(&root.0, None).is_none());
+
+        root.write("sys/class/hwmon/hwmon0/name", "acpitz\n");
+        assert!(read_temperature(&root.0, None).is_none());
+        assert!(read_temperature(&root.0, Some("acpitz")).is_none());
+        assert!(read_temperature(&root.0, Some("k10temp")).is_none());
+    }
+
+    #[test]
+    fn network() {
+        let root = FakeRoot::new();
+        root.write("sys/class/net/lo/operstate", "unknown\n")
+            .write("sys/class/net/eth0/operstate", "down\n")
+            .write("sys/class/net/wlan0/operstate", "up\n")
+            .write("sys/class/net/wlan0/statistics/rx_bytes", "2048\n")
+            .write("sys/class/net/wlan0/statistics/tx_bytes", "1024\n");
+
+        let reading = read_network(&root.0, None, &mut None).unwrap();
+        assert_eq!(
+            reading.format("{interface} {state} {rx_total} {tx_total} {rx}"),
+            "wlan0 up 2.0KiB 1.0KiB 0.0B/s"
+        );
+    }
+
+    #[test]
+    fn network_missing_files() {
+        let root = FakeRoot::new();
+        assert!(read_network(&root.0, None, &mut None).is_none());
+
+        root.write("sys/class/net/eth0/operstate", "down\n");
+        assert!(read_network(&root.0, None, &mut None).is_none());
+        assert!(read_network(&root.0, Some("eth0"), &mut None).is_none());
+    }
+}
@@ -0,0 +1,146 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use egui::Ui;
+use rhai::plugin::*;
+use rhai::Engine;
+
+use crate::output_manager::Output;
+use crate::window::Window;
+
+use super::icon_theme;
+use super::widget::*;
+
+const ICON_SIZE: u32 = 16;
+
+#[derive(Debug)]
+struct Inner {
+    current_workspace_only: bool,
+    show_icons: bool,
+    /// Windows in the order they first showed up, so raising a window does not reorder the taskbar
+    order: Vec<Window>,
+}
+
+/// Lists the toplevels of an output, click focuses or minimizes, middle click closes
+#[derive(Debug, Clone)]
+pub struct Taskbar {
+    output: Output,
+    inner: Rc<RefCell<Inner>>,
+}
+
+impl Taskbar {
+    pub fn new(output: Output) -> Self {
+        Self {
+            output,
+            inner: Rc::new(RefCell::new(Inner {
+                current_workspace_only: false,
+                show_icons: true,
+                order: Vec::new(),
+            })),
+        }
+    }
+}
+
+impl Widget for Taskbar {
+    fn render(&self, ui: &mut Ui, config_tx: &Sender<ConfigEvent>) {
+        let mut inner = self.inner.borrow_mut();
+        let inner = &mut *inner;
+
+        let mut windows = self.output.windows();
+
+        if inner.current_workspace_only {
+            let active = self
+                .output
+                .workspaces()
+                .into_iter()
+                .find(|w| w.active)
+                .map(|w| w.name);
+            windows.retain(|w| Some(&w.workspace) == active.as_ref());
+        }
+
+        inner
+            .order
+            .retain(|window| windows.iter().any(|w| &w.window == window));
+        for info in windows.iter() {
+            if !inner.order.contains(&info.window) {
+                inner.order.push(info.window.clone());
+            }
+        }
+
+        let order = &inner.order;
+        windows.sort_by_key(|w| order.iter().position(|window| window == &w.window));
+
+        for info in windows.iter() {
+            let title = if info.title.is_empty() {
+                &info.app_id
+            } else {
+                &info.title
+            };
+
+            let show_icons = inner.show_icons;
+            let mut response = ui
+                .horizontal(|ui| {
+                    if show_icons {
+                        if let Some(icon) = icon_theme::load_app_icon(&info.app_id, ICON_SIZE) {
+                            icon.paint(ui);
+                        }
+                    }
+                    ui.selectable_label(info.focused, title)
+                })
+                .inner;
+            if !info.app_id.is_empty() {
+                response = response.on_hover_text(&info.app_id);
+            }
+
+            let event = if response.middle_clicked() {
+                Some(ConfigEvent::Close(info.window.clone()))
+            } else if response.clicked() && info.focused {
+                Some(ConfigEvent::Minimize(info.window.clone()))
+            } else if response.clicked() {
+                Some(ConfigEvent::Focus(info.window.clone()))
+            } else {
+                None
+            };
+
+            if let Some(event) = event {
+                config_tx.send(event).unwrap();
+            }
+        }
+    }
+}
+
+#[export_module]
+pub mod taskbar {
+    /// Only list the windows of the active workspace
+    #[rhai_fn(get = "current_workspace_only", pure)]
+    pub fn current_workspace_only(taskbar: &mut Taskbar) -> bool {
+        taskbar.inner.borrow().current_workspace_only
+    }
+
+    #[rhai_fn(set = "current_workspace_only", pure)]
+    pub fn set_current_workspace_only(taskbar: &mut Taskbar, current_workspace_only: bool) {
+        taskbar.inner.borrow_mut().current_workspace_only = current_workspace_only;
+    }
+
+    #[rhai_fn(get = "show_icons", pure)]
+    pub fn show_icons(taskbar: &mut Taskbar) -> bool {
+        taskbar.inner.borrow().show_icons
+    }
+
+    #[rhai_fn(set = "show_icons", pure)]
+    pub fn set_show_icons(taskbar: &mut Taskbar, show_icons: bool) {
+        taskbar.inner.borrow_mut().show_icons = show_icons;
+    }
+
+    #[rhai_fn(global)]
+    pub fn convert(taskbar: &mut Taskbar) -> Rc<dyn Widget> {
+        Rc::new(taskbar.clone())
+    }
+}
+
+pub fn register(engine: &mut Engine) {
+    let taskbar_module = exported_module!(taskbar);
+    engine
+        .register_static_module("taskbar", taskbar_module.into())
+        .register_type::<Taskbar>();
+}
@@ -0,0 +1,136 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use egui::Ui;
+use rhai::plugin::*;
+use rhai::{Engine, FnPtr, FLOAT};
+
+use super::widget::*;
+
+#[derive(Debug)]
+struct TextInputInner {
+    text: String,
+    placeholder: String,
+    width: Option<f32>,
+    request_focus: bool,
+    on_submit: Option<FnPtr>,
+    on_change: Option<FnPtr>,
+}
+
+#[derive(Debug, Clone)]
+pub struct TextInput(Rc<RefCell<TextInputInner>>);
+
+impl TextInput {
+    pub fn new(placeholder: String) -> Self {
+        Self(Rc::new(RefCell::new(TextInputInner {
+            text: String::new(),
+            placeholder,
+            width: None,
+            request_focus: false,
+            on_submit: None,
+            on_change: None,
+        })))
+    }
+}
+
+impl Widget for TextInput {
+    fn render(&self, ui: &mut Ui, config_tx: &Sender<ConfigEvent>) {
+        let mut inner = self.0.borrow_mut();
+        let inner = &mut *inner;
+
+        let mut text_edit =
+            egui::TextEdit::singleline(&mut inner.text).hint_text(inner.placeholder.as_str());
+        if let Some(width) = inner.width {
+            text_edit = text_edit.desired_width(width);
+        }
+
+        let response = ui.add(text_edit);
+
+        if inner.request_focus {
+            response.request_focus();
+            inner.request_focus = false;
+        }
+
+        if response.changed() {
+            if let Some(on_change) = &inner.on_change {
+                config_tx
+                    .send(ConfigEvent::ShellWithArgs(
+                        on_change.clone(),
+                        vec![inner.text.clone().into()],
+                    ))
+                    .unwrap();
+            }
+        }
+
+        if response.lost_focus() && ui.input().key_pressed(egui::Key::Enter) {
+            if let Some(on_submit) = &inner.on_submit {
+                config_tx
+                    .send(ConfigEvent::ShellWithArgs(
+                        on_submit.clone(),
+                        vec![inner.text.clone().into()],
+                    ))
+                    .unwrap();
+            }
+        }
+    }
+}
+
+#[export_module]
+pub mod text_input {
+    #[rhai_fn(get = "text", pure)]
+    pub fn text(input: &mut TextInput) -> String {
+        input.0.borrow().text.clone()
+    }
+
+    #[rhai_fn(set = "text", pure)]
+    pub fn set_text(input: &mut TextInput, text: String) {
+        input.0.borrow_mut().text = text;
+    }
+
+    #[rhai_fn(get = "placeholder", pure)]
+    pub fn placeholder(input: &mut TextInput) -> String {
+        input.0.borrow().placeholder.clone()
+    }
+
+    #[rhai_fn(set = "placeholder", pure)]
+    pub fn set_placeholder(input: &mut TextInput, placeholder: String) {
+        input.0.borrow_mut().placeholder = placeholder;
+    }
+
+    #[rhai_fn(set = "width", pure)]
+    pub fn set_width(input: &mut TextInput, width: FLOAT) {
+        input.0.borrow_mut().width = Some(width as _);
+    }
+
+    #[rhai_fn(global)]
+    pub fn clear(input: &mut TextInput) {
+        input.0.borrow_mut().text.clear();
+    }
+
+    #[rhai_fn(global)]
+    pub fn focus(input: &mut TextInput) {
+        input.0.borrow_mut().request_focus = true;
+    }
+
+    #[rhai_fn(global)]
+    pub fn on_submit(input: &mut TextInput, on_submit: FnPtr) {
+        input.0.borrow_mut().on_submit = Some(on_submit);
+    }
+
+    #[rhai_fn(global)]
+    pub fn on_change(input: &mut TextInput, on_change: FnPtr) {
+        input.0.borrow_mut().on_change = Some(on_change);
+    }
+
+    #[rhai_fn(global)]
+    pub fn convert(input: &mut TextInput) -> Rc<dyn Widget> {
+        Rc::new(input.clone())
+    }
+}
+
+pub fn register(engine: &mut Engine) {
+    let text_input_module = exported_module!(text_input);
+    engine
+        .register_static_module("text_input", text_input_module.into())
+        .register_type::<TextInput>();
+}
@@ -0,0 +1,60 @@
+use std::cell::RefCell;
+use std::rc::{Rc, Weak};
+use std::time::Duration;
+
+use smithay::reexports::calloop::timer::{Timer, TimerHandle};
+use smithay::reexports::calloop::LoopHandle;
+
+use crate::state::Anodium;
+
+/// Widget whose content changes with time. Egui only runs when something asks for it,
+/// so those widgets are updated from the event loop instead of while rendering.
+pub trait Tick {
+    /// Update the content, returns whether it changed and how long until the next update
+    fn tick(&self) -> (bool, Duration);
+}
+
+thread_local! {
+    static TIMER: RefCell<Option<TimerHandle<Weak<dyn Tick>>>> = RefCell::new(None);
+}
+
+/// Insert the timer updating every ticking widget, does nothing when it is already there
+pub fn init(loop_handle: &LoopHandle<'static, Anodium>) {
+    TIMER.with(|timer| {
+        if timer.borrow().is_some() {
+            return;
+        }
+
+        let timer_source: Timer<Weak<dyn Tick>> =
+            Timer::new().expect("Failed to create timer event source!");
+        let timer_handle = timer_source.handle();
+
+        let handle = timer_handle.clone();
+        loop_handle
+            .insert_source(timer_source, move |widget, _metadata, anodium| {
+                // Widgets dropped with their config just stop ticking
+                if let Some(widget) = widget.upgrade() {
+                    let (changed, next) = widget.tick();
+                    if changed {
+                        for output in anodium.output_manager.outputs().iter() {
+                            output.egui().context().request_repaint();
+                        }
+                    }
+                    handle.add_timeout(next, Rc::downgrade(&widget));
+                }
+            })
+            .unwrap();
+
+        *timer.borrow_mut() = Some(timer_handle);
+    });
+}
+
+/// Start updating the widget from the event loop, as long as it is alive
+pub fn start(widget: Rc<dyn Tick>) {
+    TIMER.with(|timer| match timer.borrow().as_ref() {
+        Some(timer) => {
+            timer.add_timeout(Duration::ZERO, Rc::downgrade(&widget));
+        }
+        None => warn!("widget created before the event loop, it will not update"),
+    });
+}
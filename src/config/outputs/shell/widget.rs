@@ -1,6 +1,7 @@
 pub use calloop::channel::Sender;
 use egui::Ui;
 use rhai::plugin::*;
+use rhai::{FnPtr, FLOAT, INT};
 
 pub use crate::config::eventloop::ConfigEvent;
 
@@ -23,7 +24,23 @@ impl Clone for Box<dyn Widget> {
 #[export_module]
 pub mod widget {
     use crate::config::outputs::shell::{
-        button::Button, fps::Fps, logger::Logger, menu::Menu, output::OutputGeometry, text::Text,
+        button::Button,
+        checkbox::Checkbox,
+        clock::Clock,
+        container::Layout,
+        fps::Fps,
+        group::{Group, Separator},
+        image::Image,
+        launcher::Launcher,
+        logger::Logger,
+        menu::Menu,
+        output::OutputGeometry,
+        progress::Progress,
+        slider::Slider,
+        sysinfo::SysInfo,
+        taskbar::Taskbar,
+        text::Text,
+        text_input::TextInput,
         workspace::CurrentWorkspace,
     };
     use crate::output_manager::Output;
@@ -32,10 +49,79 @@ pub mod widget {
         Text::new(text)
     }
 
+    pub fn input(placeholder: String) -> TextInput {
+        TextInput::new(placeholder)
+    }
+
+    pub fn launcher() -> Launcher {
+        Launcher::new()
+    }
+
+    pub fn image(path: String) -> Image {
+        Image::new(path)
+    }
+
+    pub fn icon(name: String, size: INT) -> Image {
+        Image::icon(name, size.max(1) as _)
+    }
+
     pub fn button(text: String) -> Button {
         Button::new(text)
     }
 
+    pub fn slider(min: FLOAT, max: FLOAT, value: FLOAT, on_change: FnPtr) -> Slider {
+        Slider::new(min, max, value, Some(on_change))
+    }
+
+    #[rhai_fn(name = "slider")]
+    pub fn slider_int(min: INT, max: INT, value: INT, on_change: FnPtr) -> Slider {
+        Slider::new(min as _, max as _, value as _, Some(on_change))
+    }
+
+    pub fn checkbox(label: String, checked: bool) -> Checkbox {
+        Checkbox::new(label, checked)
+    }
+
+    pub fn toggle(label: String, checked: bool) -> Checkbox {
+        Checkbox::toggle(label, checked)
+    }
+
+    pub fn progress(progress: FLOAT) -> Progress {
+        Progress::new(progress)
+    }
+
+    pub fn clock(format: String) -> Clock {
+        Clock::new(format)
+    }
+
+    pub fn battery() -> SysInfo {
+        SysInfo::battery()
+    }
+
+    pub fn cpu() -> SysInfo {
+        SysInfo::cpu()
+    }
+
+    pub fn memory() -> SysInfo {
+        SysInfo::memory()
+    }
+
+    pub fn temperature() -> SysInfo {
+        SysInfo::temperature()
+    }
+
+    pub fn network() -> SysInfo {
+        SysInfo::network()
+    }
+
+    pub fn group(layout: Layout) -> Group {
+        Group::new(layout)
+    }
+
+    pub fn separator() -> Separator {
+        Separator
+    }
+
     pub fn menu(text: String) -> Menu {
         Menu::new(text)
     }
@@ -52,6 +138,10 @@ pub mod widget {
         CurrentWorkspace::new(output)
     }
 
+    pub fn taskbar(output: Output) -> Taskbar {
+        Taskbar::new(output)
+    }
+
     pub fn output_geometry(output: Output) -> OutputGeometry {
         OutputGeometry::new(output)
     }
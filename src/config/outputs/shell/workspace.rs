@@ -1,6 +1,7 @@
+use std::cell::RefCell;
 use std::rc::Rc;
 
-use egui::Ui;
+use egui::{Color32, Ui};
 use rhai::plugin::*;
 use rhai::Engine;
 
@@ -8,24 +9,69 @@ use crate::output_manager::Output;
 
 use super::widget::*;
 
+const URGENT_COLOR: Color32 = Color32::from_rgb(230, 80, 80);
+
+#[derive(Debug)]
+struct Inner {
+    hide_empty: bool,
+}
+
+/// Workspace indicator, lists the workspaces and switches to the clicked one
 #[derive(Debug, Clone)]
-pub struct CurrentWorkspace(Output);
+pub struct CurrentWorkspace {
+    output: Output,
+    inner: Rc<RefCell<Inner>>,
+}
 
 impl CurrentWorkspace {
     pub fn new(output: Output) -> Self {
-        Self(output)
+        Self {
+            output,
+            inner: Rc::new(RefCell::new(Inner { hide_empty: false })),
+        }
     }
 }
 
 impl Widget for CurrentWorkspace {
-    fn render(&self, _ui: &mut Ui, _config_tx: &Sender<ConfigEvent>) {
-        todo!();
-        // ui.text(format!("Workspace: {}", self.0.active_workspace()));
+    fn render(&self, ui: &mut Ui, config_tx: &Sender<ConfigEvent>) {
+        let hide_empty = self.inner.borrow().hide_empty;
+        let workspaces = self.output.workspaces();
+        let visible = workspaces
+            .iter()
+            .filter(|w| !hide_empty || w.active || w.urgent || w.occupied);
+
+        for workspace in visible {
+            let response = ui
+                .scope(|ui| {
+                    if workspace.urgent && !workspace.active {
+                        ui.visuals_mut().override_text_color = Some(URGENT_COLOR);
+                    }
+                    ui.selectable_label(workspace.active, &workspace.name)
+                })
+                .inner;
+
+            if response.clicked() && !workspace.active {
+                config_tx
+                    .send(ConfigEvent::SwitchWorkspace(workspace.name.clone()))
+                    .unwrap();
+            }
+        }
     }
 }
 
 #[export_module]
 pub mod workspace {
+    /// Only show workspaces that are active, urgent or have windows on the output
+    #[rhai_fn(get = "hide_empty", pure)]
+    pub fn hide_empty(current_workspace: &mut CurrentWorkspace) -> bool {
+        current_workspace.inner.borrow().hide_empty
+    }
+
+    #[rhai_fn(set = "hide_empty", pure)]
+    pub fn set_hide_empty(current_workspace: &mut CurrentWorkspace, hide_empty: bool) {
+        current_workspace.inner.borrow_mut().hide_empty = hide_empty;
+    }
+
     #[rhai_fn(global)]
     pub fn convert(current_workspace: &mut CurrentWorkspace) -> Rc<dyn Widget> {
         Rc::new(current_workspace.clone())
@@ -206,18 +206,29 @@ impl System {
     }
 }
 
+/// Spawn the command without waiting for it, the command is split like a shell would do
+pub fn spawn(command: &str) {
+    let command_split = match shell_words::split(command) {
+        Ok(command_split) if !command_split.is_empty() => command_split,
+        _ => {
+            error!("failed to parse command: {}", command);
+            return;
+        }
+    };
+
+    if let Err(e) = Command::new(&command_split[0])
+        .args(&command_split[1..])
+        .spawn()
+    {
+        error!("failed to start command: {}, err: {:?}", command, e);
+    }
+}
+
 #[export_module]
 pub mod system {
     #[rhai_fn(global)]
     pub fn exec(_system: &mut System, command: &str) {
-        let command_split = shell_words::split(command).unwrap();
-
-        if let Err(e) = Command::new(&command_split[0])
-            .args(&command_split[1..])
-            .spawn()
-        {
-            slog_scope::error!("failed to start command: {}, err: {:?}", command, e);
-        }
+        spawn(command);
     }
 
     #[rhai_fn(global)]
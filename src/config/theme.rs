@@ -0,0 +1,300 @@
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::fs;
+use std::rc::Rc;
+
+use egui::{Color32, FontData, FontDefinitions, FontFamily, TextStyle, Ui, Visuals};
+use rhai::plugin::*;
+use rhai::{Array, FLOAT};
+
+use super::outputs::shell::logger::Logger;
+
+fn parse_text_style(name: &str) -> Option<TextStyle> {
+    match name {
+        "small" => Some(TextStyle::Small),
+        "body" => Some(TextStyle::Body),
+        "button" => Some(TextStyle::Button),
+        "heading" => Some(TextStyle::Heading),
+        "monospace" => Some(TextStyle::Monospace),
+        _ => None,
+    }
+}
+
+#[derive(Debug)]
+struct Font {
+    name: String,
+    data: Vec<u8>,
+    monospace: bool,
+}
+
+#[derive(Debug)]
+struct ThemeInner {
+    dark: bool,
+    accent: Option<Color32>,
+    background: Option<Color32>,
+    text: Option<Color32>,
+    corner_radius: f32,
+    shadow: f32,
+    font_sizes: HashMap<TextStyle, f32>,
+    fonts: Vec<Font>,
+}
+
+/// Look of the egui shell, shared by all outputs.
+/// Every change bumps the generation, outputs reapply the theme when it differs from theirs.
+#[derive(Debug, Clone)]
+pub struct Theme {
+    inner: Rc<RefCell<ThemeInner>>,
+    generation: Rc<Cell<u64>>,
+}
+
+impl Theme {
+    pub fn new() -> Self {
+        Self {
+            inner: Rc::new(RefCell::new(ThemeInner {
+                dark: true,
+                accent: None,
+                background: None,
+                text: None,
+                corner_radius: 0.0,
+                shadow: 0.0,
+                font_sizes: HashMap::new(),
+                fonts: Vec::new(),
+            })),
+            generation: Default::default(),
+        }
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation.get()
+    }
+
+    fn update(&self, f: impl FnOnce(&mut ThemeInner)) {
+        f(&mut self.inner.borrow_mut());
+        self.generation.set(self.generation.get() + 1);
+    }
+
+    pub fn visuals(&self) -> Visuals {
+        let inner = self.inner.borrow();
+
+        let mut visuals = if inner.dark {
+            Visuals::dark()
+        } else {
+            Visuals::light()
+        };
+
+        visuals.window_corner_radius = inner.corner_radius;
+        visuals.widgets.noninteractive.corner_radius = inner.corner_radius;
+        visuals.widgets.inactive.corner_radius = inner.corner_radius;
+        visuals.widgets.hovered.corner_radius = inner.corner_radius;
+        visuals.widgets.active.corner_radius = inner.corner_radius;
+        visuals.widgets.open.corner_radius = inner.corner_radius;
+        visuals.window_shadow.extrusion = inner.shadow;
+
+        if let Some(accent) = inner.accent {
+            visuals.selection.bg_fill = accent;
+            visuals.hyperlink_color = accent;
+        }
+        if let Some(background) = inner.background {
+            visuals.widgets.noninteractive.bg_fill = background;
+        }
+        visuals.override_text_color = inner.text;
+
+        visuals
+    }
+
+    pub fn fonts(&self) -> FontDefinitions {
+        let inner = self.inner.borrow();
+        let mut fonts = FontDefinitions::default();
+
+        // Later fonts take precedence, so insert them at the front in order
+        for font in inner.fonts.iter() {
+            fonts
+                .font_data
+                .insert(font.name.clone(), FontData::from_owned(font.data.clone()));
+
+            let family = if font.monospace {
+                FontFamily::Monospace
+            } else {
+                FontFamily::Proportional
+            };
+            fonts
+                .fonts_for_family
+                .entry(family)
+                .or_default()
+                .insert(0, font.name.clone());
+        }
+
+        for (style, size) in inner.font_sizes.iter() {
+            if let Some((_, current)) = fonts.family_and_size.get_mut(style) {
+                *current = *size;
+            }
+        }
+
+        fonts
+    }
+}
+
+fn add_font(theme: &Theme, path: &str, monospace: bool) {
+    match fs::read(path) {
+        Ok(data) => theme.update(|inner| {
+            inner.fonts.push(Font {
+                name: path.to_owned(),
+                data,
+                monospace,
+            })
+        }),
+        Err(err) => error!("failed to load font {}: {}", path, err),
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Overrides of the theme for a single box or panel
+#[derive(Debug, Clone, Default)]
+pub struct StyleOverride {
+    pub background: Option<Color32>,
+    pub text: Option<Color32>,
+    pub accent: Option<Color32>,
+    pub corner_radius: Option<f32>,
+}
+
+impl StyleOverride {
+    pub fn apply_frame(&self, frame: &mut egui::Frame) {
+        if let Some(background) = self.background {
+            frame.fill = background;
+        }
+        if let Some(corner_radius) = self.corner_radius {
+            frame.corner_radius = corner_radius;
+        }
+    }
+
+    pub fn apply(&self, ui: &mut Ui) {
+        let visuals = ui.visuals_mut();
+
+        if let Some(text) = self.text {
+            visuals.override_text_color = Some(text);
+        }
+        if let Some(accent) = self.accent {
+            visuals.selection.bg_fill = accent;
+        }
+        if let Some(corner_radius) = self.corner_radius {
+            visuals.widgets.inactive.corner_radius = corner_radius;
+            visuals.widgets.hovered.corner_radius = corner_radius;
+            visuals.widgets.active.corner_radius = corner_radius;
+            visuals.widgets.open.corner_radius = corner_radius;
+        }
+    }
+}
+
+#[export_module]
+pub mod theme {
+    #[rhai_fn(get = "dark", pure)]
+    pub fn dark(theme: &mut Theme) -> bool {
+        theme.inner.borrow().dark
+    }
+
+    #[rhai_fn(set = "dark", pure)]
+    pub fn set_dark(theme: &mut Theme, dark: bool) {
+        theme.update(|inner| inner.dark = dark);
+    }
+
+    #[rhai_fn(set = "accent", pure)]
+    pub fn set_accent(theme: &mut Theme, accent: Array) {
+        if let Some(accent) = Logger::parse_color(accent) {
+            theme.update(|inner| inner.accent = Some(accent));
+        }
+    }
+
+    #[rhai_fn(set = "background", pure)]
+    pub fn set_background(theme: &mut Theme, background: Array) {
+        if let Some(background) = Logger::parse_color(background) {
+            theme.update(|inner| inner.background = Some(background));
+        }
+    }
+
+    #[rhai_fn(set = "text", pure)]
+    pub fn set_text(theme: &mut Theme, text: Array) {
+        if let Some(text) = Logger::parse_color(text) {
+            theme.update(|inner| inner.text = Some(text));
+        }
+    }
+
+    #[rhai_fn(get = "corner_radius", pure)]
+    pub fn corner_radius(theme: &mut Theme) -> FLOAT {
+        theme.inner.borrow().corner_radius as _
+    }
+
+    #[rhai_fn(set = "corner_radius", pure)]
+    pub fn set_corner_radius(theme: &mut Theme, corner_radius: FLOAT) {
+        theme.update(|inner| inner.corner_radius = corner_radius.max(0.0) as _);
+    }
+
+    #[rhai_fn(get = "shadow", pure)]
+    pub fn shadow(theme: &mut Theme) -> FLOAT {
+        theme.inner.borrow().shadow as _
+    }
+
+    #[rhai_fn(set = "shadow", pure)]
+    pub fn set_shadow(theme: &mut Theme, shadow: FLOAT) {
+        theme.update(|inner| inner.shadow = shadow.max(0.0) as _);
+    }
+
+    /// Size of one of the text styles: small, body, button, heading or monospace
+    #[rhai_fn(global)]
+    pub fn font_size(theme: &mut Theme, style: &str, size: FLOAT) {
+        match parse_text_style(style) {
+            Some(style) => theme.update(|inner| {
+                inner.font_sizes.insert(style, size.max(1.0) as _);
+            }),
+            None => slog_scope::warn!("unknown text style: {}, ignoring", style),
+        }
+    }
+
+    /// Load a TTF or OTF file, it takes precedence over the fonts loaded before
+    #[rhai_fn(global)]
+    pub fn load_font(theme: &mut Theme, path: &str) {
+        add_font(theme, path, false);
+    }
+
+    #[rhai_fn(global)]
+    pub fn load_monospace_font(theme: &mut Theme, path: &str) {
+        add_font(theme, path, true);
+    }
+
+    /// Empty style override, assign it to the `style` of a box or panel
+    pub fn style() -> StyleOverride {
+        StyleOverride::default()
+    }
+
+    #[rhai_fn(set = "background")]
+    pub fn set_style_background(style: &mut StyleOverride, background: Array) {
+        style.background = Logger::parse_color(background);
+    }
+
+    #[rhai_fn(set = "text")]
+    pub fn set_style_text(style: &mut StyleOverride, text: Array) {
+        style.text = Logger::parse_color(text);
+    }
+
+    #[rhai_fn(set = "accent")]
+    pub fn set_style_accent(style: &mut StyleOverride, accent: Array) {
+        style.accent = Logger::parse_color(accent);
+    }
+
+    #[rhai_fn(set = "corner_radius")]
+    pub fn set_style_corner_radius(style: &mut StyleOverride, corner_radius: FLOAT) {
+        style.corner_radius = Some(corner_radius.max(0.0) as _);
+    }
+}
+
+pub fn register(engine: &mut Engine) {
+    let theme_module = exported_module!(theme);
+    engine
+        .register_static_module("theme", theme_module.into())
+        .register_type::<Theme>()
+        .register_type::<StyleOverride>();
+}
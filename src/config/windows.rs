@@ -1,10 +1,15 @@
-use std::{cell::RefCell, rc::Rc};
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
 
 use rhai::plugin::*;
+use rhai::{FnPtr, AST};
 
 use smithay::reexports::calloop::channel::Sender;
 
 use super::eventloop::ConfigEvent;
+use super::outputs::shell::menu::Menu;
 
 use crate::window::Window as AndiumWindow;
 
@@ -29,6 +34,12 @@ impl From<Window> for Dynamic {
     }
 }
 
+impl Window {
+    fn send(&self, event: ConfigEvent) {
+        self.event_sender.send(event).unwrap();
+    }
+}
+
 #[export_module]
 pub mod window {
     #[rhai_fn(global)]
@@ -52,12 +63,66 @@ pub mod window {
             .send(ConfigEvent::Close(window.andium_window.clone()))
             .unwrap();
     }
+    #[rhai_fn(global)]
+    pub fn minimize(window: &mut Window) {
+        window.send(ConfigEvent::Minimize(window.andium_window.clone()));
+    }
+    #[rhai_fn(global)]
+    pub fn fullscreen(window: &mut Window) {
+        window.send(ConfigEvent::Fullscreen(window.andium_window.clone()));
+    }
+    #[rhai_fn(global)]
+    pub fn unfullscreen(window: &mut Window) {
+        window.send(ConfigEvent::Unfullscreen(window.andium_window.clone()));
+    }
+    /// Move the window with the pointer until the next click
+    #[rhai_fn(global, name = "move")]
+    pub fn move_with_pointer(window: &mut Window) {
+        window.send(ConfigEvent::Move(window.andium_window.clone()));
+    }
+    /// Resize the window from its bottom right corner until the next click
+    #[rhai_fn(global)]
+    pub fn resize(window: &mut Window) {
+        window.send(ConfigEvent::Resize(window.andium_window.clone()));
+    }
+    #[rhai_fn(global)]
+    pub fn move_to_workspace(window: &mut Window, workspace: String) {
+        window.send(ConfigEvent::MoveToWorkspace(
+            window.andium_window.clone(),
+            workspace,
+        ));
+    }
+    #[rhai_fn(set = "always_on_top", pure)]
+    pub fn set_always_on_top(window: &mut Window, on_top: bool) {
+        window.send(ConfigEvent::AlwaysOnTop(
+            window.andium_window.clone(),
+            on_top,
+        ));
+    }
+    #[rhai_fn(get = "title", pure)]
+    pub fn title(window: &mut Window) -> String {
+        window.andium_window.title().unwrap_or_default()
+    }
+    #[rhai_fn(get = "app_id", pure)]
+    pub fn app_id(window: &mut Window) -> String {
+        window.andium_window.app_id().unwrap_or_default()
+    }
+}
+
+/// Who draws the titlebar and borders of windows that leave the choice to the compositor
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Decorations {
+    Client,
+    Server,
 }
 
 #[derive(Debug, Clone)]
 pub struct Windows {
     event_sender: Sender<ConfigEvent>,
     focused_window: Rc<RefCell<Option<AndiumWindow>>>,
+    /// Called with the window and its menu before the menu is shown
+    on_menu: Rc<RefCell<Vec<FnPtr>>>,
+    decorations: Rc<Cell<Decorations>>,
 }
 
 impl Windows {
@@ -65,12 +130,25 @@ impl Windows {
         Self {
             event_sender,
             focused_window: Default::default(),
+            on_menu: Default::default(),
+            decorations: Rc::new(Cell::new(Decorations::Client)),
         }
     }
 
     pub fn update_focused_window(&self, window: Option<AndiumWindow>) {
         *self.focused_window.borrow_mut() = window;
     }
+
+    pub fn decorations(&self) -> Decorations {
+        self.decorations.get()
+    }
+
+    pub fn on_menu(&self, engine: &Engine, ast: &AST, window: &AndiumWindow, menu: &Menu) {
+        for on_menu in self.on_menu.borrow().clone() {
+            let window = Window::new(self.event_sender.clone(), window.clone());
+            let _result: () = on_menu.call(engine, ast, (window, menu.clone())).unwrap();
+        }
+    }
 }
 
 #[export_module]
@@ -83,15 +161,45 @@ pub mod windows {
             rhai::Dynamic::UNIT
         }
     }
+
+    /// Extend the window menu, `fnptr` gets the window and the menu to add items to
+    #[rhai_fn(global)]
+    pub fn on_menu(windows: &mut Windows, fnptr: FnPtr) {
+        windows.on_menu.borrow_mut().push(fnptr);
+    }
+
+    #[rhai_fn(get = "decorations", pure)]
+    pub fn get_decorations(windows: &mut Windows) -> Decorations {
+        windows.decorations.get()
+    }
+
+    /// Mode used until the client asks for one
+    #[rhai_fn(set = "decorations", pure)]
+    pub fn set_decorations(windows: &mut Windows, decorations: Decorations) {
+        windows.decorations.set(decorations);
+    }
+}
+
+#[export_module]
+pub mod decorations {
+    pub fn client() -> Decorations {
+        Decorations::Client
+    }
+    pub fn server() -> Decorations {
+        Decorations::Server
+    }
 }
 
 pub fn register(engine: &mut Engine) {
     let window_module = exported_module!(window);
     let windows_module = exported_module!(windows);
+    let decorations_module = exported_module!(decorations);
 
     engine
         .register_static_module("windows", windows_module.into())
         .register_static_module("window", window_module.into())
+        .register_static_module("decorations", decorations_module.into())
         .register_type::<Windows>()
-        .register_type::<Window>();
+        .register_type::<Window>()
+        .register_type::<Decorations>();
 }
@@ -4,25 +4,58 @@ use crate::Anodium;
 impl Anodium {
     pub fn process_config_event(&mut self, event: ConfigEvent) {
         match event {
-            // TODO: Implement window closing from events
-            ConfigEvent::Close(_window) => {}
-            ConfigEvent::Maximize(_window) => {
-                todo!();
-                // self.active_workspace().maximize_request(&window.toplevel());
-            }
-            ConfigEvent::Unmaximize(_window) => {
-                todo!();
-                // self.active_workspace()
-                //     .unmaximize_request(&window.toplevel());
-            }
-            ConfigEvent::SwitchWorkspace(_workspace) => todo!(),
+            ConfigEvent::Close(window) => {
+                window.close();
+            }
+            ConfigEvent::Focus(window) => {
+                self.workspace.restore(&window);
+                self.focus_window(&window);
+            }
+            ConfigEvent::Minimize(window) => {
+                self.workspace.minimize(&window);
+
+                if self.focused_window.as_ref() == Some(window.desktop_window()) {
+                    self.update_focused_window(None);
+                    self.clear_keyboard_focus();
+                }
+            }
+            ConfigEvent::Maximize(window) => {
+                self.maximize_window(window.desktop_window());
+            }
+            ConfigEvent::Unmaximize(window) => {
+                self.unmaximize_window(window.desktop_window());
+            }
+            ConfigEvent::Fullscreen(window) => {
+                self.fullscreen_window(window.desktop_window(), None);
+            }
+            ConfigEvent::Unfullscreen(window) => {
+                self.unfullscreen_window(window.desktop_window());
+            }
+            ConfigEvent::Move(window) => {
+                self.move_with_pointer(window.desktop_window());
+            }
+            ConfigEvent::Resize(window) => {
+                self.resize_with_pointer(window.desktop_window());
+            }
+            ConfigEvent::AlwaysOnTop(window, on_top) => {
+                self.workspace.set_on_top(window.desktop_window(), on_top);
+            }
+            ConfigEvent::MoveToWorkspace(window, workspace) => {
+                self.workspace
+                    .move_window_to(window.desktop_window(), &workspace);
+            }
+            ConfigEvent::SwitchWorkspace(workspace) => {
+                self.switch_workspace(&workspace);
+            }
             ConfigEvent::OutputsRearrange => {
-                self.config
-                    .output_rearrange(self.output_manager.outputs().clone());
+                self.rearrange_outputs();
             }
             ConfigEvent::Shell(fnptr) => {
                 self.config.execute_fnptr(fnptr, ());
             }
+            ConfigEvent::ShellWithArgs(fnptr, args) => {
+                self.config.execute_fnptr(fnptr, args);
+            }
         }
     }
 }
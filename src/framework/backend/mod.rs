@@ -36,6 +36,9 @@ pub trait OutputHandler {
     /// Output was created
     fn output_created(&mut self, output: Output);
 
+    /// Output was removed
+    fn output_removed(&mut self, _output: &Output) {}
+
     /// Output got resized
     fn output_mode_updated(&mut self, output: &Output, mode: wayland::output::Mode) {
         output.change_current_state(Some(mode), None, None, None);
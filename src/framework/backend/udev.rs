@@ -31,7 +31,7 @@ use smithay::{
         calloop::{
             channel,
             timer::{Timer, TimerHandle},
-            Dispatcher, EventLoop, LoopHandle,
+            Dispatcher, EventLoop, LoopHandle, RegistrationToken,
         },
         drm::{
             self,
@@ -234,7 +234,7 @@ where
                 error!("Udev device ({:?}) changed: unimplemented", device_id);
             }
             UdevEvent::Removed { device_id } => {
-                error!("Udev device ({:?}) removed: unimplemented", device_id);
+                device_removed(handler, handle.clone(), inner.clone(), device_id)
             }
         })
         .map_err(|e| -> IoError { e.into() })
@@ -276,7 +276,7 @@ pub struct UdevDeviceData {
     pointer_images: Vec<(xcursor::parser::Image, Gles2Texture)>,
     renderer: Rc<RefCell<Gles2Renderer>>,
     // gbm: GbmDevice<SessionFd>,
-    // registration_token: RegistrationToken,
+    registration_token: RegistrationToken,
     // event_dispatcher: Dispatcher<'static, DrmDevice<SessionFd>, BackendState>,
     dev_id: u64,
 }
@@ -611,7 +611,7 @@ fn device_added<D>(
             }
         });
 
-        let _registration_token = handle
+        let registration_token = handle
             .register_dispatcher(event_dispatcher.clone())
             .unwrap();
 
@@ -626,7 +626,7 @@ fn device_added<D>(
             dev_id,
             UdevDeviceData {
                 _restart_token: restart_token,
-                // registration_token,
+                registration_token,
                 // event_dispatcher,
                 surfaces: outputs,
                 renderer,
@@ -638,6 +638,39 @@ fn device_added<D>(
     }
 }
 
+fn device_removed<D>(
+    handler: &mut D,
+    handle: LoopHandle<'static, D>,
+    inner: InnerRc,
+    device_id: dev_t,
+) where
+    D: BackendHandler + 'static,
+{
+    info!("Device Removed {:?}", device_id);
+
+    let removed: Vec<Output> = {
+        let mut inner = inner.borrow_mut();
+        if let Some(device) = inner.udev_devices.remove(&device_id) {
+            // Stop listening for vblanks of the gone drm device
+            handle.remove(device.registration_token);
+        }
+
+        let (removed, outputs) = inner.outputs.drain(..).partition(|o| {
+            o.user_data()
+                .get::<UdevOutputId>()
+                .map(|id| id.device_id == device_id)
+                .unwrap_or(false)
+        });
+        inner.outputs = outputs;
+
+        removed
+    };
+
+    for output in removed.iter() {
+        handler.output_removed(output);
+    }
+}
+
 fn udev_render<D>(handler: &mut D, inner: InnerRc, dev_id: u64, crtc: Option<crtc::Handle>)
 where
     D: BackendHandler + 'static,
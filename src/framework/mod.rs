@@ -1,4 +1,7 @@
 pub mod backend;
 pub mod cursor;
+pub mod pointer_constraints;
+pub mod pointer_gestures;
+pub mod relative_pointer;
 pub mod shell;
 pub mod surface_data;
@@ -0,0 +1,282 @@
+use std::{cell::RefCell, ops::Deref, rc::Rc};
+
+use smithay::{
+    reexports::{
+        wayland_protocols::unstable::pointer_constraints::v1::server::{
+            zwp_confined_pointer_v1::{self, ZwpConfinedPointerV1},
+            zwp_locked_pointer_v1::{self, ZwpLockedPointerV1},
+            zwp_pointer_constraints_v1::{self, Lifetime, ZwpPointerConstraintsV1},
+        },
+        wayland_server::{
+            protocol::{wl_region::WlRegion, wl_surface::WlSurface},
+            Display, Filter, Global, Main,
+        },
+    },
+    utils::{Logical, Point},
+    wayland::compositor::{get_region_attributes, RegionAttributes},
+};
+
+#[derive(Debug, Clone, PartialEq)]
+enum Resource {
+    Locked(ZwpLockedPointerV1),
+    Confined(ZwpConfinedPointerV1),
+}
+
+#[derive(Debug)]
+struct Constraint {
+    resource: Resource,
+    surface: WlSurface,
+    region: Option<RegionAttributes>,
+    /// Region from set_region, it only applies once the surface gets committed
+    pending_region: Option<Option<RegionAttributes>>,
+    persistent: bool,
+
+    active: bool,
+    /// Oneshot constraint that was already deactivated, it can not be activated again
+    dead: bool,
+}
+
+impl Constraint {
+    fn activate(&mut self) {
+        self.active = true;
+        match &self.resource {
+            Resource::Locked(locked) => locked.locked(),
+            Resource::Confined(confined) => confined.confined(),
+        }
+    }
+
+    fn deactivate(&mut self) {
+        self.active = false;
+        self.dead = !self.persistent;
+        match &self.resource {
+            Resource::Locked(locked) => locked.unlocked(),
+            Resource::Confined(confined) => confined.unconfined(),
+        }
+    }
+}
+
+/// Constraint that is currently applied to the pointer
+#[derive(Debug, Clone)]
+pub enum ActiveConstraint {
+    /// The pointer does not move, only relative motion is sent
+    Locked,
+    /// The pointer can not leave the region of the surface,
+    /// `None` means the whole surface
+    Confined {
+        surface: WlSurface,
+        region: Option<RegionAttributes>,
+    },
+}
+
+#[derive(Default)]
+struct Inner {
+    constraints: Vec<Constraint>,
+}
+
+/// zwp_pointer_constraints_v1 global, lets clients lock or confine the pointer to their surface
+#[derive(Clone)]
+pub struct PointerConstraints {
+    inner: Rc<RefCell<Inner>>,
+}
+
+impl PointerConstraints {
+    pub fn init(display: &mut Display) -> (PointerConstraints, Global<ZwpPointerConstraintsV1>) {
+        let inner = Rc::new(RefCell::new(Inner::default()));
+
+        let global: Global<ZwpPointerConstraintsV1> = display.create_global(1, {
+            let inner = inner.clone();
+            Filter::new(
+                move |(manager, _): (Main<ZwpPointerConstraintsV1>, _), _, _| {
+                    let inner = inner.clone();
+                    manager.quick_assign(move |manager, request, _| match request {
+                        zwp_pointer_constraints_v1::Request::LockPointer {
+                            id,
+                            surface,
+                            region,
+                            lifetime,
+                            ..
+                        } => {
+                            if inner.borrow().is_constrained(&surface) {
+                                manager.as_ref().post_error(
+                                    zwp_pointer_constraints_v1::Error::AlreadyConstrained as u32,
+                                    "surface already has a pointer constraint".into(),
+                                );
+                                return;
+                            }
+
+                            id.quick_assign({
+                                let inner = inner.clone();
+                                move |locked, request, _| match request {
+                                    zwp_locked_pointer_v1::Request::SetRegion { region } => {
+                                        inner.borrow_mut().set_region(
+                                            &Resource::Locked(locked.deref().clone()),
+                                            region,
+                                        );
+                                    }
+                                    // The pointer is never warped, so the hint is not needed
+                                    zwp_locked_pointer_v1::Request::SetCursorPositionHint {
+                                        ..
+                                    } => {}
+                                    zwp_locked_pointer_v1::Request::Destroy => {}
+                                    _ => {}
+                                }
+                            });
+                            id.assign_destructor(Filter::new({
+                                let inner = inner.clone();
+                                move |res: ZwpLockedPointerV1, _, _| {
+                                    inner.borrow_mut().remove(&Resource::Locked(res));
+                                }
+                            }));
+
+                            inner.borrow_mut().insert(
+                                Resource::Locked(id.deref().clone()),
+                                surface,
+                                region,
+                                lifetime,
+                            );
+                        }
+                        zwp_pointer_constraints_v1::Request::ConfinePointer {
+                            id,
+                            surface,
+                            region,
+                            lifetime,
+                            ..
+                        } => {
+                            if inner.borrow().is_constrained(&surface) {
+                                manager.as_ref().post_error(
+                                    zwp_pointer_constraints_v1::Error::AlreadyConstrained as u32,
+                                    "surface already has a pointer constraint".into(),
+                                );
+                                return;
+                            }
+
+                            id.quick_assign({
+                                let inner = inner.clone();
+                                move |confined, request, _| match request {
+                                    zwp_confined_pointer_v1::Request::SetRegion { region } => {
+                                        inner.borrow_mut().set_region(
+                                            &Resource::Confined(confined.deref().clone()),
+                                            region,
+                                        );
+                                    }
+                                    zwp_confined_pointer_v1::Request::Destroy => {}
+                                    _ => {}
+                                }
+                            });
+                            id.assign_destructor(Filter::new({
+                                let inner = inner.clone();
+                                move |res: ZwpConfinedPointerV1, _, _| {
+                                    inner.borrow_mut().remove(&Resource::Confined(res));
+                                }
+                            }));
+
+                            inner.borrow_mut().insert(
+                                Resource::Confined(id.deref().clone()),
+                                surface,
+                                region,
+                                lifetime,
+                            );
+                        }
+                        zwp_pointer_constraints_v1::Request::Destroy => {}
+                        _ => {}
+                    });
+                },
+            )
+        });
+
+        (PointerConstraints { inner }, global)
+    }
+
+    /// Activate the constraint of the surface under the pointer and deactivate all others.
+    /// `focus` is the surface with pointer focus and the pointer location relative to it.
+    pub fn set_focus(&self, focus: Option<(&WlSurface, Point<f64, Logical>)>) {
+        let mut inner = self.inner.borrow_mut();
+
+        // Constraints of destroyed surfaces can never apply again
+        inner.constraints.retain(|c| c.surface.as_ref().is_alive());
+
+        for constraint in inner.constraints.iter_mut() {
+            let focused = focus
+                .map(|(surface, location)| {
+                    constraint.surface == *surface
+                        && constraint
+                            .region
+                            .as_ref()
+                            .map(|region| region.contains(location.to_i32_floor()))
+                            .unwrap_or(true)
+                })
+                .unwrap_or(false);
+
+            if constraint.active && focus.map(|(s, _)| s) != Some(&constraint.surface) {
+                constraint.deactivate();
+            } else if !constraint.active && !constraint.dead && focused {
+                constraint.activate();
+            }
+        }
+    }
+
+    /// Apply the regions set since the last commit of the surface
+    pub fn commit(&self, surface: &WlSurface) {
+        for constraint in self.inner.borrow_mut().constraints.iter_mut() {
+            if &constraint.surface == surface {
+                if let Some(region) = constraint.pending_region.take() {
+                    constraint.region = region;
+                }
+            }
+        }
+    }
+
+    pub fn active(&self) -> Option<ActiveConstraint> {
+        self.inner
+            .borrow()
+            .constraints
+            .iter()
+            .find(|c| c.active)
+            .map(|c| match c.resource {
+                Resource::Locked(_) => ActiveConstraint::Locked,
+                Resource::Confined(_) => ActiveConstraint::Confined {
+                    surface: c.surface.clone(),
+                    region: c.region.clone(),
+                },
+            })
+    }
+}
+
+impl Inner {
+    fn is_constrained(&self, surface: &WlSurface) -> bool {
+        self.constraints.iter().any(|c| &c.surface == surface)
+    }
+
+    fn insert(
+        &mut self,
+        resource: Resource,
+        surface: WlSurface,
+        region: Option<WlRegion>,
+        lifetime: Lifetime,
+    ) {
+        self.constraints.push(Constraint {
+            resource,
+            surface,
+            region: region.as_ref().map(get_region_attributes),
+            pending_region: None,
+            persistent: lifetime == Lifetime::Persistent,
+            active: false,
+            dead: false,
+        });
+    }
+
+    fn set_region(&mut self, resource: &Resource, region: Option<WlRegion>) {
+        if let Some(constraint) = self
+            .constraints
+            .iter_mut()
+            .find(|c| &c.resource == resource)
+        {
+            // The wl_region can be destroyed before the commit, so its state is copied now
+            constraint.pending_region = Some(region.as_ref().map(get_region_attributes));
+        }
+    }
+
+    fn remove(&mut self, resource: &Resource) {
+        self.constraints.retain(|c| &c.resource != resource);
+    }
+}
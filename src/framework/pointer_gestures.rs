@@ -0,0 +1,143 @@
+use std::{cell::RefCell, ops::Deref, rc::Rc};
+
+use smithay::{
+    reexports::{
+        wayland_protocols::unstable::pointer_gestures::v1::server::{
+            zwp_pointer_gesture_pinch_v1::ZwpPointerGesturePinchV1,
+            zwp_pointer_gesture_swipe_v1::ZwpPointerGestureSwipeV1,
+            zwp_pointer_gestures_v1::{self, ZwpPointerGesturesV1},
+        },
+        wayland_server::{protocol::wl_surface::WlSurface, Display, Filter, Global, Main},
+    },
+    utils::{Logical, Point},
+    wayland::Serial,
+};
+
+#[derive(Default)]
+struct Inner {
+    swipes: Vec<ZwpPointerGestureSwipeV1>,
+    pinches: Vec<ZwpPointerGesturePinchV1>,
+
+    swipe_focus: Option<WlSurface>,
+    pinch_focus: Option<WlSurface>,
+}
+
+/// zwp_pointer_gestures_v1 global,
+/// forwards touchpad gestures that were not consumed by the compositor to the focused client
+#[derive(Clone)]
+pub struct PointerGestures {
+    inner: Rc<RefCell<Inner>>,
+}
+
+impl PointerGestures {
+    pub fn init(display: &mut Display) -> (PointerGestures, Global<ZwpPointerGesturesV1>) {
+        let inner = Rc::new(RefCell::new(Inner::default()));
+
+        let global: Global<ZwpPointerGesturesV1> = display.create_global(2, {
+            let inner = inner.clone();
+            Filter::new(move |(manager, _): (Main<ZwpPointerGesturesV1>, _), _, _| {
+                let inner = inner.clone();
+                manager.quick_assign(move |_manager, request, _| match request {
+                    zwp_pointer_gestures_v1::Request::GetSwipeGesture { id, .. } => {
+                        id.quick_assign(|_, _, _| {});
+                        id.assign_destructor(Filter::new({
+                            let inner = inner.clone();
+                            move |res: ZwpPointerGestureSwipeV1, _, _| {
+                                inner.borrow_mut().swipes.retain(|s| s != &res);
+                            }
+                        }));
+
+                        inner.borrow_mut().swipes.push(id.deref().clone());
+                    }
+                    zwp_pointer_gestures_v1::Request::GetPinchGesture { id, .. } => {
+                        id.quick_assign(|_, _, _| {});
+                        id.assign_destructor(Filter::new({
+                            let inner = inner.clone();
+                            move |res: ZwpPointerGesturePinchV1, _, _| {
+                                inner.borrow_mut().pinches.retain(|p| p != &res);
+                            }
+                        }));
+
+                        inner.borrow_mut().pinches.push(id.deref().clone());
+                    }
+                    zwp_pointer_gestures_v1::Request::Release => {}
+                    _ => {}
+                });
+            })
+        });
+
+        (PointerGestures { inner }, global)
+    }
+
+    pub fn swipe_begin(&self, surface: &WlSurface, serial: Serial, time: u32, fingers: u32) {
+        let mut inner = self.inner.borrow_mut();
+
+        for swipe in inner.swipes.iter() {
+            if swipe.as_ref().same_client_as(surface.as_ref()) {
+                swipe.begin(serial.into(), time, surface, fingers);
+            }
+        }
+
+        inner.swipe_focus = Some(surface.clone());
+    }
+
+    pub fn swipe_update(&self, time: u32, delta: Point<f64, Logical>) {
+        let inner = self.inner.borrow();
+
+        if let Some(focus) = inner.swipe_focus.as_ref() {
+            for swipe in inner.swipes.iter() {
+                if swipe.as_ref().same_client_as(focus.as_ref()) {
+                    swipe.update(time, delta.x, delta.y);
+                }
+            }
+        }
+    }
+
+    pub fn swipe_end(&self, serial: Serial, time: u32, cancelled: bool) {
+        let mut inner = self.inner.borrow_mut();
+
+        if let Some(focus) = inner.swipe_focus.take() {
+            for swipe in inner.swipes.iter() {
+                if swipe.as_ref().same_client_as(focus.as_ref()) {
+                    swipe.end(serial.into(), time, cancelled as i32);
+                }
+            }
+        }
+    }
+
+    pub fn pinch_begin(&self, surface: &WlSurface, serial: Serial, time: u32, fingers: u32) {
+        let mut inner = self.inner.borrow_mut();
+
+        for pinch in inner.pinches.iter() {
+            if pinch.as_ref().same_client_as(surface.as_ref()) {
+                pinch.begin(serial.into(), time, surface, fingers);
+            }
+        }
+
+        inner.pinch_focus = Some(surface.clone());
+    }
+
+    pub fn pinch_update(&self, time: u32, delta: Point<f64, Logical>, scale: f64, rotation: f64) {
+        let inner = self.inner.borrow();
+
+        if let Some(focus) = inner.pinch_focus.as_ref() {
+            for pinch in inner.pinches.iter() {
+                if pinch.as_ref().same_client_as(focus.as_ref()) {
+                    pinch.update(time, delta.x, delta.y, scale, rotation);
+                }
+            }
+        }
+    }
+
+    pub fn pinch_end(&self, serial: Serial, time: u32, cancelled: bool) {
+        let mut inner = self.inner.borrow_mut();
+
+        if let Some(focus) = inner.pinch_focus.take() {
+            for pinch in inner.pinches.iter() {
+                if pinch.as_ref().same_client_as(focus.as_ref()) {
+                    pinch.end(serial.into(), time, cancelled as i32);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,85 @@
+use std::{cell::RefCell, ops::Deref, rc::Rc};
+
+use smithay::{
+    reexports::{
+        wayland_protocols::unstable::relative_pointer::v1::server::{
+            zwp_relative_pointer_manager_v1::{self, ZwpRelativePointerManagerV1},
+            zwp_relative_pointer_v1::{self, ZwpRelativePointerV1},
+        },
+        wayland_server::{protocol::wl_surface::WlSurface, Display, Filter, Global, Main},
+    },
+    utils::{Logical, Point},
+};
+
+#[derive(Default)]
+struct Inner {
+    pointers: Vec<ZwpRelativePointerV1>,
+}
+
+/// zwp_relative_pointer_manager_v1 global,
+/// sends unclamped pointer deltas to the client with pointer focus
+#[derive(Clone)]
+pub struct RelativePointer {
+    inner: Rc<RefCell<Inner>>,
+}
+
+impl RelativePointer {
+    pub fn init(display: &mut Display) -> (RelativePointer, Global<ZwpRelativePointerManagerV1>) {
+        let inner = Rc::new(RefCell::new(Inner::default()));
+
+        let global: Global<ZwpRelativePointerManagerV1> = display.create_global(1, {
+            let inner = inner.clone();
+            Filter::new(
+                move |(manager, _): (Main<ZwpRelativePointerManagerV1>, _), _, _| {
+                    let inner = inner.clone();
+                    manager.quick_assign(move |_manager, request, _| match request {
+                        zwp_relative_pointer_manager_v1::Request::GetRelativePointer {
+                            id, ..
+                        } => {
+                            id.quick_assign(|_, request, _| match request {
+                                zwp_relative_pointer_v1::Request::Destroy => {}
+                                _ => {}
+                            });
+                            id.assign_destructor(Filter::new({
+                                let inner = inner.clone();
+                                move |res: ZwpRelativePointerV1, _, _| {
+                                    inner.borrow_mut().pointers.retain(|p| p != &res);
+                                }
+                            }));
+
+                            inner.borrow_mut().pointers.push(id.deref().clone());
+                        }
+                        zwp_relative_pointer_manager_v1::Request::Destroy => {}
+                        _ => {}
+                    });
+                },
+            )
+        });
+
+        (RelativePointer { inner }, global)
+    }
+
+    pub fn relative_motion(
+        &self,
+        focus: &WlSurface,
+        time: u32,
+        delta: Point<f64, Logical>,
+        delta_unaccel: Point<f64, Logical>,
+    ) {
+        // Timestamp with microsecond granularity, split into two 32 bit values
+        let utime = time as u64 * 1000;
+
+        for pointer in self.inner.borrow().pointers.iter() {
+            if pointer.as_ref().same_client_as(focus.as_ref()) {
+                pointer.relative_motion(
+                    (utime >> 32) as u32,
+                    (utime & 0xffff_ffff) as u32,
+                    delta.x,
+                    delta.y,
+                    delta_unaccel.x,
+                    delta_unaccel.y,
+                );
+            }
+        }
+    }
+}
@@ -8,6 +8,7 @@ use smithay::wayland::seat::{PointerGrabStartData, Seat};
 use smithay::wayland::shell::wlr_layer::{
     wlr_layer_shell_init, Layer, LayerSurfaceAttributes, LayerSurfaceConfigure,
 };
+use smithay::wayland::shell::xdg::decoration::init_xdg_decoration_manager;
 use smithay::wayland::shell::xdg::{xdg_shell_init, XdgPopupSurfaceRoleAttributes};
 use smithay::wayland::Serial;
 use std::cell::RefCell;
@@ -25,6 +26,8 @@ use surface_lists::{NotMappedList, ShellLayerList, ShellWindowList};
 
 mod wlr_layer;
 mod xdg;
+mod xdg_decoration;
+pub use xdg_decoration::DecorationMode;
 
 #[cfg(feature = "xwayland")]
 pub mod xwayland;
@@ -81,6 +84,13 @@ pub enum ShellEvent {
         toplevel: Kind,
     },
 
+    /// Client bound `zxdg_toplevel_decoration_v1` or asked for a mode,
+    /// `None` leaves the choice to the compositor
+    WindowDecorationMode {
+        toplevel: Kind,
+        mode: Option<DecorationMode>,
+    },
+
     //
     // Popup
     //
@@ -338,6 +348,20 @@ impl<D> ShellManager<D> {
             slog_scope::logger(),
         );
 
+        // init the xdg_decoration_manager
+        init_xdg_decoration_manager(
+            display,
+            {
+                let inner = inner.clone();
+                move |request, mut ddata| {
+                    inner
+                        .borrow_mut()
+                        .xdg_decoration_request(request, ddata.get().unwrap())
+                }
+            },
+            slog_scope::logger(),
+        );
+
         // init the wlr_layer_shell
         wlr_layer_shell_init(
             display,
@@ -0,0 +1,27 @@
+use smithay::{
+    desktop::Kind,
+    reexports::wayland_protocols::unstable::xdg_decoration::v1::server::zxdg_toplevel_decoration_v1::Mode,
+    wayland::shell::xdg::decoration::XdgDecorationRequest,
+};
+
+use super::{ShellEvent, ShellHandler};
+
+pub type DecorationMode = Mode;
+
+impl<D> super::Inner<D>
+where
+    D: ShellHandler,
+{
+    pub fn xdg_decoration_request(&mut self, request: XdgDecorationRequest, handler: &mut D) {
+        let (toplevel, mode) = match request {
+            XdgDecorationRequest::NewToplevelDecoration { toplevel } => (toplevel, None),
+            XdgDecorationRequest::SetMode { toplevel, mode } => (toplevel, Some(mode)),
+            XdgDecorationRequest::UnsetMode { toplevel } => (toplevel, None),
+        };
+
+        handler.on_shell_event(ShellEvent::WindowDecorationMode {
+            toplevel: Kind::Xdg(toplevel),
+            mode,
+        });
+    }
+}
@@ -0,0 +1,231 @@
+use smithay::{
+    backend::input::{
+        Event, GestureBeginEvent, GestureEndEvent, GesturePinchUpdateEvent,
+        GestureSwipeUpdateEvent, InputBackend,
+    },
+    utils::{Logical, Point},
+    wayland::SERIAL_COUNTER as SCOUNTER,
+};
+
+use crate::{
+    config::gestures::{Gesture, GestureKind, PinchDirection, SwipeDirection},
+    Anodium,
+};
+
+/// Horizontal distance a swipe has to travel before the workspace gets switched
+const WORKSPACE_SWIPE_THRESHOLD: f64 = 200.0;
+
+/// Touchpad gesture that is currently in progress
+#[derive(Debug)]
+pub struct GestureState {
+    kind: GestureKind,
+    fingers: u32,
+
+    delta: Point<f64, Logical>,
+    scale: f64,
+
+    /// The gesture is handled by the compositor and not forwarded to clients
+    captured: bool,
+    /// Horizontal movement of the gesture drives workspace switching
+    workspace_swipe: bool,
+}
+
+impl GestureState {
+    fn new(kind: GestureKind, fingers: u32, captured: bool, workspace_swipe: bool) -> Self {
+        Self {
+            kind,
+            fingers,
+            delta: Point::default(),
+            scale: 1.0,
+            captured,
+            workspace_swipe,
+        }
+    }
+
+    fn is_horizontal(&self) -> bool {
+        self.delta.x.abs() > self.delta.y.abs()
+    }
+}
+
+impl Anodium {
+    pub(crate) fn on_gesture_swipe_begin<I: InputBackend>(
+        &mut self,
+        evt: &I::GestureSwipeBeginEvent,
+    ) {
+        let fingers = evt.fingers();
+
+        let workspace_swipe = self.config.workspace_swipe(fingers);
+        let captured = workspace_swipe || self.config.gesture_bound(GestureKind::Swipe, fingers);
+
+        if !captured {
+            if let Some((surface, _)) = self.surface_under(self.input_state.pointer_location) {
+                self.pointer_gestures.swipe_begin(
+                    &surface,
+                    SCOUNTER.next_serial(),
+                    evt.time(),
+                    fingers,
+                );
+            }
+        }
+
+        self.input_state.gesture = Some(GestureState::new(
+            GestureKind::Swipe,
+            fingers,
+            captured,
+            workspace_swipe,
+        ));
+    }
+
+    pub(crate) fn on_gesture_swipe_update<I: InputBackend>(
+        &mut self,
+        evt: &I::GestureSwipeUpdateEvent,
+    ) {
+        if let Some(gesture) = self.input_state.gesture.as_mut() {
+            if gesture.kind != GestureKind::Swipe {
+                return;
+            }
+
+            gesture.delta += evt.delta();
+
+            if !gesture.captured {
+                self.pointer_gestures.swipe_update(evt.time(), evt.delta());
+            } else if gesture.workspace_swipe && gesture.is_horizontal() {
+                self.workspace
+                    .set_offset((gesture.delta.x.round() as i32, 0));
+            } else if gesture.workspace_swipe {
+                self.workspace.reset_offset();
+            }
+        }
+    }
+
+    pub(crate) fn on_gesture_swipe_end<I: InputBackend>(&mut self, evt: &I::GestureSwipeEndEvent) {
+        let gesture = match self.input_state.gesture.take() {
+            Some(gesture) if gesture.kind == GestureKind::Swipe => gesture,
+            _ => return,
+        };
+
+        if !gesture.captured {
+            self.pointer_gestures
+                .swipe_end(SCOUNTER.next_serial(), evt.time(), evt.cancelled());
+            return;
+        }
+
+        if gesture.workspace_swipe {
+            self.workspace.reset_offset();
+        }
+
+        if evt.cancelled() {
+            return;
+        }
+
+        let direction = SwipeDirection::from_delta(gesture.delta);
+        let executed = self.config.gesture_action(Gesture::Swipe {
+            fingers: gesture.fingers,
+            direction,
+        });
+
+        if !executed
+            && gesture.workspace_swipe
+            && gesture.is_horizontal()
+            && gesture.delta.x.abs() >= WORKSPACE_SWIPE_THRESHOLD
+        {
+            // Content follows the fingers, so swiping to the left reveals the next workspace
+            self.switch_workspace_relative(direction == SwipeDirection::Left);
+        }
+    }
+
+    pub(crate) fn on_gesture_pinch_begin<I: InputBackend>(
+        &mut self,
+        evt: &I::GesturePinchBeginEvent,
+    ) {
+        let fingers = evt.fingers();
+        let captured = self.config.gesture_bound(GestureKind::Pinch, fingers);
+
+        if !captured {
+            if let Some((surface, _)) = self.surface_under(self.input_state.pointer_location) {
+                self.pointer_gestures.pinch_begin(
+                    &surface,
+                    SCOUNTER.next_serial(),
+                    evt.time(),
+                    fingers,
+                );
+            }
+        }
+
+        self.input_state.gesture = Some(GestureState::new(
+            GestureKind::Pinch,
+            fingers,
+            captured,
+            false,
+        ));
+    }
+
+    pub(crate) fn on_gesture_pinch_update<I: InputBackend>(
+        &mut self,
+        evt: &I::GesturePinchUpdateEvent,
+    ) {
+        if let Some(gesture) = self.input_state.gesture.as_mut() {
+            if gesture.kind != GestureKind::Pinch {
+                return;
+            }
+
+            gesture.delta += evt.delta();
+            gesture.scale = evt.scale();
+
+            if !gesture.captured {
+                self.pointer_gestures.pinch_update(
+                    evt.time(),
+                    evt.delta(),
+                    evt.scale(),
+                    evt.rotation(),
+                );
+            }
+        }
+    }
+
+    pub(crate) fn on_gesture_pinch_end<I: InputBackend>(&mut self, evt: &I::GesturePinchEndEvent) {
+        let gesture = match self.input_state.gesture.take() {
+            Some(gesture) if gesture.kind == GestureKind::Pinch => gesture,
+            _ => return,
+        };
+
+        if !gesture.captured {
+            self.pointer_gestures
+                .pinch_end(SCOUNTER.next_serial(), evt.time(), evt.cancelled());
+        } else if !evt.cancelled() {
+            self.config.gesture_action(Gesture::Pinch {
+                fingers: gesture.fingers,
+                direction: PinchDirection::from_scale(gesture.scale),
+            });
+        }
+    }
+
+    pub(crate) fn on_gesture_hold_begin<I: InputBackend>(
+        &mut self,
+        evt: &I::GestureHoldBeginEvent,
+    ) {
+        let fingers = evt.fingers();
+        let captured = self.config.gesture_bound(GestureKind::Hold, fingers);
+
+        // zwp_pointer_gestures_v1 version 2 has no hold gestures, so there is nothing to forward
+        self.input_state.gesture = Some(GestureState::new(
+            GestureKind::Hold,
+            fingers,
+            captured,
+            false,
+        ));
+    }
+
+    pub(crate) fn on_gesture_hold_end<I: InputBackend>(&mut self, evt: &I::GestureHoldEndEvent) {
+        let gesture = match self.input_state.gesture.take() {
+            Some(gesture) if gesture.kind == GestureKind::Hold => gesture,
+            _ => return,
+        };
+
+        if gesture.captured && !evt.cancelled() {
+            self.config.gesture_action(Gesture::Hold {
+                fingers: gesture.fingers,
+            });
+        }
+    }
+}
@@ -1,8 +1,17 @@
 use std::sync::atomic::Ordering;
 
 use crate::{
-    framework::backend::{BackendRequest, InputHandler},
+    config::eventloop::ConfigEvent,
+    framework::{
+        backend::{BackendRequest, InputHandler},
+        pointer_constraints::ActiveConstraint,
+    },
     output_manager::Output,
+    window::{
+        decoration::{Button as DecorationButton, Part},
+        Window,
+    },
+    workspace::Expanded,
     Anodium,
 };
 
@@ -11,15 +20,21 @@ use smithay::{
         self, ButtonState, Event, InputBackend, InputEvent, KeyState, KeyboardKeyEvent,
         PointerAxisEvent, PointerButtonEvent, PointerMotionAbsoluteEvent, PointerMotionEvent,
     },
-    desktop::WindowSurfaceType,
-    reexports::wayland_server::protocol::wl_pointer,
-    utils::{Logical, Point},
+    desktop::{self, WindowSurfaceType},
+    reexports::wayland_server::protocol::{wl_pointer, wl_surface::WlSurface},
+    utils::{Logical, Point, Rectangle},
     wayland::{
-        seat::{keysyms as xkb, AxisFrame, FilterResult, Keysym, ModifiersState},
-        SERIAL_COUNTER as SCOUNTER,
+        compositor::{RectangleKind, RegionAttributes},
+        seat::{
+            keysyms as xkb, AxisFrame, FilterResult, Keysym, ModifiersState, PointerGrabStartData,
+        },
+        Serial, SERIAL_COUNTER as SCOUNTER,
     },
 };
 
+const BTN_LEFT: u32 = 0x110;
+const BTN_RIGHT: u32 = 0x111;
+
 impl InputHandler for Anodium {
     fn process_input_event<I: InputBackend>(
         &mut self,
@@ -39,11 +54,8 @@ impl InputHandler for Anodium {
                 }
             }
             InputEvent::PointerMotion { event, .. } => {
-                self.input_state.pointer_location =
-                    self.clamp_coords(self.input_state.pointer_location + event.delta());
-                self.on_pointer_move(event.time());
-                self.surface_under(self.input_state.pointer_location)
-                    .is_none()
+                self.on_pointer_move_relative::<I>(event);
+                self.shell_under(self.input_state.pointer_location)
             }
             InputEvent::PointerMotionAbsolute { event, .. } => {
                 let output = output.cloned().unwrap_or_else(|| {
@@ -62,18 +74,91 @@ impl InputHandler for Anodium {
                 self.input_state.pointer_location =
                     event.position_transformed(output_size) + output_pos;
                 self.on_pointer_move(event.time());
-                self.surface_under(self.input_state.pointer_location)
-                    .is_none()
+                self.shell_under(self.input_state.pointer_location)
             }
             InputEvent::PointerButton { event, .. } => {
                 self.on_pointer_button::<I>(event);
-                self.surface_under(self.input_state.pointer_location)
-                    .is_none()
+                self.shell_under(self.input_state.pointer_location)
             }
             InputEvent::PointerAxis { event, .. } => {
                 self.on_pointer_axis::<I>(event);
-                self.surface_under(self.input_state.pointer_location)
-                    .is_none()
+                self.shell_under(self.input_state.pointer_location)
+            }
+            InputEvent::GestureSwipeBegin { event, .. } => {
+                self.on_gesture_swipe_begin::<I>(event);
+                return;
+            }
+            InputEvent::GestureSwipeUpdate { event, .. } => {
+                self.on_gesture_swipe_update::<I>(event);
+                return;
+            }
+            InputEvent::GestureSwipeEnd { event, .. } => {
+                self.on_gesture_swipe_end::<I>(event);
+                return;
+            }
+            InputEvent::GesturePinchBegin { event, .. } => {
+                self.on_gesture_pinch_begin::<I>(event);
+                return;
+            }
+            InputEvent::GesturePinchUpdate { event, .. } => {
+                self.on_gesture_pinch_update::<I>(event);
+                return;
+            }
+            InputEvent::GesturePinchEnd { event, .. } => {
+                self.on_gesture_pinch_end::<I>(event);
+                return;
+            }
+            InputEvent::GestureHoldBegin { event, .. } => {
+                self.on_gesture_hold_begin::<I>(event);
+                return;
+            }
+            InputEvent::GestureHoldEnd { event, .. } => {
+                self.on_gesture_hold_end::<I>(event);
+                return;
+            }
+            InputEvent::DeviceAdded { device } => {
+                self.on_tablet_device_added::<I>(device);
+                return;
+            }
+            InputEvent::DeviceRemoved { device } => {
+                self.on_tablet_device_removed::<I>(device);
+                return;
+            }
+            InputEvent::TabletToolAxis { event, .. } => {
+                self.on_tablet_tool_axis::<I>(event, output);
+                return;
+            }
+            InputEvent::TabletToolProximity { event, .. } => {
+                self.on_tablet_tool_proximity::<I>(event, output);
+                return;
+            }
+            InputEvent::TabletToolTip { event, .. } => {
+                self.on_tablet_tool_tip::<I>(event);
+                return;
+            }
+            InputEvent::TabletToolButton { event, .. } => {
+                self.on_tablet_tool_button::<I>(event);
+                return;
+            }
+            InputEvent::TouchDown { event, .. } => {
+                self.on_touch_down::<I>(event, output);
+                return;
+            }
+            InputEvent::TouchMotion { event, .. } => {
+                self.on_touch_motion::<I>(event, output);
+                return;
+            }
+            InputEvent::TouchUp { event, .. } => {
+                self.on_touch_up::<I>(event);
+                return;
+            }
+            InputEvent::TouchCancel { event, .. } => {
+                self.on_touch_cancel::<I>(event);
+                return;
+            }
+            InputEvent::TouchFrame { event, .. } => {
+                self.on_touch_frame::<I>(event);
+                return;
             }
             _ => false,
         };
@@ -95,7 +180,32 @@ impl InputHandler for Anodium {
 }
 
 impl Anodium {
-    fn reset_egui_event(&self, output: &Output) {
+    /// Output an absolute input device is mapped to,
+    /// either the one selected in config, the one reported by the backend or the first one
+    pub(crate) fn mapped_output(
+        &self,
+        configured: Option<String>,
+        output: Option<&Output>,
+    ) -> Option<Output> {
+        let configured = configured.and_then(|name| {
+            self.workspace
+                .outputs()
+                .find(|o| o.name() == name)
+                .cloned()
+                .map(Output::wrap)
+        });
+
+        configured
+            .or_else(|| output.cloned())
+            .or_else(|| self.workspace.outputs().next().cloned().map(Output::wrap))
+    }
+
+    /// Nothing but the egui shell is under the point, so it gets the pointer events
+    fn shell_under(&self, point: Point<f64, Logical>) -> bool {
+        self.surface_under(point).is_none() && self.decoration_under(point).is_none()
+    }
+
+    pub(crate) fn reset_egui_event(&self, output: &Output) {
         let mut max_point = Point::default();
         max_point.x = i32::MAX;
         max_point.y = i32::MAX;
@@ -122,14 +232,8 @@ impl Anodium {
                 }
             }
 
-            //InputEvent::Keyboard { event } => {
-            //TODO - is that enough or do we need the whole code from here https://github.com/Smithay/smithay-egui/blob/main/examples/integrate.rs#L69 ?
-            // output.egui().handle_keyboard(
-            //     event.key_code(),
-            //     event.state() == KeyState::Pressed,
-            //     self.input_state.modifiers_state,
-            // );
-            //}
+            // Keyboard events are forwarded from the keyboard filter in `keyboard_key_to_action`,
+            // as egui needs the xkb translated keysym
             InputEvent::PointerAxis { event, .. } => output.egui().handle_pointer_axis(
                 event
                     .amount_discrete(input::Axis::Horizontal)
@@ -160,6 +264,13 @@ impl Anodium {
         let pressed_keys = &mut self.input_state.pressed_keys;
         let configvm = self.config.clone();
 
+        // Egui shell that got focused by a click and has a widget waiting for text
+        let egui = self
+            .input_state
+            .egui_focus
+            .clone()
+            .filter(|output| output.egui().wants_keyboard());
+
         self.input_state
             .keyboard
             .input(keycode, state, serial, time, |modifiers, handle| {
@@ -194,12 +305,20 @@ impl Anodium {
                     } else if configvm.key_action(keysym, state, pressed_keys) {
                         suppressed_keys.push(keysym);
                         return FilterResult::Intercept(KeyAction::Filtred);
+                    } else if let Some(output) = egui.as_ref() {
+                        output.egui().handle_keyboard(&handle, true, *modifiers);
+                        suppressed_keys.push(keysym);
+                        return FilterResult::Intercept(KeyAction::Filtred);
                     }
 
                     action
                         .map(FilterResult::Intercept)
                         .unwrap_or(FilterResult::Forward)
                 } else {
+                    if let Some(output) = egui.as_ref() {
+                        output.egui().handle_keyboard(&handle, false, *modifiers);
+                    }
+
                     let suppressed = suppressed_keys.contains(&keysym);
                     if suppressed {
                         suppressed_keys.retain(|k| *k != keysym);
@@ -223,13 +342,18 @@ impl Anodium {
         debug!("Mouse Event"; "Mouse button" => format!("{:?}", evt.button()));
 
         let button = evt.button_code();
+        let mut decoration = None;
         let state = match evt.state() {
             input::ButtonState::Pressed => {
                 // change the keyboard focus unless the pointer is grabbed
                 if !self.input_state.pointer.is_grabbed() {
                     let point = self.input_state.pointer_location;
+                    decoration = self.decoration_under(point);
                     // let under = self.surface_under(self.input_state.pointer_location);
-                    let window = self.workspace.window_under(point).cloned();
+                    let window = decoration
+                        .as_ref()
+                        .map(|(window, _)| window.clone())
+                        .or_else(|| self.workspace.window_under(point).cloned());
                     // let surface = under.as_ref().map(|&(ref s, _)| s);
                     // if let Some(surface) = surface {
                     //     let mut window = None;
@@ -241,9 +365,23 @@ impl Anodium {
 
                     self.update_focused_window(window.as_ref());
 
-                    let surface = window
-                        .and_then(|w| w.surface_under(point, WindowSurfaceType::ALL))
-                        .map(|s| s.0);
+                    // Clicking the shell moves the keyboard focus from clients to egui
+                    self.input_state.egui_focus = match window {
+                        Some(_) => None,
+                        None => self
+                            .workspace
+                            .output_under(point)
+                            .next()
+                            .cloned()
+                            .map(Output::wrap),
+                    };
+
+                    let surface = match decoration {
+                        Some(_) => window.and_then(|w| w.toplevel().get_surface().cloned()),
+                        None => window
+                            .and_then(|w| w.surface_under(point, WindowSurfaceType::ALL))
+                            .map(|s| s.0),
+                    };
 
                     self.input_state
                         .keyboard
@@ -258,6 +396,15 @@ impl Anodium {
             .clone()
             .button(button, state, serial, evt.time(), self);
 
+        match state {
+            wl_pointer::ButtonState::Pressed => {
+                if let Some((window, part)) = decoration {
+                    self.on_decoration_pressed(&window, part, button, serial);
+                }
+            }
+            _ => self.on_decoration_released(),
+        }
+
         // {
         //     if evt.state() == input::ButtonState::Pressed {
         //         let under = self.surface_under(self.input_state.pointer_location);
@@ -305,6 +452,66 @@ impl Anodium {
         // }
     }
 
+    /// Titlebar starts a move, borders a resize and buttons act once released over them
+    fn on_decoration_pressed(
+        &mut self,
+        window: &desktop::Window,
+        part: Part,
+        button: u32,
+        serial: Serial,
+    ) {
+        self.workspace.raise_window(window, true);
+
+        let pointer = self.input_state.pointer.clone();
+        let start_data = PointerGrabStartData {
+            focus: None,
+            button,
+            location: self.input_state.pointer_location,
+        };
+
+        match (button, part) {
+            (BTN_LEFT, Part::Titlebar) => self.start_move(&pointer, window, start_data, serial),
+            (BTN_LEFT, Part::Edge(edges)) => {
+                self.start_resize(&pointer, window, start_data, edges, serial)
+            }
+            (BTN_LEFT, Part::Button(button)) => {
+                self.input_state.decoration_press = Some((window.clone(), button));
+            }
+            (BTN_RIGHT, Part::Titlebar) => {
+                if let Some(geometry) = self.workspace.window_geometry(window) {
+                    let location = self.input_state.pointer_location - geometry.loc.to_f64();
+                    self.show_window_menu(window, location.to_i32_round());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn on_decoration_released(&mut self) {
+        let (window, button) = match self.input_state.decoration_press.take() {
+            Some(press) => press,
+            None => return,
+        };
+
+        // Pressing a button and releasing it somewhere else cancels the click
+        let under = self.decoration_under(self.input_state.pointer_location);
+        if under != Some((window.clone(), Part::Button(button))) {
+            return;
+        }
+
+        let event = match button {
+            DecorationButton::Close => ConfigEvent::Close,
+            DecorationButton::Maximize
+                if self.workspace.expanded(&window) == Some(Expanded::Maximized) =>
+            {
+                ConfigEvent::Unmaximize
+            }
+            DecorationButton::Maximize => ConfigEvent::Maximize,
+            DecorationButton::Minimize => ConfigEvent::Minimize,
+        };
+        self.process_config_event(event(Window::wrap(window)));
+    }
+
     fn on_pointer_axis<I: InputBackend>(&mut self, evt: &I::PointerAxisEvent) {
         let source = match evt.source() {
             input::AxisSource::Continuous => wl_pointer::AxisSource::Continuous,
@@ -344,7 +551,35 @@ impl Anodium {
         }
     }
 
-    fn on_pointer_move(&mut self, time: u32) {
+    fn on_pointer_move_relative<I: InputBackend>(&mut self, evt: &I::PointerMotionEvent) {
+        if let Some(focus) = self.input_state.pointer_focus.as_ref() {
+            if focus.as_ref().is_alive() {
+                self.relative_pointer.relative_motion(
+                    focus,
+                    evt.time(),
+                    evt.delta(),
+                    evt.delta_unaccel(),
+                );
+            }
+        }
+
+        // Locked pointer only emits relative motion, unless its surface got unmapped,
+        // minimized or moved away from under the pointer meanwhile
+        if let Some(ActiveConstraint::Locked) = self.pointer_constraints.active() {
+            let under = self.surface_under(self.input_state.pointer_location);
+            self.update_pointer_constraint(under.as_ref());
+
+            if let Some(ActiveConstraint::Locked) = self.pointer_constraints.active() {
+                return;
+            }
+        }
+
+        self.input_state.pointer_location =
+            self.clamp_coords(self.input_state.pointer_location + evt.delta());
+        self.on_pointer_move(evt.time());
+    }
+
+    pub(crate) fn on_pointer_move(&mut self, time: u32) {
         let serial = SCOUNTER.next_serial();
 
         // for (id, w) in self.workspaces.iter_mut() {
@@ -358,6 +593,14 @@ impl Anodium {
         // }
 
         let under = self.surface_under(self.input_state.pointer_location);
+
+        self.update_pointer_constraint(under.as_ref());
+
+        // Grabs keep sending the events to the surface they started on
+        if !self.input_state.pointer.is_grabbed() {
+            self.input_state.pointer_focus = under.as_ref().map(|(surface, _)| surface.clone());
+        }
+
         self.input_state.pointer.clone().motion(
             self.input_state.pointer_location,
             under,
@@ -367,22 +610,133 @@ impl Anodium {
         );
     }
 
-    fn clamp_coords(&self, pos: Point<f64, Logical>) -> Point<f64, Logical> {
-        // let (pos_x, pos_y) = pos.into();
-        // let output_map = &self.output_map;
-        // let max_x = output_map.width();
-        // let clamped_x = pos_x.max(0.0).min(max_x as f64);
-        // let max_y = output_map.height(clamped_x as i32);
+    /// Activate the constraint of the surface under the pointer, if it has one
+    fn update_pointer_constraint(&self, under: Option<&(WlSurface, Point<i32, Logical>)>) {
+        let location = self.input_state.pointer_location;
+        self.pointer_constraints
+            .set_focus(under.map(|(surface, loc)| (surface, location - loc.to_f64())));
+    }
 
-        // if let Some(max_y) = max_y {
-        //     let clamped_y = pos_y.max(0.0).min(max_y as f64);
+    /// Clamp the position to the union of all output geometries,
+    /// positions outside of every output are moved to the nearest point of the closest output
+    pub(crate) fn clamp_coords(&self, pos: Point<f64, Logical>) -> Point<f64, Logical> {
+        let geometries = self
+            .workspace
+            .outputs()
+            .filter_map(|o| self.workspace.output_geometry(o));
+
+        let mut nearest: Option<(f64, Point<f64, Logical>)> = None;
+        for geometry in geometries {
+            let geometry = geometry.to_f64();
+            if geometry.contains(pos) {
+                nearest = Some((0.0, pos));
+                break;
+            }
 
-        //     (clamped_x, clamped_y).into()
-        // } else {
-        //     (clamped_x, pos_y).into()
-        // }
+            // The right and bottom edges are not part of the output
+            let max_x = geometry.loc.x + (geometry.size.w - 1.0).max(0.0);
+            let max_y = geometry.loc.y + (geometry.size.h - 1.0).max(0.0);
+
+            let clamped: Point<f64, Logical> = (
+                pos.x.max(geometry.loc.x).min(max_x),
+                pos.y.max(geometry.loc.y).min(max_y),
+            )
+                .into();
+
+            let diff = clamped - pos;
+            let distance = diff.x * diff.x + diff.y * diff.y;
+
+            if nearest.map(|(d, _)| distance < d).unwrap_or(true) {
+                nearest = Some((distance, clamped));
+            }
+        }
+
+        let pos = nearest.map(|(_, clamped)| clamped).unwrap_or(pos);
+
+        match self.pointer_constraints.active() {
+            Some(ActiveConstraint::Confined { surface, region }) => {
+                self.confine_coords(pos, &surface, region.as_ref())
+            }
+            _ => pos,
+        }
+    }
+
+    /// Keep the position inside of the confinement region of the surface with pointer focus
+    fn confine_coords(
+        &self,
+        pos: Point<f64, Logical>,
+        surface: &WlSurface,
+        region: Option<&RegionAttributes>,
+    ) -> Point<f64, Logical> {
+        let current = self.input_state.pointer_location;
+
+        let surface_loc = match self.surface_under(current) {
+            Some((under, loc)) if &under == surface => loc,
+            _ => return pos,
+        };
 
-        pos
+        let rects: Vec<Rectangle<i32, Logical>> = match region {
+            Some(region) => {
+                let relative = pos - surface_loc.to_f64();
+                if region.contains(relative.to_i32_floor()) {
+                    return pos;
+                }
+
+                region
+                    .rects
+                    .iter()
+                    .filter(|(kind, _)| matches!(kind, RectangleKind::Add))
+                    .map(|(_, rect)| {
+                        Rectangle::from_loc_and_size(rect.loc + surface_loc, rect.size)
+                    })
+                    .collect()
+            }
+            None => self
+                .workspace
+                .window_under(current)
+                .and_then(|w| self.workspace.window_bbox(w))
+                .into_iter()
+                .collect(),
+        };
+
+        let confined = rects
+            .iter()
+            .map(|rect| {
+                let rect = rect.to_f64();
+                let max_x = rect.loc.x + (rect.size.w - 1.0).max(0.0);
+                let max_y = rect.loc.y + (rect.size.h - 1.0).max(0.0);
+                Point::<f64, Logical>::from((
+                    pos.x.max(rect.loc.x).min(max_x),
+                    pos.y.max(rect.loc.y).min(max_y),
+                ))
+            })
+            .min_by(|a, b| {
+                let da = (*a - pos).x.powi(2) + (*a - pos).y.powi(2);
+                let db = (*b - pos).x.powi(2) + (*b - pos).y.powi(2);
+                da.partial_cmp(&db).unwrap_or(std::cmp::Ordering::Equal)
+            });
+
+        match confined {
+            // Clamped point can still land in a subtracted part of the region
+            Some(confined)
+                if region
+                    .map(|r| r.contains((confined - surface_loc.to_f64()).to_i32_floor()))
+                    .unwrap_or(true) =>
+            {
+                confined
+            }
+            _ => current,
+        }
+    }
+
+    /// Move the pointer back onto an output, used after the output layout changed
+    pub fn clamp_pointer(&mut self) {
+        let clamped = self.clamp_coords(self.input_state.pointer_location);
+
+        if clamped != self.input_state.pointer_location {
+            self.input_state.pointer_location = clamped;
+            self.on_pointer_move(self.start_time.elapsed().as_millis() as u32);
+        }
     }
 }
 
@@ -5,6 +5,7 @@
 extern crate slog_scope;
 
 mod event_handler;
+mod gesture_handler;
 mod input_handler;
 
 mod framework;
@@ -25,9 +26,12 @@ mod state;
 
 mod backend_handler;
 mod shell_handler;
+mod tablet_handler;
+mod touch_handler;
 
 mod cli;
 
+mod animation;
 mod workspace;
 
 use config::outputs::shell::logger::ShellDrain;
@@ -45,4 +45,9 @@ impl OutputManager {
         space.map_output(output, 1.0, loc);
         self.outputs.borrow_mut().push(output.clone());
     }
+
+    pub fn remove(&mut self, space: &mut Workspace, output: &Output) {
+        space.unmap_output(output);
+        self.outputs.borrow_mut().retain(|o| o != output);
+    }
 }
@@ -1,11 +1,14 @@
 use std::cell::{Cell, RefCell, RefMut};
+use std::collections::{hash_map::Entry, HashMap};
+use std::rc::{Rc, Weak};
 use std::time::Instant;
 
 use anodium_protocol::server::{AnodiumProtocol, AnodiumProtocolOutput};
 use calloop::channel::Sender;
+use smithay::backend::renderer::gles2::{Gles2Renderer, Gles2Texture};
 use smithay::desktop;
 use smithay::reexports::wayland_server::protocol::wl_output::WlOutput;
-use smithay::utils::Rectangle;
+use smithay::utils::{Logical, Point, Rectangle};
 use smithay::wayland::output::Output as SmithayOutput;
 
 use smithay::wayland::seat::ModifiersState;
@@ -17,7 +20,25 @@ use smithay::{
 use smithay_egui::{EguiFrame, EguiMode, EguiState};
 
 use crate::config::eventloop::ConfigEvent;
-use crate::config::outputs::shell::Shell;
+use crate::config::notifications::Notifications;
+use crate::config::osd::Osd;
+use crate::config::outputs::shell::{
+    container::OutputState,
+    image::{self, Placement, Raster},
+    panel::PanelPosition,
+    Shell,
+};
+use crate::config::theme::Theme;
+use crate::render::{
+    border::intersection,
+    image::ImageElement,
+    quad::QuadPipeline,
+    renderer::import_bitmap,
+    snapshot::{Snapshot, SnapshotElement, SnapshotSource},
+    texture::{Offscreen, TexturePipeline},
+};
+use crate::window::decoration::{self, Decoration};
+use crate::workspace::{WindowInfo, WorkspaceInfo};
 
 /// Inmutable description of phisical output
 /// Used before wayland output is created
@@ -50,6 +71,29 @@ struct Data {
 
     egui: RefCell<EguiState>,
     egui_shell: Shell,
+    /// Generation of the theme applied to the egui context
+    theme_generation: Cell<Option<u64>>,
+
+    /// Workspaces as of the last render, read by shell widgets
+    workspaces: RefCell<Vec<WorkspaceInfo>>,
+    windows: RefCell<Vec<WindowInfo>>,
+    decorations: RefCell<Vec<Decoration>>,
+    /// Border strips drawn in the last frame
+    borders: RefCell<Vec<(Rectangle<i32, Logical>, [f32; 4])>>,
+    quad_pipeline: RefCell<Option<Rc<QuadPipeline>>>,
+    texture_pipeline: RefCell<Option<Rc<TexturePipeline>>>,
+    /// Last look of the windows on this output, drawn by their close animation
+    snapshots: Rc<RefCell<Vec<Snapshot>>>,
+    /// The last frame got drawn while windows were animated, bypassing the damage tracking of the space
+    animated_frame: Cell<bool>,
+    /// Images painted by the last egui run
+    images: RefCell<Vec<Placement>>,
+    /// Uploaded images, dropped once their raster is gone
+    image_textures: RefCell<HashMap<egui::TextureId, (Weak<Raster>, Gles2Texture)>>,
+    /// Images drawn in the last frame, with their visible part
+    drawn_images: RefCell<Vec<(egui::TextureId, Rectangle<i32, Logical>)>>,
+    pointer: Cell<Option<Point<f64, Logical>>>,
+    fullscreen: Cell<bool>,
 
     #[cfg(feature = "debug")]
     fps_ticker: fps_ticker::Fps,
@@ -94,19 +138,6 @@ impl Output {
 
         let mut egui = EguiState::new(EguiMode::Reactive);
         egui.set_zindex(0);
-        let mut visuals = egui::style::Visuals {
-            window_corner_radius: 0.0,
-            ..Default::default()
-        };
-
-        visuals.widgets.inactive.corner_radius = 0.0;
-        visuals.widgets.noninteractive.corner_radius = 0.0;
-        visuals.widgets.hovered.corner_radius = 0.0;
-        visuals.widgets.active.corner_radius = 0.0;
-        visuals.widgets.open.corner_radius = 0.0;
-        visuals.window_shadow.extrusion = 0.0;
-
-        egui.context().set_visuals(visuals);
 
         let added = output.user_data().insert_if_missing(move || Data {
             _anodium_protocol_output: anodium_protocol_output,
@@ -115,6 +146,20 @@ impl Output {
             possible_modes: RefCell::new(possible_modes),
             egui: RefCell::new(egui),
             egui_shell: Shell::new(),
+            theme_generation: Default::default(),
+            workspaces: Default::default(),
+            windows: Default::default(),
+            decorations: Default::default(),
+            borders: Default::default(),
+            quad_pipeline: Default::default(),
+            texture_pipeline: Default::default(),
+            snapshots: Default::default(),
+            animated_frame: Default::default(),
+            images: Default::default(),
+            image_textures: Default::default(),
+            drawn_images: Default::default(),
+            pointer: Default::default(),
+            fullscreen: Default::default(),
             fps_ticker: fps_ticker::Fps::default(),
         });
         assert!(added);
@@ -148,23 +193,298 @@ impl Output {
         &self.data().egui_shell
     }
 
+    /// Area left for windows, relative to the output, after layer surfaces
+    /// and egui panels took their exclusive zones
+    pub fn usable_area(&self) -> Rectangle<i32, Logical> {
+        let mut area = self.layer_map().non_exclusive_zone();
+
+        for (position, size) in self.data().egui_shell.exclusive_zones() {
+            let size = (size.ceil() as i32).max(0);
+            match position {
+                PanelPosition::Top => {
+                    area.loc.y += size;
+                    area.size.h -= size;
+                }
+                PanelPosition::Bottom => {
+                    area.size.h -= size;
+                }
+                PanelPosition::Left => {
+                    area.loc.x += size;
+                    area.size.w -= size;
+                }
+                PanelPosition::Right => {
+                    area.size.w -= size;
+                }
+            }
+        }
+
+        area.size.w = area.size.w.max(0);
+        area.size.h = area.size.h.max(0);
+        area
+    }
+
+    pub fn workspaces(&self) -> Vec<WorkspaceInfo> {
+        self.data().workspaces.borrow().clone()
+    }
+
+    pub fn set_workspaces(&self, workspaces: Vec<WorkspaceInfo>) {
+        let mut current = self.data().workspaces.borrow_mut();
+        if *current != workspaces {
+            // Switching workspace does not give egui any input, the shell has to be redrawn
+            self.data().egui.borrow().context().request_repaint();
+            *current = workspaces;
+        }
+    }
+
+    pub fn windows(&self) -> Vec<WindowInfo> {
+        self.data().windows.borrow().clone()
+    }
+
+    pub fn set_windows(&self, windows: Vec<WindowInfo>) {
+        let mut current = self.data().windows.borrow_mut();
+        if *current != windows {
+            // Windows come and go without egui getting any input, the taskbar has to be redrawn
+            self.data().egui.borrow().context().request_repaint();
+            *current = windows;
+        }
+    }
+
+    pub fn set_decorations(&self, decorations: Vec<Decoration>) {
+        let mut current = self.data().decorations.borrow_mut();
+        if *current != decorations {
+            // Decorations are outside of the window damage, so the shell has to be redrawn
+            self.data().egui.borrow().context().request_repaint();
+            *current = decorations;
+        }
+    }
+
+    /// Store the borders of this frame, returns the area that has to be redrawn
+    pub fn set_borders(
+        &self,
+        borders: Vec<(Rectangle<i32, Logical>, [f32; 4])>,
+    ) -> Vec<Rectangle<i32, Logical>> {
+        let mut current = self.data().borders.borrow_mut();
+        if *current == borders {
+            return Vec::new();
+        }
+
+        let damage = current
+            .iter()
+            .chain(borders.iter())
+            .map(|(rect, _)| *rect)
+            .collect();
+        *current = borders;
+        damage
+    }
+
+    /// Pipeline drawing the borders, created in the GL context of the renderer of this output
+    pub fn quad_pipeline(&self, renderer: &mut Gles2Renderer) -> Option<Rc<QuadPipeline>> {
+        let mut pipeline = self.data().quad_pipeline.borrow_mut();
+        if pipeline.is_none() {
+            *pipeline = renderer
+                .with_context(|_, gl| Rc::new(QuadPipeline::new(gl)))
+                .ok();
+        }
+        pipeline.clone()
+    }
+
+    /// Pipeline drawing window animations, created in the GL context of the renderer of this output
+    pub fn texture_pipeline(&self, renderer: &mut Gles2Renderer) -> Option<Rc<TexturePipeline>> {
+        let mut pipeline = self.data().texture_pipeline.borrow_mut();
+        if pipeline.is_none() {
+            *pipeline = renderer
+                .with_context(|_, gl| Rc::new(TexturePipeline::new(gl)))
+                .ok();
+        }
+        pipeline.clone()
+    }
+
+    /// Keeps the snapshots of the given windows up to date, and those of closing windows around
+    pub fn snapshot_element(
+        &self,
+        sources: Vec<SnapshotSource>,
+        closing: Vec<desktop::Window>,
+    ) -> SnapshotElement {
+        let scale = self.output.current_scale();
+        let mode_size = self.current_mode().unwrap().size;
+        SnapshotElement::new(
+            mode_size.to_logical(scale),
+            mode_size,
+            self.current_transform().into(),
+            self.data().snapshots.clone(),
+            sources,
+            closing,
+        )
+    }
+
+    pub fn snapshot(&self, window: &desktop::Window) -> Option<Offscreen> {
+        self.data()
+            .snapshots
+            .borrow()
+            .iter()
+            .find(|snapshot| &snapshot.window == window)
+            .and_then(|snapshot| snapshot.offscreen)
+    }
+
+    /// Buffer age to render the space with, the first frame after animated ones is drawn whole
+    pub fn space_age(&self, age: usize) -> usize {
+        if self.data().animated_frame.replace(false) {
+            0
+        } else {
+            age
+        }
+    }
+
+    pub fn set_animated_frame(&self) {
+        self.data().animated_frame.set(true);
+    }
+
+    /// Images painted by the egui shell in its last run, uploaded on first use
+    pub fn image_element(&self, renderer: &mut Gles2Renderer) -> ImageElement {
+        let data = self.data();
+        let scale = self.output.current_scale();
+        let output_size = self.current_mode().unwrap().size.to_logical(scale);
+        let output_rect = egui::Rect::from_min_size(
+            egui::pos2(0.0, 0.0),
+            egui::vec2(output_size.w as f32, output_size.h as f32),
+        );
+
+        let mut textures = data.image_textures.borrow_mut();
+        textures.retain(|_, (raster, _)| raster.strong_count() > 0);
+
+        let mut images = Vec::new();
+        for placement in data.images.borrow().iter() {
+            let raster = &placement.raster;
+            let texture = match textures.entry(raster.texture_id()) {
+                Entry::Occupied(entry) => entry.get().1.clone(),
+                Entry::Vacant(entry) => {
+                    let (width, height) = raster.dimensions();
+                    let buffer = ::image::ImageBuffer::<::image::Rgba<u8>, _>::from_raw(
+                        width,
+                        height,
+                        raster.rgba(),
+                    )
+                    .unwrap();
+                    match import_bitmap(renderer, &buffer, None) {
+                        Ok(texture) => entry.insert((Rc::downgrade(raster), texture)).1.clone(),
+                        Err(err) => {
+                            warn!("failed to upload image: {}", err);
+                            continue;
+                        }
+                    }
+                }
+            };
+
+            let (width, height) = raster.dimensions();
+            let rect = Rectangle::from_loc_and_size(
+                (
+                    placement.rect.min.x.round() as i32,
+                    placement.rect.min.y.round() as i32,
+                ),
+                (width as i32, height as i32),
+            );
+            let clip = placement.clip.intersect(output_rect);
+            let (x, y) = (clip.min.x.floor() as i32, clip.min.y.floor() as i32);
+            let clip = Rectangle::from_loc_and_size(
+                (x, y),
+                (clip.max.x.ceil() as i32 - x, clip.max.y.ceil() as i32 - y),
+            );
+
+            if let Some(visible) = intersection(rect, clip) {
+                images.push((raster.texture_id(), texture, rect, visible));
+            }
+        }
+
+        let drawn: Vec<_> = images
+            .iter()
+            .map(|(id, _, _, visible)| (*id, *visible))
+            .collect();
+        let mut current = data.drawn_images.borrow_mut();
+        let damage = if *current == drawn {
+            Vec::new()
+        } else {
+            current
+                .iter()
+                .chain(drawn.iter())
+                .map(|(_, visible)| *visible)
+                .collect()
+        };
+        *current = drawn;
+
+        ImageElement::new(
+            output_size,
+            images
+                .into_iter()
+                .map(|(_, texture, rect, visible)| (texture, rect, visible))
+                .collect(),
+            damage,
+        )
+    }
+
+    /// Pointer location relative to the output, `None` when it is on another output
+    pub fn set_pointer(&self, pointer: Option<Point<f64, Logical>>) {
+        self.data().pointer.set(pointer);
+    }
+
+    pub fn set_fullscreen(&self, fullscreen: bool) {
+        if self.data().fullscreen.replace(fullscreen) != fullscreen {
+            // Panels hide behind fullscreen windows without egui getting any input
+            self.data().egui.borrow().context().request_repaint();
+        }
+    }
+
     pub fn render_egui_shell(
         &self,
         start_time: &Instant,
         modifiers: &ModifiersState,
+        theme: &Theme,
+        notifications: Option<&Notifications>,
+        osd: &Osd,
         config_tx: &Sender<ConfigEvent>,
     ) -> EguiFrame {
         let scale = self.output.current_scale();
         let size = self.output.current_mode().unwrap().size;
 
         let data = self.data();
-        data.egui.borrow_mut().run(
+
+        if data.theme_generation.get() != Some(theme.generation()) {
+            let egui = data.egui.borrow();
+            egui.context().set_visuals(theme.visuals());
+            egui.context().set_fonts(theme.fonts());
+            data.theme_generation.set(Some(theme.generation()));
+        }
+        let state = OutputState {
+            workspace: data
+                .workspaces
+                .borrow()
+                .iter()
+                .find(|w| w.active)
+                .map(|w| w.name.clone())
+                .unwrap_or_default(),
+            pointer: data
+                .pointer
+                .get()
+                .map(|p| egui::pos2(p.x as f32, p.y as f32)),
+            fullscreen: data.fullscreen.get(),
+        };
+
+        // Drop what got painted outside of a shell run
+        image::take_placed();
+        let ran = Cell::new(false);
+
+        let frame = data.egui.borrow_mut().run(
             |ctx| {
+                ran.set(true);
                 //TODO - fix that in smithay, currently if crashes if egui does not have any element
                 egui::Area::new("main")
                     .anchor(egui::Align2::LEFT_TOP, (10.0, 10.0))
                     .show(ctx, |_ui| {});
-                data.egui_shell.render(ctx, config_tx);
+                decoration::paint(ctx, &data.decorations.borrow());
+                data.egui_shell.render(ctx, &state, config_tx);
+                if let Some(notifications) = notifications {
+                    notifications.render(ctx, config_tx);
+                }
+                osd.render(ctx);
             },
             Rectangle::from_loc_and_size((0, 0), size.to_logical(scale)),
             size,
@@ -172,7 +492,13 @@ impl Output {
             1.0,
             start_time,
             *modifiers,
-        )
+        );
+
+        // Egui may skip the run when nothing changed, the images then stay where they were
+        if ran.get() {
+            *data.images.borrow_mut() = image::take_placed();
+        }
+        frame
     }
 
     #[cfg(feature = "debug")]
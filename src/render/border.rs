@@ -0,0 +1,129 @@
+use std::rc::Rc;
+
+use smithay::{
+    backend::renderer::gles2::{Gles2Error, Gles2Frame, Gles2Renderer, Gles2Texture},
+    desktop::space::{RenderElement, SpaceOutputTuple},
+    utils::{Logical, Physical, Point, Rectangle, Size, Transform},
+};
+
+use super::quad::QuadPipeline;
+
+/// Window borders of one output, every rectangle is relative to the output
+pub struct BorderElement {
+    pipeline: Rc<QuadPipeline>,
+    output_size: Size<i32, Logical>,
+    mode_size: Size<i32, Physical>,
+    transform: Transform,
+    rects: Vec<(Rectangle<i32, Logical>, [f32; 4])>,
+    damage: Vec<Rectangle<i32, Logical>>,
+}
+
+impl BorderElement {
+    pub fn new(
+        pipeline: Rc<QuadPipeline>,
+        output_size: Size<i32, Logical>,
+        mode_size: Size<i32, Physical>,
+        transform: Transform,
+        rects: Vec<(Rectangle<i32, Logical>, [f32; 4])>,
+        damage: Vec<Rectangle<i32, Logical>>,
+    ) -> Self {
+        Self {
+            pipeline,
+            output_size,
+            mode_size,
+            transform,
+            rects,
+            damage,
+        }
+    }
+}
+
+/// Rectangles of `rect` that are not covered by `cover`
+pub fn subtract(
+    rect: Rectangle<i32, Logical>,
+    cover: Rectangle<i32, Logical>,
+) -> Vec<Rectangle<i32, Logical>> {
+    if !rect.overlaps(cover) {
+        return vec![rect];
+    }
+
+    let (left, top) = (rect.loc.x, rect.loc.y);
+    let (right, bottom) = (left + rect.size.w, top + rect.size.h);
+    let cover_left = cover.loc.x.max(left);
+    let cover_top = cover.loc.y.max(top);
+    let cover_right = (cover.loc.x + cover.size.w).min(right);
+    let cover_bottom = (cover.loc.y + cover.size.h).min(bottom);
+
+    let pieces = [
+        (left, top, right, cover_top),
+        (left, cover_bottom, right, bottom),
+        (left, cover_top, cover_left, cover_bottom),
+        (cover_right, cover_top, right, cover_bottom),
+    ];
+
+    pieces
+        .iter()
+        .filter(|(x1, y1, x2, y2)| x2 > x1 && y2 > y1)
+        .map(|(x1, y1, x2, y2)| Rectangle::from_loc_and_size((*x1, *y1), (x2 - x1, y2 - y1)))
+        .collect()
+}
+
+/// Overlap of two rectangles, if any
+pub fn intersection(
+    a: Rectangle<i32, Logical>,
+    b: Rectangle<i32, Logical>,
+) -> Option<Rectangle<i32, Logical>> {
+    let x1 = a.loc.x.max(b.loc.x);
+    let y1 = a.loc.y.max(b.loc.y);
+    let x2 = (a.loc.x + a.size.w).min(b.loc.x + b.size.w);
+    let y2 = (a.loc.y + a.size.h).min(b.loc.y + b.size.h);
+
+    if x2 > x1 && y2 > y1 {
+        Some(Rectangle::from_loc_and_size((x1, y1), (x2 - x1, y2 - y1)))
+    } else {
+        None
+    }
+}
+
+impl RenderElement<Gles2Renderer, Gles2Frame, Gles2Error, Gles2Texture> for BorderElement {
+    fn id(&self) -> usize {
+        1
+    }
+
+    fn geometry(&self) -> Rectangle<i32, Logical> {
+        Rectangle::from_loc_and_size((0, 0), self.output_size)
+    }
+
+    fn accumulated_damage(
+        &self,
+        _: Option<SpaceOutputTuple<'_, '_>>,
+    ) -> Vec<Rectangle<i32, Logical>> {
+        self.damage.clone()
+    }
+
+    fn draw(
+        &self,
+        renderer: &mut Gles2Renderer,
+        _frame: &mut Gles2Frame,
+        scale: f64,
+        _location: Point<i32, Logical>,
+        damage: &[Rectangle<i32, Logical>],
+        _log: &slog::Logger,
+    ) -> Result<(), Gles2Error> {
+        renderer.with_context(|_renderer, gl| {
+            for (rect, color) in self.rects.iter() {
+                for damage in damage {
+                    if let Some(rect) = intersection(*rect, *damage) {
+                        self.pipeline.render(
+                            gl,
+                            self.mode_size,
+                            rect.to_f64().to_physical(scale),
+                            self.transform,
+                            *color,
+                        );
+                    }
+                }
+            }
+        })
+    }
+}
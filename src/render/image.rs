@@ -0,0 +1,97 @@
+use smithay::{
+    backend::renderer::{
+        gles2::{Gles2Error, Gles2Frame, Gles2Renderer, Gles2Texture},
+        Frame,
+    },
+    desktop::space::{RenderElement, SpaceOutputTuple},
+    utils::{Logical, Point, Rectangle, Size, Transform},
+};
+
+use super::border::intersection;
+
+/// Images of the egui shell of one output, every rectangle is relative to the output
+pub struct ImageElement {
+    output_size: Size<i32, Logical>,
+    /// Texture, where it goes and the part of that which is visible
+    images: Vec<(
+        Gles2Texture,
+        Rectangle<i32, Logical>,
+        Rectangle<i32, Logical>,
+    )>,
+    damage: Vec<Rectangle<i32, Logical>>,
+}
+
+impl ImageElement {
+    pub fn new(
+        output_size: Size<i32, Logical>,
+        images: Vec<(
+            Gles2Texture,
+            Rectangle<i32, Logical>,
+            Rectangle<i32, Logical>,
+        )>,
+        damage: Vec<Rectangle<i32, Logical>>,
+    ) -> Self {
+        Self {
+            output_size,
+            images,
+            damage,
+        }
+    }
+}
+
+impl RenderElement<Gles2Renderer, Gles2Frame, Gles2Error, Gles2Texture> for ImageElement {
+    fn id(&self) -> usize {
+        3
+    }
+
+    fn geometry(&self) -> Rectangle<i32, Logical> {
+        Rectangle::from_loc_and_size((0, 0), self.output_size)
+    }
+
+    fn accumulated_damage(
+        &self,
+        _: Option<SpaceOutputTuple<'_, '_>>,
+    ) -> Vec<Rectangle<i32, Logical>> {
+        self.damage.clone()
+    }
+
+    fn draw(
+        &self,
+        _renderer: &mut Gles2Renderer,
+        frame: &mut Gles2Frame,
+        scale: f64,
+        _location: Point<i32, Logical>,
+        damage: &[Rectangle<i32, Logical>],
+        _log: &slog::Logger,
+    ) -> Result<(), Gles2Error> {
+        for (texture, rect, visible) in self.images.iter() {
+            // Images are uploaded at their logical size, so buffer and logical coordinates match
+            let damage = damage
+                .iter()
+                .filter_map(|damage| intersection(*damage, *visible))
+                .map(|damage| {
+                    Rectangle::from_loc_and_size(damage.loc - rect.loc, damage.size).to_buffer(
+                        1,
+                        Transform::Normal,
+                        &rect.size,
+                    )
+                })
+                .collect::<Vec<_>>();
+            if damage.is_empty() {
+                continue;
+            }
+
+            frame.render_texture_at(
+                texture,
+                rect.loc.to_f64().to_physical(scale).to_i32_round(),
+                1,
+                scale,
+                Transform::Normal,
+                &damage,
+                1.0,
+            )?;
+        }
+
+        Ok(())
+    }
+}
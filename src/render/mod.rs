@@ -1,4 +1,10 @@
+pub mod border;
+pub mod image;
+pub mod quad;
 pub mod renderer;
+pub mod snapshot;
+pub mod texture;
+pub mod transition;
 
 pub mod wayland;
 pub use wayland::*;
@@ -1,35 +1,34 @@
+use std::ffi::CString;
+
 use cgmath::Matrix3;
-use glow::HasContext;
 use smithay::{
-    backend::renderer::{gles2::ffi, Transform},
-    utils::{Physical, Rectangle},
+    backend::renderer::gles2::ffi,
+    utils::{Physical, Rectangle, Size, Transform},
 };
 
-use super::renderer::RenderContext;
-
+/// Solid color rectangles, drawn inside of a `Gles2Frame`
 pub struct QuadPipeline {
-    program: glow::Program,
+    program: ffi::types::GLuint,
 
-    projection: glow::UniformLocation,
-    color: glow::UniformLocation,
-    position: u32, // AtributeLocation,
+    projection: ffi::types::GLint,
+    color: ffi::types::GLint,
+    position: ffi::types::GLuint,
 }
 
 impl QuadPipeline {
-    pub fn new(context: &mut RenderContext) -> Self {
+    pub fn new(gl: &ffi::Gles2) -> Self {
         let program = create_program(
-            context,
+            gl,
             include_str!("./shaders/quad.vert"),
             include_str!("./shaders/quad.frag"),
         );
 
-        let gl = context.glow;
-
+        let location = |name: &str| CString::new(name).unwrap();
         let (projection, color, position) = unsafe {
             (
-                gl.get_uniform_location(program, "projection").unwrap(),
-                gl.get_uniform_location(program, "color").unwrap(),
-                gl.get_attrib_location(program, "position").unwrap(),
+                gl.GetUniformLocation(program, location("projection").as_ptr()),
+                gl.GetUniformLocation(program, location("color").as_ptr()),
+                gl.GetAttribLocation(program, location("position").as_ptr()),
             )
         };
 
@@ -37,59 +36,31 @@ impl QuadPipeline {
             program,
 
             projection,
-            position,
+            position: position as _,
             color,
         }
     }
 
+    /// Draw `quad_rect`, relative to the output, with a non premultiplied color
     pub fn render(
         &self,
-        output_geometry: Rectangle<f64, Physical>,
-        mut quad_rect: Rectangle<f64, Physical>,
+        gl: &ffi::Gles2,
+        output_size: Size<i32, Physical>,
+        quad_rect: Rectangle<f64, Physical>,
         transform: Transform,
-        context: &RenderContext,
-        alpha: f32,
+        color: [f32; 4],
     ) {
-        quad_rect.loc.x -= output_geometry.loc.x;
-
-        let glow = context.glow;
-        let gles = context.gles;
-
-        let screen = Matrix3 {
-            x: [2.0 / output_geometry.size.w as f32, 0.0, 0.0].into(),
-            y: [0.0, -2.0 / output_geometry.size.h as f32, 0.0].into(),
-            z: [-1.0, 1.0, 1.0].into(),
-        };
-
-        let x = quad_rect.loc.x as f32;
-        let y = quad_rect.loc.y as f32;
-
-        let w = quad_rect.size.w as f32;
-        let h = quad_rect.size.h as f32;
-
-        let quad = Matrix3 {
-            x: [w, 0.0, 0.0].into(),
-            y: [0.0, h, 0.0].into(),
-            z: [x, y, 1.0].into(),
-        };
-
         unsafe {
-            glow.use_program(Some(self.program));
+            gl.UseProgram(self.program);
 
-            let mat = transform.matrix() * screen * quad;
+            let mat = projection(output_size, quad_rect, transform);
             let mat: &[f32; 9] = mat.as_ref();
 
-            glow.uniform_matrix_3_f32_slice(Some(&self.projection), false, mat);
+            gl.UniformMatrix3fv(self.projection, 1, ffi::FALSE, mat.as_ptr());
 
-            glow.uniform_4_f32(
-                Some(&self.color),
-                26.0 / 255.0,
-                95.0 / 255.0,
-                205.0 / 255.0,
-                alpha,
-            );
+            gl.Uniform4f(self.color, color[0], color[1], color[2], color[3]);
 
-            gles.VertexAttribPointer(
+            gl.VertexAttribPointer(
                 self.position,
                 2,
                 ffi::FLOAT,
@@ -98,60 +69,113 @@ impl QuadPipeline {
                 VERTS.as_ptr() as *const _,
             );
 
-            glow.enable_vertex_attrib_array(self.position);
+            gl.EnableVertexAttribArray(self.position);
 
-            glow.draw_arrays(glow::TRIANGLE_STRIP, 0, 4);
+            gl.DrawArrays(ffi::TRIANGLE_STRIP, 0, 4);
 
-            glow.disable_vertex_attrib_array(self.position);
-            glow.use_program(None);
+            gl.DisableVertexAttribArray(self.position);
+            gl.UseProgram(0);
         }
     }
 }
 
-static VERTS: [ffi::types::GLfloat; 8] = [
+/// Maps the unit square onto `rect` of an output with the given size and transform
+pub(super) fn projection(
+    output_size: Size<i32, Physical>,
+    rect: Rectangle<f64, Physical>,
+    transform: Transform,
+) -> Matrix3<f32> {
+    let screen = Matrix3 {
+        x: [2.0 / output_size.w as f32, 0.0, 0.0].into(),
+        y: [0.0, -2.0 / output_size.h as f32, 0.0].into(),
+        z: [-1.0, 1.0, 1.0].into(),
+    };
+
+    let x = rect.loc.x as f32;
+    let y = rect.loc.y as f32;
+
+    let w = rect.size.w as f32;
+    let h = rect.size.h as f32;
+
+    let quad = Matrix3 {
+        x: [w, 0.0, 0.0].into(),
+        y: [0.0, h, 0.0].into(),
+        z: [x, y, 1.0].into(),
+    };
+
+    transform.matrix() * screen * quad
+}
+
+pub(super) static VERTS: [ffi::types::GLfloat; 8] = [
     1.0, 0.0, // top right
     0.0, 0.0, // top left
     1.0, 1.0, // bottom right
     0.0, 1.0, // bottom left
 ];
 
-fn create_program(
-    context: &mut RenderContext,
+unsafe fn compile_shader(
+    gl: &ffi::Gles2,
+    shader_type: ffi::types::GLenum,
+    source: &str,
+) -> ffi::types::GLuint {
+    let shader = gl.CreateShader(shader_type);
+    gl.ShaderSource(
+        shader,
+        1,
+        &source.as_ptr() as *const *const u8 as *const *const ffi::types::GLchar,
+        &(source.len() as ffi::types::GLint) as *const _,
+    );
+    gl.CompileShader(shader);
+
+    let mut status = ffi::FALSE as ffi::types::GLint;
+    gl.GetShaderiv(shader, ffi::COMPILE_STATUS, &mut status as *mut _);
+    if status == ffi::FALSE as ffi::types::GLint {
+        panic!("{}", shader_info_log(gl, shader));
+    }
+
+    shader
+}
+
+unsafe fn shader_info_log(gl: &ffi::Gles2, shader: ffi::types::GLuint) -> String {
+    let mut len = 0;
+    gl.GetShaderiv(shader, ffi::INFO_LOG_LENGTH, &mut len as *mut _);
+
+    let mut log = vec![0u8; len.max(1) as usize];
+    gl.GetShaderInfoLog(
+        shader,
+        len,
+        std::ptr::null_mut(),
+        log.as_mut_ptr() as *mut ffi::types::GLchar,
+    );
+    String::from_utf8_lossy(&log).into_owned()
+}
+
+pub(super) fn create_program(
+    gl: &ffi::Gles2,
     vertex_shader_source: &str,
     fragment_shader_source: &str,
-) -> glow::Program {
-    let gl = &context.glow;
+) -> ffi::types::GLuint {
     unsafe {
-        let program = gl.create_program().expect("Cannot create program");
+        let program = gl.CreateProgram();
 
-        let shader_sources = [
-            (glow::VERTEX_SHADER, vertex_shader_source),
-            (glow::FRAGMENT_SHADER, fragment_shader_source),
+        let shaders = [
+            compile_shader(gl, ffi::VERTEX_SHADER, vertex_shader_source),
+            compile_shader(gl, ffi::FRAGMENT_SHADER, fragment_shader_source),
         ];
-
-        let mut shaders = Vec::with_capacity(shader_sources.len());
-
-        for (shader_type, shader_source) in shader_sources.iter() {
-            let shader = gl
-                .create_shader(*shader_type)
-                .expect("Cannot create shader");
-            gl.shader_source(shader, shader_source);
-            gl.compile_shader(shader);
-            if !gl.get_shader_compile_status(shader) {
-                panic!("{}", gl.get_shader_info_log(shader));
-            }
-            gl.attach_shader(program, shader);
-            shaders.push(shader);
+        for shader in shaders.iter() {
+            gl.AttachShader(program, *shader);
         }
 
-        gl.link_program(program);
-        if !gl.get_program_link_status(program) {
-            panic!("{}", gl.get_program_info_log(program));
+        gl.LinkProgram(program);
+        let mut status = ffi::FALSE as ffi::types::GLint;
+        gl.GetProgramiv(program, ffi::LINK_STATUS, &mut status as *mut _);
+        if status == ffi::FALSE as ffi::types::GLint {
+            panic!("Failed to link the shader program");
         }
 
-        for shader in shaders {
-            gl.detach_shader(program, shader);
-            gl.delete_shader(shader);
+        for shader in shaders.iter() {
+            gl.DetachShader(program, *shader);
+            gl.DeleteShader(*shader);
         }
 
         program
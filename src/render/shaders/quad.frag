@@ -2,8 +2,7 @@
 
 precision mediump float;
 varying vec4 v_color;
-varying vec2 v_texcoord;
 
 void main() {
-	gl_FragColor = v_color * v_color.w;
-};
\ No newline at end of file
+	gl_FragColor = vec4(v_color.rgb * v_color.a, v_color.a);
+}
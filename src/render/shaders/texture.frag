@@ -0,0 +1,13 @@
+#version 100
+
+precision mediump float;
+uniform sampler2D tex;
+uniform float alpha;
+
+varying vec2 v_tex_coords;
+
+void main() {
+	// Nothing was drawn outside of the part of the viewport the texture holds
+	vec2 inside = step(vec2(0.0), v_tex_coords) * step(v_tex_coords, vec2(1.0));
+	gl_FragColor = texture2D(tex, v_tex_coords) * alpha * inside.x * inside.y;
+}
@@ -0,0 +1,16 @@
+#version 100
+
+uniform mat3 projection;
+uniform mat3 tex_projection;
+uniform vec4 tex_area;
+
+attribute vec2 position;
+
+varying vec2 v_tex_coords;
+
+void main() {
+	gl_Position = vec4(projection * vec3(position, 1.0), 1.0);
+	// The offscreen texture holds the part tex_area of the viewport
+	vec2 viewport_coords = (tex_projection * vec3(position, 1.0)).xy * 0.5 + 0.5;
+	v_tex_coords = (viewport_coords - tex_area.xy) / tex_area.zw;
+}
@@ -0,0 +1,160 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use smithay::{
+    backend::renderer::{
+        gles2::{Gles2Error, Gles2Frame, Gles2Renderer, Gles2Texture},
+        utils::draw_surface_tree,
+    },
+    desktop::{
+        self,
+        space::{RenderElement, SpaceOutputTuple},
+    },
+    reexports::wayland_server::protocol::wl_surface::WlSurface,
+    utils::{Logical, Physical, Point, Rectangle, Size, Transform},
+};
+
+use super::texture::Offscreen;
+
+/// Last look of a window on one output
+#[derive(Debug)]
+pub struct Snapshot {
+    pub window: desktop::Window,
+    /// Area and origin of the toplevel surface, relative to the output, and serial of the
+    /// commit drawn
+    state: Option<(Rectangle<i32, Logical>, Point<i32, Logical>, u64)>,
+    pub offscreen: Option<Offscreen>,
+}
+
+/// Window whose snapshot is kept up to date, every location is relative to the output
+#[derive(Debug, Clone)]
+pub struct SnapshotSource {
+    pub window: desktop::Window,
+    pub surface: WlSurface,
+    /// Part of the output the window covers, the only part kept
+    pub area: Rectangle<i32, Logical>,
+    /// Origin of the toplevel surface
+    pub surface_loc: Point<i32, Logical>,
+    /// Serial of the last commit of the window
+    pub serial: u64,
+}
+
+/// Draws every window that changed since its last snapshot into its own offscreen, holding
+/// the part of the output the window covers, so it can still be drawn once its client
+/// destroyed it. Snapshots of windows that can no longer close with an animation are dropped.
+/// Nothing ends up on the output.
+pub struct SnapshotElement {
+    output_size: Size<i32, Logical>,
+    mode_size: Size<i32, Physical>,
+    transform: Transform,
+    snapshots: Rc<RefCell<Vec<Snapshot>>>,
+    sources: Vec<SnapshotSource>,
+    /// Windows that are gone but whose snapshot is still drawn
+    closing: Vec<desktop::Window>,
+}
+
+impl SnapshotElement {
+    pub fn new(
+        output_size: Size<i32, Logical>,
+        mode_size: Size<i32, Physical>,
+        transform: Transform,
+        snapshots: Rc<RefCell<Vec<Snapshot>>>,
+        sources: Vec<SnapshotSource>,
+        closing: Vec<desktop::Window>,
+    ) -> Self {
+        Self {
+            output_size,
+            mode_size,
+            transform,
+            snapshots,
+            sources,
+            closing,
+        }
+    }
+}
+
+impl RenderElement<Gles2Renderer, Gles2Frame, Gles2Error, Gles2Texture> for SnapshotElement {
+    fn id(&self) -> usize {
+        2
+    }
+
+    fn geometry(&self) -> Rectangle<i32, Logical> {
+        Rectangle::from_loc_and_size((0, 0), self.output_size)
+    }
+
+    fn accumulated_damage(
+        &self,
+        _: Option<SpaceOutputTuple<'_, '_>>,
+    ) -> Vec<Rectangle<i32, Logical>> {
+        // A new snapshot only follows a commit, which damages the window anyway
+        Vec::new()
+    }
+
+    fn draw(
+        &self,
+        renderer: &mut Gles2Renderer,
+        frame: &mut Gles2Frame,
+        scale: f64,
+        _location: Point<i32, Logical>,
+        _damage: &[Rectangle<i32, Logical>],
+        log: &slog::Logger,
+    ) -> Result<(), Gles2Error> {
+        let mut snapshots = self.snapshots.borrow_mut();
+
+        let (kept, stale): (Vec<_>, Vec<_>) = snapshots.drain(..).partition(|snapshot| {
+            self.closing.contains(&snapshot.window)
+                || self
+                    .sources
+                    .iter()
+                    .any(|source| source.window == snapshot.window)
+        });
+        *snapshots = kept;
+        renderer.with_context(|_, gl| {
+            for offscreen in stale.into_iter().filter_map(|snapshot| snapshot.offscreen) {
+                offscreen.delete(gl);
+            }
+        })?;
+
+        for source in self.sources.iter() {
+            let index = match snapshots.iter().position(|s| s.window == source.window) {
+                Some(index) => index,
+                None => {
+                    snapshots.push(Snapshot {
+                        window: source.window.clone(),
+                        state: None,
+                        offscreen: None,
+                    });
+                    snapshots.len() - 1
+                }
+            };
+            let snapshot = &mut snapshots[index];
+
+            let state = Some((source.area, source.surface_loc, source.serial));
+            if snapshot.state == state {
+                continue;
+            }
+
+            let area = source.area.to_f64().to_physical(scale);
+            let mut offscreen = snapshot.offscreen;
+            let previous = renderer.with_context(|_, gl| {
+                Offscreen::bind_rect(gl, &mut offscreen, self.mode_size, area, self.transform)
+            })?;
+            let drawn = draw_surface_tree(
+                renderer,
+                frame,
+                &source.surface,
+                scale,
+                source.surface_loc,
+                &[source.area],
+                log,
+            );
+            renderer.with_context(|_, gl| Offscreen::unbind(gl, previous))?;
+            snapshot.offscreen = offscreen;
+            drawn?;
+
+            snapshot.state = state;
+        }
+
+        Ok(())
+    }
+}
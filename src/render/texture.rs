@@ -0,0 +1,320 @@
+use std::cell::Cell;
+use std::ffi::CString;
+
+use cgmath::Vector3;
+use smithay::{
+    backend::renderer::gles2::ffi,
+    utils::{Physical, Rectangle, Size, Transform},
+};
+
+use super::quad::{create_program, projection, VERTS};
+
+/// Framebuffer and its texture, holding a part of the viewport they got created for
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Offscreen {
+    framebuffer: ffi::types::GLuint,
+    texture: ffi::types::GLuint,
+    viewport: (i32, i32),
+    /// Part of the viewport held, as x, y, width and height in pixels from the bottom left
+    part: [i32; 4],
+}
+
+/// Framebuffer and viewport to go back to once done drawing offscreen
+#[derive(Debug, Clone, Copy)]
+pub struct Previous {
+    framebuffer: ffi::types::GLuint,
+    viewport: [i32; 4],
+}
+
+impl Offscreen {
+    /// Redirect drawing into the cleared offscreen, which gets (re)created for the current
+    /// viewport when needed
+    pub fn bind(gl: &ffi::Gles2, offscreen: &mut Option<Offscreen>) -> Previous {
+        let viewport = current_viewport(gl);
+        Self::bind_part(gl, offscreen, [0, 0, viewport[2], viewport[3]])
+    }
+
+    /// Same as `bind`, only keeping the part of the viewport `rect` of the output ends up in
+    pub fn bind_rect(
+        gl: &ffi::Gles2,
+        offscreen: &mut Option<Offscreen>,
+        output_size: Size<i32, Physical>,
+        rect: Rectangle<f64, Physical>,
+        transform: Transform,
+    ) -> Previous {
+        let viewport = current_viewport(gl);
+        let (width, height) = (viewport[2] as f32, viewport[3] as f32);
+
+        let mat = projection(output_size, rect, transform);
+        let (mut x0, mut y0, mut x1, mut y1) = (width, height, 0.0f32, 0.0f32);
+        for corner in VERTS.chunks(2) {
+            let ndc = mat * Vector3::new(corner[0], corner[1], 1.0);
+            let (x, y) = ((ndc.x * 0.5 + 0.5) * width, (ndc.y * 0.5 + 0.5) * height);
+            x0 = x0.min(x);
+            y0 = y0.min(y);
+            x1 = x1.max(x);
+            y1 = y1.max(y);
+        }
+
+        let x0 = (x0.floor() as i32).max(0);
+        let y0 = (y0.floor() as i32).max(0);
+        let x1 = (x1.ceil() as i32).min(viewport[2]);
+        let y1 = (y1.ceil() as i32).min(viewport[3]);
+
+        Self::bind_part(gl, offscreen, [x0, y0, (x1 - x0).max(1), (y1 - y0).max(1)])
+    }
+
+    fn bind_part(gl: &ffi::Gles2, offscreen: &mut Option<Offscreen>, part: [i32; 4]) -> Previous {
+        unsafe {
+            let mut previous = 0;
+            gl.GetIntegerv(ffi::FRAMEBUFFER_BINDING, &mut previous);
+
+            let viewport = current_viewport(gl);
+            let size = (viewport[2], viewport[3]);
+
+            let (framebuffer, texture) = match *offscreen {
+                Some(current) if current.part[2..] == part[2..] => {
+                    (current.framebuffer, current.texture)
+                }
+                outdated => {
+                    if let Some(outdated) = outdated {
+                        outdated.delete(gl);
+                    }
+                    create_offscreen(gl, (part[2], part[3]))
+                }
+            };
+            *offscreen = Some(Offscreen {
+                framebuffer,
+                texture,
+                viewport: size,
+                part,
+            });
+
+            gl.BindFramebuffer(ffi::FRAMEBUFFER, framebuffer);
+            // The part held ends up at the origin of the framebuffer
+            gl.Viewport(-part[0], -part[1], size.0, size.1);
+            gl.Disable(ffi::SCISSOR_TEST);
+            gl.ClearColor(0.0, 0.0, 0.0, 0.0);
+            gl.Clear(ffi::COLOR_BUFFER_BIT);
+
+            Previous {
+                framebuffer: previous as _,
+                viewport,
+            }
+        }
+    }
+
+    pub fn unbind(gl: &ffi::Gles2, previous: Previous) {
+        let [x, y, width, height] = previous.viewport;
+        unsafe {
+            gl.BindFramebuffer(ffi::FRAMEBUFFER, previous.framebuffer);
+            gl.Viewport(x, y, width, height);
+        }
+    }
+
+    pub fn delete(self, gl: &ffi::Gles2) {
+        unsafe {
+            gl.DeleteFramebuffers(1, &self.framebuffer);
+            gl.DeleteTextures(1, &self.texture);
+        }
+    }
+
+    /// Part of the viewport held, relative to the viewport size
+    fn tex_area(&self) -> [f32; 4] {
+        let (width, height) = (self.viewport.0 as f32, self.viewport.1 as f32);
+        [
+            self.part[0] as f32 / width,
+            self.part[1] as f32 / height,
+            self.part[2] as f32 / width,
+            self.part[3] as f32 / height,
+        ]
+    }
+}
+
+fn current_viewport(gl: &ffi::Gles2) -> [i32; 4] {
+    let mut viewport = [0; 4];
+    unsafe {
+        gl.GetIntegerv(ffi::VIEWPORT, viewport.as_mut_ptr());
+    }
+    viewport
+}
+
+/// Draws a part of an offscreen copy of the frame somewhere else, scaled and faded
+pub struct TexturePipeline {
+    program: ffi::types::GLuint,
+
+    projection: ffi::types::GLint,
+    tex_projection: ffi::types::GLint,
+    tex_area: ffi::types::GLint,
+    tex: ffi::types::GLint,
+    alpha: ffi::types::GLint,
+    position: ffi::types::GLuint,
+
+    offscreen: Cell<Option<Offscreen>>,
+}
+
+impl TexturePipeline {
+    pub fn new(gl: &ffi::Gles2) -> Self {
+        let program = create_program(
+            gl,
+            include_str!("./shaders/texture.vert"),
+            include_str!("./shaders/texture.frag"),
+        );
+
+        let location = |name: &str| CString::new(name).unwrap();
+        let (projection, tex_projection, tex_area, tex, alpha, position) = unsafe {
+            (
+                gl.GetUniformLocation(program, location("projection").as_ptr()),
+                gl.GetUniformLocation(program, location("tex_projection").as_ptr()),
+                gl.GetUniformLocation(program, location("tex_area").as_ptr()),
+                gl.GetUniformLocation(program, location("tex").as_ptr()),
+                gl.GetUniformLocation(program, location("alpha").as_ptr()),
+                gl.GetAttribLocation(program, location("position").as_ptr()),
+            )
+        };
+
+        Self {
+            program,
+
+            projection,
+            tex_projection,
+            tex_area,
+            tex,
+            alpha,
+            position: position as _,
+
+            offscreen: Cell::new(None),
+        }
+    }
+
+    /// Redirect drawing into the cleared offscreen texture, returns what to go back to
+    pub fn bind_offscreen(&self, gl: &ffi::Gles2) -> Previous {
+        let mut offscreen = self.offscreen.get();
+        let previous = Offscreen::bind(gl, &mut offscreen);
+        self.offscreen.set(offscreen);
+        previous
+    }
+
+    pub fn unbind_offscreen(&self, gl: &ffi::Gles2, previous: Previous) {
+        Offscreen::unbind(gl, previous);
+    }
+
+    /// Draw `src` of the offscreen texture at `dst`, both relative to the output
+    pub fn render(
+        &self,
+        gl: &ffi::Gles2,
+        output_size: Size<i32, Physical>,
+        src: Rectangle<f64, Physical>,
+        dst: Rectangle<f64, Physical>,
+        transform: Transform,
+        alpha: f32,
+    ) {
+        if let Some(offscreen) = self.offscreen.get() {
+            self.render_from(gl, &offscreen, output_size, src, dst, transform, alpha);
+        }
+    }
+
+    /// Same as `render`, from another offscreen of the same output. Outside of the part of the
+    /// output it holds, nothing is drawn
+    #[allow(clippy::too_many_arguments)]
+    pub fn render_from(
+        &self,
+        gl: &ffi::Gles2,
+        offscreen: &Offscreen,
+        output_size: Size<i32, Physical>,
+        src: Rectangle<f64, Physical>,
+        dst: Rectangle<f64, Physical>,
+        transform: Transform,
+        alpha: f32,
+    ) {
+        let texture = offscreen.texture;
+        let tex_area = offscreen.tex_area();
+
+        unsafe {
+            gl.UseProgram(self.program);
+
+            let mat = projection(output_size, dst, transform);
+            let mat: &[f32; 9] = mat.as_ref();
+            gl.UniformMatrix3fv(self.projection, 1, ffi::FALSE, mat.as_ptr());
+
+            let tex_mat = projection(output_size, src, transform);
+            let tex_mat: &[f32; 9] = tex_mat.as_ref();
+            gl.UniformMatrix3fv(self.tex_projection, 1, ffi::FALSE, tex_mat.as_ptr());
+            gl.Uniform4f(
+                self.tex_area,
+                tex_area[0],
+                tex_area[1],
+                tex_area[2],
+                tex_area[3],
+            );
+
+            gl.ActiveTexture(ffi::TEXTURE0);
+            gl.BindTexture(ffi::TEXTURE_2D, texture);
+            gl.Uniform1i(self.tex, 0);
+            gl.Uniform1f(self.alpha, alpha);
+
+            gl.VertexAttribPointer(
+                self.position,
+                2,
+                ffi::FLOAT,
+                ffi::FALSE,
+                0,
+                VERTS.as_ptr() as *const _,
+            );
+
+            gl.EnableVertexAttribArray(self.position);
+
+            gl.DrawArrays(ffi::TRIANGLE_STRIP, 0, 4);
+
+            gl.DisableVertexAttribArray(self.position);
+            gl.BindTexture(ffi::TEXTURE_2D, 0);
+            gl.UseProgram(0);
+        }
+    }
+}
+
+unsafe fn create_offscreen(
+    gl: &ffi::Gles2,
+    (width, height): (i32, i32),
+) -> (ffi::types::GLuint, ffi::types::GLuint) {
+    let mut texture = 0;
+    gl.GenTextures(1, &mut texture);
+    gl.BindTexture(ffi::TEXTURE_2D, texture);
+    gl.TexParameteri(ffi::TEXTURE_2D, ffi::TEXTURE_MIN_FILTER, ffi::LINEAR as i32);
+    gl.TexParameteri(ffi::TEXTURE_2D, ffi::TEXTURE_MAG_FILTER, ffi::LINEAR as i32);
+    gl.TexParameteri(
+        ffi::TEXTURE_2D,
+        ffi::TEXTURE_WRAP_S,
+        ffi::CLAMP_TO_EDGE as i32,
+    );
+    gl.TexParameteri(
+        ffi::TEXTURE_2D,
+        ffi::TEXTURE_WRAP_T,
+        ffi::CLAMP_TO_EDGE as i32,
+    );
+    gl.TexImage2D(
+        ffi::TEXTURE_2D,
+        0,
+        ffi::RGBA as i32,
+        width,
+        height,
+        0,
+        ffi::RGBA,
+        ffi::UNSIGNED_BYTE,
+        std::ptr::null(),
+    );
+    gl.BindTexture(ffi::TEXTURE_2D, 0);
+
+    let mut framebuffer = 0;
+    gl.GenFramebuffers(1, &mut framebuffer);
+    gl.BindFramebuffer(ffi::FRAMEBUFFER, framebuffer);
+    gl.FramebufferTexture2D(
+        ffi::FRAMEBUFFER,
+        ffi::COLOR_ATTACHMENT0,
+        ffi::TEXTURE_2D,
+        texture,
+        0,
+    );
+
+    (framebuffer, texture)
+}
@@ -0,0 +1,189 @@
+use smithay::{
+    backend::renderer::{
+        gles2::{Gles2Error, Gles2Frame, Gles2Renderer},
+        utils::draw_surface_tree,
+        Frame, Renderer,
+    },
+    desktop::{self, space::DynamicRenderElements},
+    utils::{Logical, Physical, Point, Rectangle, Size, Transform},
+    wayland::{output::Output as SmithayOutput, shell::wlr_layer::Layer as WlrLayer},
+};
+
+use super::texture::{Offscreen, TexturePipeline};
+use crate::animation::{Content, Sample};
+
+/// One entry of the window stack of an output, every location is relative to the output
+#[derive(Debug, Clone)]
+pub enum Layered {
+    /// Window drawn as it is, from the origin of its toplevel surface
+    Window(desktop::Window, Point<i32, Logical>),
+    /// Window drawn by its animation
+    Transition(Sample),
+}
+
+/// Output properties needed to draw it
+pub struct Target<'a> {
+    pub output: &'a SmithayOutput,
+    pub output_size: Size<i32, Logical>,
+    pub mode_size: Size<i32, Physical>,
+    pub transform: Transform,
+    pub scale: f64,
+}
+
+/// Everything, whatever the coordinates the damage is given in are relative to
+fn everything() -> Rectangle<i32, Logical> {
+    Rectangle::from_loc_and_size((-(1 << 24), -(1 << 24)), (1 << 25, 1 << 25))
+}
+
+/// Draw the output like `Space::render_output` does, with the animated windows drawn by their
+/// transition at their place in the stack instead. The whole output is redrawn.
+pub fn render_output(
+    renderer: &mut Gles2Renderer,
+    target: &Target,
+    pipeline: &TexturePipeline,
+    stack: &[Layered],
+    snapshot: impl Fn(&desktop::Window) -> Option<Offscreen>,
+    elements: &[DynamicRenderElements<Gles2Renderer>],
+    clear_color: [f32; 4],
+) -> Result<Vec<Rectangle<i32, Logical>>, Gles2Error> {
+    let log = slog_scope::logger();
+    let scale = target.scale;
+    let whole = Rectangle::from_loc_and_size((0, 0), target.output_size);
+
+    let layers = |layers: &[WlrLayer]| {
+        let map = desktop::layer_map_for_output(target.output);
+        layers
+            .iter()
+            .flat_map(|layer| map.layers_on(*layer))
+            .map(|layer| (layer.clone(), map.layer_geometry(layer).loc))
+            .collect::<Vec<_>>()
+    };
+    let below = layers(&[WlrLayer::Background, WlrLayer::Bottom]);
+    let above = layers(&[WlrLayer::Top, WlrLayer::Overlay]);
+
+    renderer.render(target.mode_size, target.transform, |renderer, frame| {
+        frame.clear(
+            clear_color,
+            &[Rectangle::from_loc_and_size((0, 0), target.mode_size)],
+        )?;
+
+        for (layer, location) in below.iter() {
+            desktop::draw_layer(
+                renderer,
+                frame,
+                layer,
+                scale,
+                *location,
+                &[everything()],
+                &log,
+            )?;
+        }
+
+        for layered in stack {
+            match layered {
+                Layered::Window(window, location) => {
+                    desktop::draw_window(
+                        renderer,
+                        frame,
+                        window,
+                        scale,
+                        *location,
+                        &[everything()],
+                        &log,
+                    )?;
+                }
+                Layered::Transition(sample) => {
+                    draw_sample(renderer, frame, target, pipeline, sample, &snapshot, &log)?;
+                }
+            }
+        }
+
+        for (layer, location) in above.iter() {
+            desktop::draw_layer(
+                renderer,
+                frame,
+                layer,
+                scale,
+                *location,
+                &[everything()],
+                &log,
+            )?;
+        }
+
+        for element in elements {
+            let geometry = element.geometry();
+            element.draw(
+                renderer,
+                frame,
+                scale,
+                geometry.loc,
+                &[Rectangle::from_loc_and_size((0, 0), geometry.size)],
+                &log,
+            )?;
+        }
+
+        Ok(())
+    })??;
+
+    Ok(vec![whole])
+}
+
+/// Draw the sample unchanged offscreen first, then moved, scaled and faded from there.
+/// Closed windows are already in the snapshot the output took of them
+fn draw_sample(
+    renderer: &mut Gles2Renderer,
+    frame: &mut Gles2Frame,
+    target: &Target,
+    pipeline: &TexturePipeline,
+    sample: &Sample,
+    snapshot: &impl Fn(&desktop::Window) -> Option<Offscreen>,
+    log: &slog::Logger,
+) -> Result<(), Gles2Error> {
+    let scale = target.scale;
+    let src = sample.area.to_f64().to_physical(scale);
+    let dst = sample.target.to_physical(scale);
+
+    match &sample.content {
+        Content::Surface(surface) => {
+            let whole = Rectangle::from_loc_and_size((0, 0), target.output_size);
+
+            let previous = renderer.with_context(|_, gl| pipeline.bind_offscreen(gl))?;
+            let drawn = draw_surface_tree(
+                renderer,
+                frame,
+                surface,
+                scale,
+                sample.surface_loc,
+                &[whole],
+                log,
+            );
+            renderer.with_context(|_, gl| pipeline.unbind_offscreen(gl, previous))?;
+            drawn?;
+
+            renderer.with_context(|_, gl| {
+                pipeline.render(
+                    gl,
+                    target.mode_size,
+                    src,
+                    dst,
+                    target.transform,
+                    sample.alpha,
+                )
+            })
+        }
+        Content::Snapshot(window) => match snapshot(window) {
+            Some(offscreen) => renderer.with_context(|_, gl| {
+                pipeline.render_from(
+                    gl,
+                    &offscreen,
+                    target.mode_size,
+                    src,
+                    dst,
+                    target.transform,
+                    sample.alpha,
+                )
+            }),
+            None => Ok(()),
+        },
+    }
+}
@@ -1,20 +1,44 @@
+use std::rc::Rc;
+use std::sync::Mutex;
+
 use smithay::{
-    desktop::{self, WindowSurfaceType},
-    reexports::wayland_server::protocol::wl_surface::WlSurface,
-    utils::{Logical, Point},
+    desktop::{self, Kind, WindowSurfaceType},
+    reexports::{
+        wayland_protocols::xdg_shell::server::xdg_toplevel,
+        wayland_server::protocol::wl_surface::WlSurface,
+    },
+    utils::{Logical, Point, Rectangle},
+    wayland::{
+        compositor,
+        output::Output as SmithayOutput,
+        seat::{PointerGrabStartData, PointerHandle},
+        shell::xdg::{ToplevelSurface, XdgToplevelSurfaceRoleAttributes},
+        Serial, SERIAL_COUNTER as SCOUNTER,
+    },
 };
 
 use crate::{
+    config::{
+        eventloop::ConfigEvent,
+        outputs::shell::menu::{action::Action, item::MenuItem, Menu},
+        windows::Decorations,
+    },
     framework::{
-        shell::{ShellEvent, ShellHandler},
-        surface_data::{ResizeData, ResizeState, SurfaceData},
+        shell::{DecorationMode, ShellEvent, ShellHandler},
+        surface_data::{ResizeData, ResizeEdge, ResizeState, SurfaceData},
     },
     grabs::{MoveSurfaceGrab, ResizeSurfaceGrab},
     output_manager::Output,
     state::Anodium,
-    window::Window,
+    window::{
+        decoration::{self, Part},
+        Window,
+    },
+    workspace::Expanded,
 };
 
+const BTN_LEFT: u32 = 0x110;
+
 impl ShellHandler for Anodium {
     fn on_shell_event(&mut self, event: ShellEvent) {
         match event {
@@ -22,7 +46,29 @@ impl ShellHandler for Anodium {
             // Toplevel
             //
             ShellEvent::WindowCreated { window } => {
-                self.workspace.map_window(&window, (0, 0), false);
+                let decorated = window.server_side_decorated();
+                let location = self
+                    .usable_geometry_at(self.input_state.pointer_location)
+                    .map(|area| {
+                        if decorated {
+                            decoration::inner(area).loc
+                        } else {
+                            area.loc
+                        }
+                    })
+                    .unwrap_or_default();
+                self.workspace.map_window(&window, location, false);
+                self.workspace.raise_on_top();
+
+                let animations = &self.config.anodize.animations;
+                if let Some(curve) = animations.open() {
+                    self.workspace.animate_open(
+                        &window,
+                        self.start_time.elapsed(),
+                        curve,
+                        animations.scale(),
+                    );
+                }
             }
 
             ShellEvent::WindowMove {
@@ -35,18 +81,11 @@ impl ShellHandler for Anodium {
 
                 let window = self
                     .workspace
-                    .window_for_surface(toplevel.get_surface().unwrap());
+                    .window_for_surface(toplevel.get_surface().unwrap())
+                    .cloned();
 
                 if let Some(window) = window {
-                    let initial_window_location =
-                        self.workspace.window_geometry(window).unwrap().loc;
-
-                    let grab = MoveSurfaceGrab {
-                        start_data,
-                        window: window.clone(),
-                        initial_window_location,
-                    };
-                    pointer.set_grab(grab, serial);
+                    self.start_move(&pointer, &window, start_data, serial);
                 }
             }
 
@@ -58,32 +97,14 @@ impl ShellHandler for Anodium {
                 serial,
             } => {
                 let pointer = seat.get_pointer().unwrap();
-                let wl_surface = toplevel.get_surface().unwrap();
 
-                let window = self.workspace.window_for_surface(wl_surface);
+                let window = self
+                    .workspace
+                    .window_for_surface(toplevel.get_surface().unwrap())
+                    .cloned();
 
                 if let Some(window) = window {
-                    let geometry = self.workspace.window_geometry(window).unwrap();
-                    let (initial_window_location, initial_window_size) =
-                        (geometry.loc, geometry.size);
-
-                    SurfaceData::with_mut(wl_surface, |data| {
-                        data.resize_state = ResizeState::Resizing(ResizeData {
-                            edges,
-                            initial_window_location,
-                            initial_window_size,
-                        });
-                    });
-
-                    let grab = ResizeSurfaceGrab {
-                        start_data,
-                        window: window.clone(),
-                        edges,
-                        initial_window_size,
-                        last_window_size: initial_window_size,
-                    };
-
-                    pointer.set_grab(grab, serial);
+                    self.start_resize(&pointer, &window, start_data, edges, serial);
                 }
             }
 
@@ -91,11 +112,48 @@ impl ShellHandler for Anodium {
                 window,
                 new_location,
             } => {
-                self.workspace.map_window(&window, new_location, false);
+                if let Some(space) = self.workspace.space_for_window_mut(&window) {
+                    space.map_window(&window, new_location, false);
+                }
             }
 
-            ShellEvent::WindowMaximize { .. } => {}
-            ShellEvent::WindowUnMaximize { .. } => {}
+            ShellEvent::WindowMaximize { toplevel } => {
+                if let Some(window) = toplevel
+                    .get_surface()
+                    .and_then(|surface| self.workspace.window_for_surface(surface))
+                    .cloned()
+                {
+                    self.maximize_window(&window);
+                }
+            }
+            ShellEvent::WindowUnMaximize { toplevel } => {
+                if let Some(window) = toplevel
+                    .get_surface()
+                    .and_then(|surface| self.workspace.window_for_surface(surface))
+                    .cloned()
+                {
+                    self.unmaximize_window(&window);
+                }
+            }
+            ShellEvent::WindowFullscreen { toplevel, output } => {
+                if let Some(window) = toplevel
+                    .get_surface()
+                    .and_then(|surface| self.workspace.window_for_surface(surface))
+                    .cloned()
+                {
+                    let output = output.and_then(|o| SmithayOutput::from_resource(&o));
+                    self.fullscreen_window(&window, output);
+                }
+            }
+            ShellEvent::WindowUnFullscreen { toplevel } => {
+                if let Some(window) = toplevel
+                    .get_surface()
+                    .and_then(|surface| self.workspace.window_for_surface(surface))
+                    .cloned()
+                {
+                    self.unfullscreen_window(&window);
+                }
+            }
 
             //
             // Popup
@@ -128,8 +186,38 @@ impl ShellHandler for Anodium {
                 }
             }
 
+            ShellEvent::WindowDecorationMode { toplevel, mode } => {
+                if let Kind::Xdg(xdg) = toplevel {
+                    let mode = mode.unwrap_or(match self.config.decorations() {
+                        Decorations::Client => DecorationMode::ClientSide,
+                        Decorations::Server => DecorationMode::ServerSide,
+                    });
+
+                    let ret = xdg.with_pending_state(|pending| {
+                        pending.decoration_mode = Some(mode);
+                    });
+                    // Before the initial commit the mode goes out with the initial configure
+                    if ret.is_ok() && initial_configure_sent(&xdg) {
+                        xdg.send_configure();
+                    }
+                }
+            }
+
+            ShellEvent::ShowWindowMenu {
+                toplevel, location, ..
+            } => {
+                if let Some(window) = toplevel
+                    .get_surface()
+                    .and_then(|surface| self.workspace.window_for_surface(surface))
+                    .cloned()
+                {
+                    self.show_window_menu(&window, location);
+                }
+            }
+
             ShellEvent::SurfaceCommit { surface } => {
                 self.workspace.commit(&surface);
+                self.pointer_constraints.commit(&surface);
             }
             _ => {}
         }
@@ -145,6 +233,10 @@ impl Anodium {
         &self,
         point: Point<f64, Logical>,
     ) -> Option<(WlSurface, Point<i32, Logical>)> {
+        if self.shell_menu_open() || self.decoration_under(point).is_some() {
+            return None;
+        }
+
         let window = self.workspace.window_under(point)?;
 
         let window_loc = self.workspace.window_geometry(window).unwrap().loc;
@@ -152,4 +244,326 @@ impl Anodium {
             .surface_under(point - window_loc.to_f64(), WindowSurfaceType::ALL)
             .map(|(s, loc)| (s, loc + window_loc))
     }
+
+    /// Server side decorations under the point, they are inactive while a shell menu is open
+    pub fn decoration_under(&self, point: Point<f64, Logical>) -> Option<(desktop::Window, Part)> {
+        if self.shell_menu_open() {
+            None
+        } else {
+            self.workspace.decoration_under(point)
+        }
+    }
+
+    /// Some shell has a menu open, it gets all the pointer input until the menu closes
+    fn shell_menu_open(&self) -> bool {
+        self.workspace
+            .outputs()
+            .any(|o| Output::wrap(o.clone()).egui_shell().menu_open())
+    }
+
+    pub fn start_move(
+        &mut self,
+        pointer: &PointerHandle,
+        window: &desktop::Window,
+        start_data: PointerGrabStartData,
+        serial: Serial,
+    ) {
+        let initial_window_location = match self.workspace.window_geometry(window) {
+            Some(geometry) => geometry.loc,
+            None => return,
+        };
+
+        let grab = MoveSurfaceGrab {
+            start_data,
+            window: window.clone(),
+            initial_window_location,
+        };
+        pointer.set_grab(grab, serial);
+    }
+
+    pub fn start_resize(
+        &mut self,
+        pointer: &PointerHandle,
+        window: &desktop::Window,
+        start_data: PointerGrabStartData,
+        edges: ResizeEdge,
+        serial: Serial,
+    ) {
+        let geometry = match self.workspace.window_geometry(window) {
+            Some(geometry) => geometry,
+            None => return,
+        };
+        let wl_surface = match window.toplevel().get_surface() {
+            Some(wl_surface) => wl_surface,
+            None => return,
+        };
+        let (initial_window_location, initial_window_size) = (geometry.loc, geometry.size);
+
+        SurfaceData::with_mut(wl_surface, |data| {
+            data.resize_state = ResizeState::Resizing(ResizeData {
+                edges,
+                initial_window_location,
+                initial_window_size,
+            });
+        });
+
+        let grab = ResizeSurfaceGrab {
+            start_data,
+            window: window.clone(),
+            edges,
+            initial_window_size,
+            last_window_size: initial_window_size,
+        };
+
+        pointer.set_grab(grab, serial);
+    }
+
+    /// Grab started from the shell instead of a client, it follows the pointer until the next click
+    fn pointer_start_data(&self) -> PointerGrabStartData {
+        PointerGrabStartData {
+            focus: None,
+            button: BTN_LEFT,
+            location: self.input_state.pointer_location,
+        }
+    }
+
+    pub fn move_with_pointer(&mut self, window: &desktop::Window) {
+        let pointer = self.input_state.pointer.clone();
+        let start_data = self.pointer_start_data();
+        self.start_move(&pointer, window, start_data, SCOUNTER.next_serial());
+    }
+
+    pub fn resize_with_pointer(&mut self, window: &desktop::Window) {
+        let pointer = self.input_state.pointer.clone();
+        let start_data = self.pointer_start_data();
+        self.start_resize(
+            &pointer,
+            window,
+            start_data,
+            ResizeEdge::BOTTOM_RIGHT,
+            SCOUNTER.next_serial(),
+        );
+    }
+
+    /// Menu requested by the client with `xdg_toplevel.show_window_menu`,
+    /// `location` is relative to the window geometry
+    pub fn show_window_menu(&mut self, window: &desktop::Window, location: Point<i32, Logical>) {
+        let geometry = match self.workspace.window_geometry(window) {
+            Some(geometry) => geometry,
+            None => return,
+        };
+        let position = geometry.loc + location;
+        let output = match self.output_at(position.to_f64()) {
+            Some(output) => output,
+            None => return,
+        };
+        let output_geometry = match self.workspace.output_geometry(&output) {
+            Some(output_geometry) => output_geometry,
+            None => return,
+        };
+        let position = position - output_geometry.loc;
+
+        let target = Window::wrap(window.clone());
+        let item = |label: &str, event: ConfigEvent| -> Rc<dyn MenuItem> {
+            Rc::new(Action::new(label, event))
+        };
+
+        let menu = Menu::new(String::new());
+        menu.push(item("Move", ConfigEvent::Move(target.clone())));
+        menu.push(item("Resize", ConfigEvent::Resize(target.clone())));
+        menu.push_separator();
+
+        let expanded = self.workspace.expanded(window);
+        menu.push(if expanded == Some(Expanded::Maximized) {
+            item("Unmaximize", ConfigEvent::Unmaximize(target.clone()))
+        } else {
+            item("Maximize", ConfigEvent::Maximize(target.clone()))
+        });
+        menu.push(item("Minimize", ConfigEvent::Minimize(target.clone())));
+        menu.push(if expanded == Some(Expanded::Fullscreen) {
+            item(
+                "Leave fullscreen",
+                ConfigEvent::Unfullscreen(target.clone()),
+            )
+        } else {
+            item("Fullscreen", ConfigEvent::Fullscreen(target.clone()))
+        });
+
+        let on_top = self.workspace.is_on_top(window);
+        menu.push(item(
+            if on_top {
+                "Disable always on top"
+            } else {
+                "Always on top"
+            },
+            ConfigEvent::AlwaysOnTop(target.clone(), !on_top),
+        ));
+
+        let workspaces = Menu::new("Move to workspace".into());
+        let current = self.workspace.workspace_of_window(window);
+        for name in self.workspace.names().filter(|name| Some(*name) != current) {
+            workspaces.push(item(
+                name,
+                ConfigEvent::MoveToWorkspace(target.clone(), name.to_owned()),
+            ));
+        }
+        workspaces.push(item(
+            "New workspace",
+            ConfigEvent::MoveToWorkspace(target.clone(), self.workspace.next_free_name()),
+        ));
+        menu.push(Rc::new(workspaces));
+
+        self.config.extend_window_menu(&target, &menu);
+
+        menu.push_separator();
+        menu.push(item("Close", ConfigEvent::Close(target)));
+
+        let output = Output::wrap(output);
+        output
+            .egui_shell()
+            .open_window_menu(menu, egui::pos2(position.x as f32, position.y as f32));
+        self.input_state.egui_focus = Some(output);
+    }
+
+    /// Output under the point, falls back to the first output
+    fn output_at(&self, point: Point<f64, Logical>) -> Option<SmithayOutput> {
+        self.workspace
+            .output_under(point)
+            .next()
+            .or_else(|| self.workspace.outputs().next())
+            .cloned()
+    }
+
+    /// Usable area of the output under the point, in global coordinates
+    fn usable_geometry_at(&self, point: Point<f64, Logical>) -> Option<Rectangle<i32, Logical>> {
+        let output = self.output_at(point)?;
+
+        let output_geometry = self.workspace.output_geometry(&output)?;
+        let mut area = Output::wrap(output).usable_area();
+        area.loc = area.loc + output_geometry.loc;
+        Some(area)
+    }
+
+    pub fn maximize_window(&mut self, window: &desktop::Window) {
+        self.expand_window(window, Expanded::Maximized, None);
+    }
+
+    pub fn unmaximize_window(&mut self, window: &desktop::Window) {
+        self.restore_window_geometry(window);
+    }
+
+    pub fn fullscreen_window(&mut self, window: &desktop::Window, output: Option<SmithayOutput>) {
+        self.expand_window(window, Expanded::Fullscreen, output);
+    }
+
+    pub fn unfullscreen_window(&mut self, window: &desktop::Window) {
+        self.restore_window_geometry(window);
+    }
+
+    /// Resize the window to the usable area when maximized, or the whole output when fullscreen
+    fn expand_window(
+        &mut self,
+        window: &desktop::Window,
+        expanded: Expanded,
+        output: Option<SmithayOutput>,
+    ) {
+        let geometry = match self.workspace.window_geometry(window) {
+            Some(geometry) => geometry,
+            None => return,
+        };
+        let center = Point::<i32, Logical>::from((
+            geometry.loc.x + geometry.size.w / 2,
+            geometry.loc.y + geometry.size.h / 2,
+        ));
+        let output = match output.or_else(|| self.output_at(center.to_f64())) {
+            Some(output) => output,
+            None => return,
+        };
+        let output_geometry = match self.workspace.output_geometry(&output) {
+            Some(output_geometry) => output_geometry,
+            None => return,
+        };
+
+        let (area, state) = match expanded {
+            Expanded::Maximized => {
+                let mut area = Output::wrap(output).usable_area();
+                area.loc = area.loc + output_geometry.loc;
+                if Window::wrap(window.clone()).server_side_decorated() {
+                    area = decoration::inner(area);
+                }
+                (area, xdg_toplevel::State::Maximized)
+            }
+            Expanded::Fullscreen => (output_geometry, xdg_toplevel::State::Fullscreen),
+        };
+
+        if let Kind::Xdg(xdg) = window.toplevel() {
+            let ret = xdg.with_pending_state(|pending| {
+                pending.states.set(state);
+                pending.size = Some(area.size);
+            });
+            if ret.is_ok() {
+                xdg.send_configure();
+            }
+        }
+
+        self.workspace.set_expanded(window, expanded, geometry);
+        self.workspace.map_window(window, area.loc, false);
+
+        if expanded == Expanded::Maximized {
+            self.animate_maximize(window, geometry, area);
+        }
+    }
+
+    fn restore_window_geometry(&mut self, window: &desktop::Window) {
+        let geometry = self.workspace.window_geometry(window);
+        let expanded = self.workspace.take_expanded(window);
+        let restore = expanded.map(|(_, restore)| restore);
+
+        if let Kind::Xdg(xdg) = window.toplevel() {
+            let ret = xdg.with_pending_state(|pending| {
+                pending.states.unset(xdg_toplevel::State::Maximized);
+                pending.states.unset(xdg_toplevel::State::Fullscreen);
+                pending.size = restore.map(|restore| restore.size);
+            });
+            if ret.is_ok() {
+                xdg.send_configure();
+            }
+        }
+
+        if let Some(restore) = restore {
+            self.workspace.map_window(window, restore.loc, false);
+        }
+
+        if let (Some((Expanded::Maximized, restore)), Some(geometry)) = (expanded, geometry) {
+            self.animate_maximize(window, geometry, restore);
+        }
+    }
+
+    fn animate_maximize(
+        &mut self,
+        window: &desktop::Window,
+        from: Rectangle<i32, Logical>,
+        to: Rectangle<i32, Logical>,
+    ) {
+        if let Some(curve) = self.config.anodize.animations.maximize() {
+            self.workspace
+                .animate_resize(window, from, to, self.start_time.elapsed(), curve);
+        }
+    }
+}
+
+fn initial_configure_sent(toplevel: &ToplevelSurface) -> bool {
+    toplevel
+        .get_surface()
+        .and_then(|surface| {
+            compositor::with_states(surface, |states| {
+                states
+                    .data_map
+                    .get::<Mutex<XdgToplevelSurfaceRoleAttributes>>()
+                    .map(|attributes| attributes.lock().unwrap().initial_configure_sent)
+            })
+            .ok()
+            .flatten()
+        })
+        .unwrap_or(false)
 }
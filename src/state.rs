@@ -12,7 +12,10 @@ use std::{
 use anodium_protocol::server::AnodiumProtocol;
 use calloop::channel::{self, Channel};
 use smithay::{
-    backend::renderer::gles2::{Gles2Renderer, Gles2Texture},
+    backend::{
+        input::TouchSlot,
+        renderer::gles2::{Gles2Renderer, Gles2Texture},
+    },
     desktop::{
         self,
         space::{DynamicRenderElements, SurfaceTree},
@@ -25,8 +28,14 @@ use smithay::{
     wayland::{
         data_device::{self, DataDeviceEvent},
         output::xdg::init_xdg_output_manager,
-        seat::{CursorImageStatus, KeyboardHandle, ModifiersState, PointerHandle, Seat, XkbConfig},
+        seat::{
+            CursorImageStatus, KeyboardHandle, ModifiersState, PointerHandle, Seat, TouchHandle,
+            XkbConfig,
+        },
         shm::init_shm_global,
+        tablet_manager::{init_tablet_manager_global, TabletSeatTrait},
+        xdg_activation::{init_xdg_activation_global, XdgActivationEvent},
+        SERIAL_COUNTER as SCOUNTER,
     },
 };
 
@@ -37,9 +46,15 @@ use crate::{
     cli::AnodiumOptions,
     config::{eventloop::ConfigEvent, ConfigVM},
     framework::backend::BackendRequest,
-    framework::{cursor::PointerElement, shell::ShellManager},
+    framework::{
+        cursor::PointerElement, pointer_constraints::PointerConstraints,
+        pointer_gestures::PointerGestures, relative_pointer::RelativePointer, shell::ShellManager,
+    },
+    gesture_handler::GestureState,
     output_manager::{Output, OutputManager},
-    render,
+    render::{self, border::BorderElement},
+    touch_handler::TouchFocus,
+    window::decoration,
     workspace::Workspace,
 };
 
@@ -47,12 +62,28 @@ pub struct InputState {
     pub pointer_location: Point<f64, Logical>,
     pub previous_pointer_location: Point<f64, Logical>,
     pub pointer: PointerHandle,
+    /// Surface that got the last pointer motion, kept while a grab holds the pointer
+    pub pointer_focus: Option<WlSurface>,
 
     pub keyboard: KeyboardHandle,
     pub modifiers_state: ModifiersState,
 
     pub suppressed_keys: Vec<u32>,
     pub pressed_keys: HashSet<u32>,
+
+    /// Output whose egui shell receives keyboard input
+    pub egui_focus: Option<Output>,
+
+    /// Titlebar button pressed, it acts when released over the same button
+    pub decoration_press: Option<(desktop::Window, decoration::Button)>,
+
+    pub gesture: Option<GestureState>,
+
+    pub touch: TouchHandle,
+    pub touch_slots: Vec<(Option<TouchSlot>, TouchFocus)>,
+
+    /// The tablet tip is down on a surface that receives emulated pointer events
+    pub tablet_emulating: bool,
 }
 
 pub struct Anodium {
@@ -79,11 +110,13 @@ pub struct Anodium {
 
     // Desktop
     pub anodium_protocol: AnodiumProtocol,
+    pub pointer_gestures: PointerGestures,
+    pub relative_pointer: RelativePointer,
+    pub pointer_constraints: PointerConstraints,
     pub output_manager: OutputManager,
 
     pub workspace: Workspace,
 
-    pub active_workspace: Option<String>,
     pub focused_window: Option<desktop::Window>,
 
     #[cfg(feature = "xwayland")]
@@ -173,6 +206,29 @@ impl Anodium {
         dnd_icon
     }
 
+    /// init xdg activation, lets clients ask for focus
+    fn init_xdg_activation(display: &Rc<RefCell<Display>>) {
+        init_xdg_activation_global(
+            &mut display.borrow_mut(),
+            |state, request, mut ddata| {
+                if let XdgActivationEvent::RequestActivation {
+                    token,
+                    token_data,
+                    surface,
+                } = request
+                {
+                    let anodium = ddata.get::<Anodium>().unwrap();
+                    // Tokens are only honored shortly after they got created
+                    let valid = token_data.timestamp.elapsed().as_secs() < 10;
+                    anodium.activation_request(&surface, valid);
+
+                    state.lock().unwrap().remove_request(&token);
+                }
+            },
+            slog_scope::logger(),
+        );
+    }
+
     /// init wayland seat, keyboard and pointer
     fn init_seat(
         display: &Rc<RefCell<Display>>,
@@ -181,6 +237,7 @@ impl Anodium {
         Seat,
         PointerHandle,
         KeyboardHandle,
+        TouchHandle,
         Arc<Mutex<CursorImageStatus>>,
     ) {
         let (mut seat, _) = Seat::new(&mut display.borrow_mut(), seat_name, slog_scope::logger());
@@ -198,7 +255,14 @@ impl Anodium {
             })
             .expect("Failed to initialize the keyboard");
 
-        (seat, pointer, keyboard, cursor_status)
+        let touch = seat.add_touch();
+
+        seat.tablet_seat().on_cursor_surface({
+            let cursor_status = cursor_status.clone();
+            move |_tool, new_status| *cursor_status.lock().unwrap() = new_status
+        });
+
+        (seat, pointer, keyboard, touch, cursor_status)
     }
 
     fn init_config_channel(handle: &LoopHandle<'static, Self>) -> Sender<ConfigEvent> {
@@ -234,14 +298,20 @@ impl Anodium {
 
         init_shm_global(&mut display.borrow_mut(), vec![], log.clone());
         init_xdg_output_manager(&mut display.borrow_mut(), log.clone());
+        init_tablet_manager_global(&mut display.borrow_mut());
 
         let dnd_icon = Self::init_data_device(&display);
 
+        Self::init_xdg_activation(&display);
+
         let shell_manager = ShellManager::init_shell(&mut display.borrow_mut());
 
-        let (seat, pointer, keyboard, cursor_status) = Self::init_seat(&display, seat_name);
+        let (seat, pointer, keyboard, touch, cursor_status) = Self::init_seat(&display, seat_name);
 
         let (anodium_protocol, _global) = AnodiumProtocol::init(&mut display.borrow_mut());
+        let (pointer_gestures, _global) = PointerGestures::init(&mut display.borrow_mut());
+        let (relative_pointer, _global) = RelativePointer::init(&mut display.borrow_mut());
+        let (pointer_constraints, _global) = PointerConstraints::init(&mut display.borrow_mut());
 
         #[cfg(feature = "xwayland")]
         let xwayland = Self::init_xwayland_connection(&handle, &display);
@@ -273,10 +343,17 @@ impl Anodium {
                     pointer_location: (0.0, 0.0).into(),
                     previous_pointer_location: (0.0, 0.0).into(),
                     pointer,
+                    pointer_focus: None,
                     keyboard,
                     modifiers_state: Default::default(),
                     suppressed_keys: Vec::new(),
                     pressed_keys: HashSet::new(),
+                    egui_focus: None,
+                    decoration_press: None,
+                    gesture: None,
+                    touch,
+                    touch_slots: Vec::new(),
+                    tablet_emulating: false,
                 },
 
                 seat,
@@ -289,10 +366,12 @@ impl Anodium {
                 config,
 
                 anodium_protocol,
+                pointer_gestures,
+                relative_pointer,
+                pointer_constraints,
                 output_manager: output_map,
                 workspace: Workspace::new(),
 
-                active_workspace: None,
                 focused_window: Default::default(),
 
                 #[cfg(feature = "xwayland")]
@@ -308,6 +387,13 @@ impl Anodium {
 impl Anodium {
     pub fn update(&mut self) {
         self.shell_manager.refresh();
+
+        let animations = &self.config.anodize.animations;
+        self.workspace.track_closing(
+            self.start_time.elapsed(),
+            animations.close(),
+            animations.scale(),
+        );
         self.workspace.refresh();
 
         if let Some(focused_window) = &self.focused_window {
@@ -362,16 +448,76 @@ impl Anodium {
         age: usize,
         pointer_image: Option<&Gles2Texture>,
     ) -> Result<Option<Vec<Rectangle<i32, Logical>>>, smithay::backend::SwapBuffersError> {
+        let now = self.start_time.elapsed();
+        self.workspace.advance(now);
+
         let output_geometry = self.workspace.output_geometry(output).unwrap();
 
         let mut elems: Vec<DynamicRenderElements<_>> = Vec::new();
 
+        output.set_workspaces(self.workspace.info(output));
+        output.set_windows(
+            self.workspace
+                .windows_info(output, self.focused_window.as_ref()),
+        );
+        output.set_decorations(self.workspace.decorations(
+            output,
+            self.focused_window.as_ref(),
+            self.input_state.pointer_location,
+        ));
+        output.set_fullscreen(self.workspace.has_fullscreen(output));
+        output.set_pointer(
+            if output_geometry
+                .to_f64()
+                .contains(self.input_state.pointer_location)
+            {
+                Some(self.input_state.pointer_location - output_geometry.loc.to_f64())
+            } else {
+                None
+            },
+        );
+
+        let snapshot_sources = if self.config.anodize.animations.close().is_some() {
+            self.workspace.snapshot_sources(output)
+        } else {
+            Vec::new()
+        };
+        elems.push(Box::new(
+            output.snapshot_element(snapshot_sources, self.workspace.closing()),
+        ));
+
+        let borders = self
+            .workspace
+            .borders(output, self.focused_window.as_ref(), |window| {
+                self.config.anodize.borders.style(
+                    &window.app_id().unwrap_or_default(),
+                    &window.title().unwrap_or_default(),
+                )
+            });
+        let damage = output.set_borders(borders.clone());
+        if let Some(pipeline) = output.quad_pipeline(renderer) {
+            elems.push(Box::new(BorderElement::new(
+                pipeline,
+                output_geometry.size,
+                output.current_mode().unwrap().size,
+                output.current_transform().into(),
+                borders,
+                damage,
+            )));
+        }
+
+        let notifications = &self.config.anodize.notifications;
+        let first = self.workspace.outputs().next() == Some(&**output);
         let frame = output.render_egui_shell(
             &self.start_time,
             &self.input_state.modifiers_state,
+            &self.config.anodize.theme,
+            Some(notifications).filter(|n| n.shown_on(&output.name(), first)),
+            &self.config.anodize.osd,
             &self.config_tx,
         );
         elems.push(Box::new(frame));
+        elems.push(Box::new(output.image_element(renderer)));
 
         // Pointer Related:
         if output_geometry
@@ -398,10 +544,38 @@ impl Anodium {
             }
         }
 
-        let render_result = self
-            .workspace
-            .render_output(renderer, output, age, [0.1, 0.1, 0.1, 1.0], &elems)
-            .unwrap();
+        let clear_color = [0.1, 0.1, 0.1, 1.0];
+        let stack = self.workspace.stack(output, now);
+        let pipeline = output.texture_pipeline(renderer);
+        let render_result = match (stack, pipeline) {
+            (Some(stack), Some(pipeline)) => {
+                let scale = self.workspace.output_scale(output).unwrap_or(1.0);
+                let target = render::transition::Target {
+                    output: &**output,
+                    output_size: output_geometry.size,
+                    mode_size: output.current_mode().unwrap().size,
+                    transform: output.current_transform().into(),
+                    scale,
+                };
+                output.set_animated_frame();
+                Some(
+                    render::transition::render_output(
+                        renderer,
+                        &target,
+                        &pipeline,
+                        &stack,
+                        |window| output.snapshot(window),
+                        &elems,
+                        clear_color,
+                    )
+                    .unwrap(),
+                )
+            }
+            _ => self
+                .workspace
+                .render_output(renderer, output, output.space_age(age), clear_color, &elems)
+                .unwrap(),
+        };
 
         if render_result.is_some() {
             #[cfg(feature = "debug")]
@@ -420,10 +594,89 @@ impl Anodium {
 
         if let Some(window) = window {
             window.set_activated(true);
+            self.workspace.set_urgent(window, false);
         }
 
         self.workspace.windows().for_each(|w| w.configure());
 
         self.focused_window = window.cloned();
     }
+
+    pub fn switch_workspace(&mut self, name: &str) {
+        let forward = self.workspace.is_after_active(name);
+        if !self.workspace.switch_to(name) {
+            return;
+        }
+
+        info!("switched to workspace: {}", name);
+
+        if let Some(curve) = self.config.anodize.animations.workspace() {
+            // The new workspace comes in from the side it is on
+            let width = self
+                .workspace
+                .output_under(self.input_state.pointer_location)
+                .next()
+                .and_then(|output| self.workspace.output_geometry(output))
+                .map(|geometry| geometry.size.w)
+                .unwrap_or(0);
+            let from = if forward { width } else { -width };
+            self.workspace
+                .slide_in((from, 0), self.start_time.elapsed(), curve);
+        }
+
+        let window = self.workspace.windows().last().cloned();
+        self.update_focused_window(window.as_ref());
+
+        let surface = window.and_then(|w| w.toplevel().get_surface().cloned());
+        self.input_state
+            .keyboard
+            .set_focus(surface.as_ref(), SCOUNTER.next_serial());
+    }
+
+    /// Switch to the workspace of the window, raise it and give it keyboard focus
+    pub fn focus_window(&mut self, window: &desktop::Window) {
+        if let Some(name) = self.workspace.workspace_of_window(window) {
+            let name = name.to_owned();
+            self.switch_workspace(&name);
+        }
+
+        self.workspace.raise_window(window, true);
+        self.update_focused_window(Some(window));
+
+        let surface = window.toplevel().get_surface().cloned();
+        self.input_state
+            .keyboard
+            .set_focus(surface.as_ref(), SCOUNTER.next_serial());
+    }
+
+    /// Activation requested through xdg_activation, windows of other workspaces
+    /// and stale requests only mark the window as urgent
+    pub fn activation_request(&mut self, surface: &WlSurface, valid: bool) {
+        let window = match self.workspace.find_window(surface) {
+            Some(window) => window.clone(),
+            None => return,
+        };
+
+        let visible = self.workspace.windows().any(|w| w == &window);
+
+        if valid && visible {
+            self.focus_window(&window);
+        } else if self.focused_window.as_ref() != Some(&window) {
+            self.workspace.set_urgent(&window, true);
+        }
+    }
+
+    /// Switch to the workspace next to the active one, going forward from a non empty
+    /// last workspace creates a new one
+    pub fn switch_workspace_relative(&mut self, forward: bool) {
+        let name = match self.workspace.neighbour(forward) {
+            Some(name) => name.to_owned(),
+            None if forward && !self.workspace.is_empty(self.workspace.active_name()) => {
+                self.workspace.next_free_name()
+            }
+            None => return,
+        };
+
+        self.switch_workspace(&name);
+    }
 }
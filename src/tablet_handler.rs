@@ -0,0 +1,264 @@
+use smithay::{
+    backend::input::{
+        ButtonState, Device, DeviceCapability, Event, InputBackend, MouseButton, ProximityState,
+        TabletToolButtonEvent, TabletToolEvent, TabletToolProximityEvent, TabletToolTipEvent,
+        TabletToolTipState,
+    },
+    desktop::WindowSurfaceType,
+    reexports::wayland_server::protocol::{wl_pointer, wl_surface::WlSurface},
+    utils::{Logical, Point},
+    wayland::{
+        seat::Seat,
+        tablet_manager::{TabletDescriptor, TabletSeatTrait},
+        SERIAL_COUNTER as SCOUNTER,
+    },
+};
+
+use crate::{output_manager::Output, Anodium};
+
+/// Linux input event code of the left mouse button
+const BTN_LEFT: u32 = 0x110;
+
+/// Check if the client owning the surface bound tablet-v2,
+/// tools hovering surfaces of other clients are emulated as a pointer
+fn client_uses_tablet(seat: &Seat, surface: &WlSurface) -> bool {
+    surface
+        .as_ref()
+        .client()
+        .map(|client| seat.tablet_seat().has_client(&client))
+        .unwrap_or(false)
+}
+
+impl Anodium {
+    pub(crate) fn on_tablet_device_added<I: InputBackend>(&mut self, device: &I::Device) {
+        if device.has_capability(DeviceCapability::TabletTool) {
+            self.seat
+                .tablet_seat()
+                .add_tablet(&TabletDescriptor::from(device));
+        }
+    }
+
+    pub(crate) fn on_tablet_device_removed<I: InputBackend>(&mut self, device: &I::Device) {
+        if device.has_capability(DeviceCapability::TabletTool) {
+            let tablet_seat = self.seat.tablet_seat();
+
+            tablet_seat.remove_tablet(&TabletDescriptor::from(device));
+
+            // If there are no tablets in seat we can remove all tools
+            if tablet_seat.count_tablets() == 0 {
+                tablet_seat.clear_tools();
+            }
+        }
+    }
+
+    fn tablet_location<I: InputBackend, E: TabletToolEvent<I>>(
+        &self,
+        evt: &E,
+        output: Option<&Output>,
+    ) -> Option<Point<f64, Logical>> {
+        let output = self.mapped_output(self.config.tablet_output(), output)?;
+        let output_geometry = self.workspace.output_geometry(&output)?;
+
+        Some(evt.position_transformed(output_geometry.size) + output_geometry.loc.to_f64())
+    }
+
+    /// Move the pointer along with the tool, used for clients without tablet-v2 and the egui shell
+    fn emulate_pointer_motion(&mut self, location: Point<f64, Logical>, time: u32) {
+        self.input_state.pointer_location = location;
+        self.on_pointer_move(time);
+
+        if let Some(output) = self.workspace.output_under(location).next().cloned() {
+            let output = Output::wrap(output);
+            let output_loc = self.workspace.output_geometry(&output).unwrap().loc;
+
+            if self.surface_under(location).is_none() {
+                output
+                    .egui()
+                    .handle_pointer_motion((location - output_loc.to_f64()).to_i32_round());
+            } else {
+                self.reset_egui_event(&output);
+            }
+        }
+    }
+
+    pub(crate) fn on_tablet_tool_axis<I: InputBackend>(
+        &mut self,
+        evt: &I::TabletToolAxisEvent,
+        output: Option<&Output>,
+    ) {
+        let location = match self.tablet_location(evt, output) {
+            Some(location) => location,
+            None => return,
+        };
+
+        let under = self.surface_under(location);
+        let tablet_seat = self.seat.tablet_seat();
+        let tablet = tablet_seat.get_tablet(&TabletDescriptor::from(&evt.device()));
+        let tool = tablet_seat.get_tool(&evt.tool());
+
+        let uses_tablet = under
+            .as_ref()
+            .map(|(surface, _)| client_uses_tablet(&self.seat, surface))
+            .unwrap_or(false);
+
+        if let (Some(tablet), Some(tool)) = (tablet, tool) {
+            if uses_tablet && !self.input_state.tablet_emulating {
+                if evt.pressure_has_changed() {
+                    tool.pressure(evt.pressure());
+                }
+                if evt.distance_has_changed() {
+                    tool.distance(evt.distance());
+                }
+                if evt.tilt_has_changed() {
+                    tool.tilt(evt.tilt());
+                }
+                if evt.slider_has_changed() {
+                    tool.slider_position(evt.slider_position());
+                }
+                if evt.rotation_has_changed() {
+                    tool.rotation(evt.rotation());
+                }
+                if evt.wheel_has_changed() {
+                    tool.wheel(evt.wheel_delta(), evt.wheel_delta_discrete());
+                }
+
+                self.input_state.pointer_location = location;
+                tool.motion(location, under, &tablet, SCOUNTER.next_serial(), evt.time());
+                return;
+            }
+
+            // Leave the tablet-v2 surface before the pointer takes over
+            tool.motion(location, None, &tablet, SCOUNTER.next_serial(), evt.time());
+        }
+
+        self.emulate_pointer_motion(location, evt.time());
+    }
+
+    pub(crate) fn on_tablet_tool_proximity<I: InputBackend>(
+        &mut self,
+        evt: &I::TabletToolProximityEvent,
+        output: Option<&Output>,
+    ) {
+        let location = match self.tablet_location(evt, output) {
+            Some(location) => location,
+            None => return,
+        };
+
+        let tablet_seat = self.seat.tablet_seat();
+        let tool = evt.tool();
+        tablet_seat.add_tool(&tool);
+
+        let under = self.surface_under(location);
+        let tablet = tablet_seat.get_tablet(&TabletDescriptor::from(&evt.device()));
+        let tool = tablet_seat.get_tool(&tool);
+
+        match (under, tablet, tool) {
+            (Some(under), Some(tablet), Some(tool)) if client_uses_tablet(&self.seat, &under.0) => {
+                self.input_state.pointer_location = location;
+
+                match evt.state() {
+                    ProximityState::In => tool.proximity_in(
+                        location,
+                        under,
+                        &tablet,
+                        SCOUNTER.next_serial(),
+                        evt.time(),
+                    ),
+                    ProximityState::Out => tool.proximity_out(evt.time()),
+                }
+            }
+            _ => {
+                if evt.state() == ProximityState::In {
+                    self.emulate_pointer_motion(location, evt.time());
+                }
+            }
+        }
+    }
+
+    pub(crate) fn on_tablet_tool_tip<I: InputBackend>(&mut self, evt: &I::TabletToolTipEvent) {
+        let tool = self.seat.tablet_seat().get_tool(&evt.tool());
+        let location = self.input_state.pointer_location;
+
+        let uses_tablet = self
+            .surface_under(location)
+            .map(|(surface, _)| client_uses_tablet(&self.seat, &surface))
+            .unwrap_or(false);
+
+        match evt.tip_state() {
+            TabletToolTipState::Down => {
+                let serial = SCOUNTER.next_serial();
+
+                // change the keyboard focus
+                let window = self.workspace.window_under(location).cloned();
+                self.update_focused_window(window.as_ref());
+                let surface = window
+                    .and_then(|w| w.surface_under(location, WindowSurfaceType::ALL))
+                    .map(|s| s.0);
+                self.input_state
+                    .keyboard
+                    .set_focus(surface.as_ref(), serial);
+
+                match tool {
+                    Some(tool) if uses_tablet => tool.tip_down(serial, evt.time()),
+                    _ => {
+                        self.input_state.tablet_emulating = true;
+                        self.emulate_pointer_button(ButtonState::Pressed, evt.time());
+                    }
+                }
+            }
+            TabletToolTipState::Up => {
+                if self.input_state.tablet_emulating {
+                    self.input_state.tablet_emulating = false;
+                    self.emulate_pointer_button(ButtonState::Released, evt.time());
+                } else if let Some(tool) = tool {
+                    tool.tip_up(evt.time());
+                }
+            }
+        }
+    }
+
+    pub(crate) fn on_tablet_tool_button<I: InputBackend>(
+        &mut self,
+        evt: &I::TabletToolButtonEvent,
+    ) {
+        if self.input_state.tablet_emulating {
+            return;
+        }
+
+        if let Some(tool) = self.seat.tablet_seat().get_tool(&evt.tool()) {
+            tool.button(
+                evt.button(),
+                evt.button_state(),
+                SCOUNTER.next_serial(),
+                evt.time(),
+            );
+        }
+    }
+
+    fn emulate_pointer_button(&mut self, state: ButtonState, time: u32) {
+        let location = self.input_state.pointer_location;
+
+        if self.surface_under(location).is_none() {
+            if let Some(output) = self.workspace.output_under(location).next().cloned() {
+                Output::wrap(output).egui().handle_pointer_button(
+                    MouseButton::Left,
+                    state == ButtonState::Pressed,
+                    self.input_state.modifiers_state,
+                );
+            }
+        }
+
+        let state = match state {
+            ButtonState::Pressed => wl_pointer::ButtonState::Pressed,
+            ButtonState::Released => wl_pointer::ButtonState::Released,
+        };
+
+        self.input_state.pointer.clone().button(
+            BTN_LEFT,
+            state,
+            SCOUNTER.next_serial(),
+            time,
+            self,
+        );
+    }
+}
@@ -0,0 +1,188 @@
+use smithay::{
+    backend::input::{
+        Event, InputBackend, MouseButton, TouchDownEvent, TouchMotionEvent, TouchSlot, TouchUpEvent,
+    },
+    desktop::WindowSurfaceType,
+    utils::{Logical, Point},
+    wayland::SERIAL_COUNTER as SCOUNTER,
+};
+
+use crate::{output_manager::Output, Anodium};
+
+/// Receiver of a single touch point, decided when the touch point goes down
+#[derive(Debug)]
+pub enum TouchFocus {
+    /// Client surface with its location at the time of the touch down
+    Surface(Point<i32, Logical>),
+    Egui(Output),
+}
+
+impl Anodium {
+    fn take_touch_slot(&mut self, slot: Option<TouchSlot>) -> Option<TouchFocus> {
+        let index = self
+            .input_state
+            .touch_slots
+            .iter()
+            .position(|(s, _)| *s == slot)?;
+        Some(self.input_state.touch_slots.remove(index).1)
+    }
+
+    pub(crate) fn on_touch_down<I: InputBackend>(
+        &mut self,
+        evt: &I::TouchDownEvent,
+        output: Option<&Output>,
+    ) {
+        let output = match self.mapped_output(self.config.touch_output(), output) {
+            Some(output) => output,
+            None => return,
+        };
+        let output_geometry = match self.workspace.output_geometry(&output) {
+            Some(geometry) => geometry,
+            None => return,
+        };
+        let location =
+            evt.position_transformed(output_geometry.size) + output_geometry.loc.to_f64();
+
+        let slot = evt.slot();
+        let serial = SCOUNTER.next_serial();
+
+        // A slot that was not released yet gets replaced
+        self.take_touch_slot(slot);
+
+        let focus = match self.surface_under(location) {
+            Some((surface, surface_location)) => {
+                let window = self.workspace.window_under(location).cloned();
+                self.update_focused_window(window.as_ref());
+                self.input_state.egui_focus = None;
+
+                let keyboard_focus = window
+                    .and_then(|w| w.surface_under(location, WindowSurfaceType::ALL))
+                    .map(|s| s.0);
+                self.input_state
+                    .keyboard
+                    .set_focus(keyboard_focus.as_ref(), serial);
+
+                self.input_state.touch.down(
+                    serial,
+                    evt.time(),
+                    &surface,
+                    slot,
+                    location - surface_location.to_f64(),
+                );
+
+                TouchFocus::Surface(surface_location)
+            }
+            None => {
+                let mut egui = output.egui();
+                egui.handle_pointer_motion(
+                    (location - output_geometry.loc.to_f64()).to_i32_round(),
+                );
+                egui.handle_pointer_button(
+                    MouseButton::Left,
+                    true,
+                    self.input_state.modifiers_state,
+                );
+                drop(egui);
+
+                self.update_focused_window(None);
+                self.input_state.keyboard.set_focus(None, serial);
+                self.input_state.egui_focus = Some(output.clone());
+                TouchFocus::Egui(output)
+            }
+        };
+
+        self.input_state.touch_slots.push((slot, focus));
+    }
+
+    pub(crate) fn on_touch_motion<I: InputBackend>(
+        &mut self,
+        evt: &I::TouchMotionEvent,
+        output: Option<&Output>,
+    ) {
+        let output = match self.mapped_output(self.config.touch_output(), output) {
+            Some(output) => output,
+            None => return,
+        };
+        let output_geometry = match self.workspace.output_geometry(&output) {
+            Some(geometry) => geometry,
+            None => return,
+        };
+        let location =
+            evt.position_transformed(output_geometry.size) + output_geometry.loc.to_f64();
+
+        let slot = evt.slot();
+        let focus = self
+            .input_state
+            .touch_slots
+            .iter()
+            .find(|(s, _)| *s == slot)
+            .map(|(_, focus)| focus);
+
+        match focus {
+            Some(TouchFocus::Surface(loc)) => {
+                self.input_state
+                    .touch
+                    .motion(evt.time(), slot, location - loc.to_f64());
+            }
+            Some(TouchFocus::Egui(output)) => {
+                let output_loc = match self.workspace.output_geometry(output) {
+                    Some(output_geometry) => output_geometry.loc,
+                    None => return,
+                };
+                output
+                    .egui()
+                    .handle_pointer_motion((location - output_loc.to_f64()).to_i32_round());
+            }
+            None => {}
+        }
+    }
+
+    pub(crate) fn on_touch_up<I: InputBackend>(&mut self, evt: &I::TouchUpEvent) {
+        let slot = evt.slot();
+
+        match self.take_touch_slot(slot) {
+            Some(TouchFocus::Surface(_)) => {
+                self.input_state
+                    .touch
+                    .up(SCOUNTER.next_serial(), evt.time(), slot);
+            }
+            Some(TouchFocus::Egui(output)) => {
+                release_egui_touch(&output, self);
+            }
+            None => {}
+        }
+    }
+
+    pub(crate) fn on_touch_cancel<I: InputBackend>(&mut self, _evt: &I::TouchCancelEvent) {
+        let slots = std::mem::take(&mut self.input_state.touch_slots);
+
+        for (_, focus) in slots.iter() {
+            if let TouchFocus::Egui(output) = focus {
+                release_egui_touch(output, self);
+            }
+        }
+
+        if slots
+            .iter()
+            .any(|(_, focus)| matches!(focus, TouchFocus::Surface(_)))
+        {
+            self.input_state.touch.cancel();
+        }
+    }
+
+    pub(crate) fn on_touch_frame<I: InputBackend>(&mut self, _evt: &I::TouchFrameEvent) {
+        self.input_state.touch.frame();
+    }
+}
+
+/// Release the emulated button and move the emulated pointer out of the way,
+/// so nothing stays hovered after the finger is lifted
+fn release_egui_touch(output: &Output, anodium: &Anodium) {
+    let mut egui = output.egui();
+    egui.handle_pointer_button(
+        MouseButton::Left,
+        false,
+        anodium.input_state.modifiers_state,
+    );
+    egui.handle_pointer_motion((i32::MAX, i32::MAX).into());
+}
@@ -0,0 +1,216 @@
+use egui::{Align2, CtxRef, LayerId, Stroke, TextStyle};
+use smithay::utils::{Logical, Point, Rectangle};
+
+use crate::framework::surface_data::ResizeEdge;
+
+pub const TITLEBAR_HEIGHT: i32 = 24;
+pub const BORDER_WIDTH: i32 = 4;
+const BUTTON_WIDTH: i32 = 28;
+const TITLE_PADDING: f32 = 8.0;
+const ICON_SIZE: f32 = 4.0;
+
+/// Buttons of the titlebar, from its right end
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Button {
+    Close,
+    Maximize,
+    Minimize,
+}
+
+const BUTTONS: [Button; 3] = [Button::Close, Button::Maximize, Button::Minimize];
+
+/// Part of the decorations under the pointer
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Part {
+    Titlebar,
+    Button(Button),
+    Edge(ResizeEdge),
+}
+
+/// Area covered by a window with the given geometry and its decorations
+pub fn outer(geometry: Rectangle<i32, Logical>) -> Rectangle<i32, Logical> {
+    Rectangle::from_loc_and_size(
+        (
+            geometry.loc.x - BORDER_WIDTH,
+            geometry.loc.y - TITLEBAR_HEIGHT - BORDER_WIDTH,
+        ),
+        (
+            geometry.size.w + 2 * BORDER_WIDTH,
+            geometry.size.h + TITLEBAR_HEIGHT + 2 * BORDER_WIDTH,
+        ),
+    )
+}
+
+/// Window geometry that fits into `area` together with its decorations
+pub fn inner(area: Rectangle<i32, Logical>) -> Rectangle<i32, Logical> {
+    Rectangle::from_loc_and_size(
+        (
+            area.loc.x + BORDER_WIDTH,
+            area.loc.y + TITLEBAR_HEIGHT + BORDER_WIDTH,
+        ),
+        (
+            (area.size.w - 2 * BORDER_WIDTH).max(1),
+            (area.size.h - TITLEBAR_HEIGHT - 2 * BORDER_WIDTH).max(1),
+        ),
+    )
+}
+
+fn titlebar(geometry: Rectangle<i32, Logical>) -> Rectangle<i32, Logical> {
+    Rectangle::from_loc_and_size(
+        (geometry.loc.x, geometry.loc.y - TITLEBAR_HEIGHT),
+        (geometry.size.w, TITLEBAR_HEIGHT),
+    )
+}
+
+fn button(geometry: Rectangle<i32, Logical>, index: usize) -> Rectangle<i32, Logical> {
+    let titlebar = titlebar(geometry);
+    Rectangle::from_loc_and_size(
+        (
+            titlebar.loc.x + titlebar.size.w - (index as i32 + 1) * BUTTON_WIDTH,
+            titlebar.loc.y,
+        ),
+        (BUTTON_WIDTH, TITLEBAR_HEIGHT),
+    )
+}
+
+/// Part of the decorations of a window with the given geometry under the point
+pub fn part_at(geometry: Rectangle<i32, Logical>, point: Point<f64, Logical>) -> Option<Part> {
+    let outer = outer(geometry).to_f64();
+    let inner = geometry.to_f64();
+    if !outer.contains(point) || inner.contains(point) {
+        return None;
+    }
+
+    let mut edges = ResizeEdge::NONE;
+    if point.x < inner.loc.x {
+        edges |= ResizeEdge::LEFT;
+    }
+    if point.x >= inner.loc.x + inner.size.w {
+        edges |= ResizeEdge::RIGHT;
+    }
+    if point.y < outer.loc.y + BORDER_WIDTH as f64 {
+        edges |= ResizeEdge::TOP;
+    }
+    if point.y >= inner.loc.y + inner.size.h {
+        edges |= ResizeEdge::BOTTOM;
+    }
+    if !edges.is_empty() {
+        return Some(Part::Edge(edges));
+    }
+
+    let button = BUTTONS
+        .iter()
+        .enumerate()
+        .find(|(index, _)| button(geometry, *index).to_f64().contains(point))
+        .map(|(_, button)| Part::Button(*button));
+
+    Some(button.unwrap_or(Part::Titlebar))
+}
+
+/// Decorations of one window as seen from an output, painted by its egui shell
+#[derive(Debug, Clone, PartialEq)]
+pub struct Decoration {
+    /// Window geometry, relative to the output
+    pub geometry: Rectangle<i32, Logical>,
+    /// Parts of the decorations not covered by anything drawn above, relative to the output
+    pub visible: Vec<Rectangle<i32, Logical>>,
+    pub title: String,
+    pub focused: bool,
+    pub hovered: Option<Part>,
+}
+
+fn to_egui(rect: Rectangle<i32, Logical>) -> egui::Rect {
+    egui::Rect::from_min_size(
+        egui::pos2(rect.loc.x as f32, rect.loc.y as f32),
+        egui::vec2(rect.size.w as f32, rect.size.h as f32),
+    )
+}
+
+/// Paint the decorations below every shell container, in stacking order
+pub fn paint(ctx: &CtxRef, decorations: &[Decoration]) {
+    let visuals = ctx.style().visuals.clone();
+
+    for decoration in decorations {
+        // The shell is drawn above every window, so only the uncovered parts are painted
+        for visible in decoration.visible.iter() {
+            let painter = ctx
+                .layer_painter(LayerId::background())
+                .with_clip_rect(to_egui(*visible));
+            paint_decoration(&painter, &visuals, decoration);
+        }
+    }
+}
+
+fn paint_decoration(painter: &egui::Painter, visuals: &egui::Visuals, decoration: &Decoration) {
+    let geometry = to_egui(decoration.geometry);
+    let outer = to_egui(outer(decoration.geometry));
+
+    let fill = if decoration.focused {
+        visuals.selection.bg_fill
+    } else {
+        visuals.widgets.noninteractive.bg_fill
+    };
+    let color = visuals.text_color();
+
+    // Only around the window, its content is drawn below the shell
+    let strips = [
+        egui::Rect::from_min_max(outer.min, egui::pos2(outer.max.x, geometry.min.y)),
+        egui::Rect::from_min_max(
+            egui::pos2(outer.min.x, geometry.min.y),
+            egui::pos2(geometry.min.x, geometry.max.y),
+        ),
+        egui::Rect::from_min_max(
+            egui::pos2(geometry.max.x, geometry.min.y),
+            egui::pos2(outer.max.x, geometry.max.y),
+        ),
+        egui::Rect::from_min_max(egui::pos2(outer.min.x, geometry.max.y), outer.max),
+    ];
+    for strip in strips.iter() {
+        painter.rect_filled(*strip, 0.0, fill);
+    }
+
+    let titlebar = to_egui(titlebar(decoration.geometry));
+    let title_clip = egui::Rect::from_min_max(
+        titlebar.min,
+        egui::pos2(
+            titlebar.max.x - BUTTONS.len() as f32 * BUTTON_WIDTH as f32,
+            titlebar.max.y,
+        ),
+    );
+    painter
+        .with_clip_rect(title_clip.intersect(painter.clip_rect()))
+        .text(
+            egui::pos2(titlebar.min.x + TITLE_PADDING, titlebar.center().y),
+            Align2::LEFT_CENTER,
+            &decoration.title,
+            TextStyle::Body,
+            color,
+        );
+
+    let stroke = Stroke::new(1.5, color);
+    for (index, kind) in BUTTONS.iter().enumerate() {
+        let rect = to_egui(button(decoration.geometry, index));
+        if decoration.hovered == Some(Part::Button(*kind)) {
+            painter.rect_filled(rect, 0.0, visuals.widgets.hovered.bg_fill);
+        }
+
+        let c = rect.center();
+        let s = ICON_SIZE;
+        match kind {
+            Button::Close => {
+                painter.line_segment([c + egui::vec2(-s, -s), c + egui::vec2(s, s)], stroke);
+                painter.line_segment([c + egui::vec2(-s, s), c + egui::vec2(s, -s)], stroke);
+            }
+            Button::Maximize => {
+                painter.rect_stroke(
+                    egui::Rect::from_center_size(c, egui::vec2(2.0 * s, 2.0 * s)),
+                    0.0,
+                    stroke,
+                );
+            }
+            Button::Minimize => {
+                painter.line_segment([c + egui::vec2(-s, s), c + egui::vec2(s, s)], stroke);
+            }
+        }
+    }
+}
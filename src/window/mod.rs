@@ -1,11 +1,18 @@
+use std::sync::Mutex;
+
 use smithay::desktop;
 use smithay::{
     desktop::Kind,
+    reexports::wayland_protocols::xdg_shell::server::xdg_toplevel,
     utils::{Logical, Rectangle},
+    wayland::{compositor, shell::xdg::XdgToplevelSurfaceRoleAttributes},
 };
 
+use crate::framework::shell::DecorationMode;
 use crate::workspace::Workspace;
 
+pub mod decoration;
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Window {
     window: smithay::desktop::Window,
@@ -18,11 +25,64 @@ impl Window {
         }
     }
 
+    pub fn wrap(window: desktop::Window) -> Self {
+        Self { window }
+    }
+
     pub fn desktop_window(&self) -> &desktop::Window {
         &self.window
     }
 }
 
+impl Window {
+    fn with_toplevel_attributes<T>(
+        &self,
+        f: impl FnOnce(&XdgToplevelSurfaceRoleAttributes) -> T,
+    ) -> Option<T> {
+        match self.window.toplevel() {
+            Kind::Xdg(xdg) => compositor::with_states(xdg.get_surface()?, |states| {
+                states
+                    .data_map
+                    .get::<Mutex<XdgToplevelSurfaceRoleAttributes>>()
+                    .map(|attributes| f(&attributes.lock().unwrap()))
+            })
+            .ok()
+            .flatten(),
+            #[cfg(feature = "xwayland")]
+            _ => None,
+        }
+    }
+
+    pub fn title(&self) -> Option<String> {
+        self.with_toplevel_attributes(|attributes| attributes.title.clone())
+            .flatten()
+    }
+
+    pub fn app_id(&self) -> Option<String> {
+        self.with_toplevel_attributes(|attributes| attributes.app_id.clone())
+            .flatten()
+    }
+
+    /// The titlebar and borders are drawn by the compositor, fullscreen windows have none
+    pub fn server_side_decorated(&self) -> bool {
+        self.with_toplevel_attributes(|attributes| {
+            attributes.current.decoration_mode == Some(DecorationMode::ServerSide)
+                && !attributes
+                    .current
+                    .states
+                    .contains(xdg_toplevel::State::Fullscreen)
+        })
+        .unwrap_or(false)
+    }
+
+    /// Ask the client to close the window
+    pub fn close(&self) {
+        if let Kind::Xdg(xdg) = self.window.toplevel() {
+            xdg.send_close();
+        }
+    }
+}
+
 impl Window {
     pub fn bbox_in_comp_space(&self, space: &Workspace) -> Rectangle<i32, Logical> {
         space.window_bbox(&self.window).unwrap()
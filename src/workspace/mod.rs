@@ -1,10 +1,112 @@
-use smithay::desktop;
+use std::time::Duration;
 
+use smithay::{
+    desktop::{self, PopupManager},
+    reexports::wayland_server::protocol::wl_surface::WlSurface,
+    utils::{Logical, Point, Rectangle},
+    wayland::{
+        compositor::{self, TraversalAction},
+        output::Output as SmithayOutput,
+        shell::wlr_layer::Layer as WlrLayer,
+    },
+};
+
+use crate::animation::{self, Animation, Content, Curve, Motion, Placement, Sample, Transition};
+use crate::config::borders::BorderStyle;
+use crate::render::{border, snapshot::SnapshotSource, transition::Layered};
+use crate::window::decoration::{self, Decoration, Part};
+use crate::window::Window;
+
+pub const DEFAULT_WORKSPACE: &str = "1";
+
+/// Toplevel as seen from one output, used by the shell
+#[derive(Debug, Clone, PartialEq)]
+pub struct WindowInfo {
+    pub window: Window,
+    pub title: String,
+    pub app_id: String,
+    pub workspace: String,
+    pub focused: bool,
+    pub minimized: bool,
+}
+
+/// Window removed from its space until it gets restored
 #[derive(PartialEq)]
-pub struct Workspace {
+struct Minimized {
+    workspace: String,
+    window: desktop::Window,
+    location: Point<i32, Logical>,
+    outputs: Vec<SmithayOutput>,
+}
+
+/// Windows that got resized to cover an area of their output
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Expanded {
+    Maximized,
+    Fullscreen,
+}
+
+/// State of a single workspace as seen from one output, used by the shell
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorkspaceInfo {
+    pub name: String,
+    pub active: bool,
+    pub urgent: bool,
+    /// Some window of the workspace is visible on the output
+    pub occupied: bool,
+}
+
+#[derive(PartialEq)]
+struct NamedSpace {
+    name: String,
     space: desktop::Space,
 }
 
+impl NamedSpace {
+    fn new(name: String) -> Self {
+        Self {
+            name,
+            space: desktop::Space::new(slog_scope::logger()),
+        }
+    }
+}
+
+/// Collection of named spaces, derefs to the currently active one
+#[derive(PartialEq)]
+pub struct Workspace {
+    spaces: Vec<NamedSpace>,
+    active: usize,
+
+    /// Output layout, only the active space maps the outputs so windows of hidden
+    /// workspaces are not shown on any of them
+    outputs: Vec<(SmithayOutput, f64, Point<i32, Logical>)>,
+
+    /// Location of every window of the active space before the switch offset got applied
+    offset_origins: Vec<(desktop::Window, Point<i32, Logical>)>,
+    offset: Point<i32, Logical>,
+
+    /// Windows that requested attention without getting focused
+    urgent: Vec<desktop::Window>,
+
+    minimized: Vec<Minimized>,
+
+    /// Windows kept above the others of their space
+    on_top: Vec<desktop::Window>,
+
+    /// Maximized and fullscreen windows, with their geometry from before
+    expanded: Vec<(desktop::Window, Expanded, Rectangle<i32, Logical>)>,
+
+    /// Windows drawn by their animation instead of their space
+    transitions: Vec<Transition>,
+    /// Last state of the windows of the active space, closed ones are animated from it
+    seen: Vec<(desktop::Window, Placement)>,
+    /// Serial of the last commit to the surface tree of every window
+    commits: Vec<(desktop::Window, u64)>,
+    commit_serial: u64,
+    /// Switch offset animated back to zero, with the offset it started from
+    slide: Option<(Animation, Point<i32, Logical>)>,
+}
+
 impl Default for Workspace {
     fn default() -> Self {
         Self::new()
@@ -14,8 +116,1051 @@ impl Default for Workspace {
 impl Workspace {
     pub fn new() -> Self {
         Self {
-            space: desktop::Space::new(slog_scope::logger()),
+            spaces: vec![NamedSpace::new(DEFAULT_WORKSPACE.into())],
+            active: 0,
+            outputs: Vec::new(),
+            offset_origins: Vec::new(),
+            offset: Point::default(),
+            urgent: Vec::new(),
+            minimized: Vec::new(),
+            on_top: Vec::new(),
+            expanded: Vec::new(),
+            transitions: Vec::new(),
+            seen: Vec::new(),
+            commits: Vec::new(),
+            commit_serial: 0,
+            slide: None,
+        }
+    }
+
+    pub fn active_name(&self) -> &str {
+        &self.spaces[self.active].name
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.spaces.iter().map(|s| s.name.as_str())
+    }
+
+    pub fn is_empty(&self, name: &str) -> bool {
+        self.spaces
+            .iter()
+            .find(|s| s.name == name)
+            .map(|s| s.space.windows().next().is_none())
+            .unwrap_or(true)
+    }
+
+    pub fn is_urgent(&self, name: &str) -> bool {
+        self.spaces
+            .iter()
+            .find(|s| s.name == name)
+            .map(|s| s.space.windows().any(|w| self.urgent.contains(w)))
+            .unwrap_or(false)
+    }
+
+    pub fn set_urgent(&mut self, window: &desktop::Window, urgent: bool) {
+        self.urgent.retain(|w| w != window);
+        if urgent {
+            self.urgent.push(window.clone());
+        }
+    }
+
+    /// Snapshot of all workspaces for the given output
+    pub fn info(&self, output: &SmithayOutput) -> Vec<WorkspaceInfo> {
+        self.spaces
+            .iter()
+            .enumerate()
+            .map(|(i, s)| WorkspaceInfo {
+                name: s.name.clone(),
+                active: i == self.active,
+                urgent: self.is_urgent(&s.name),
+                occupied: s
+                    .space
+                    .windows()
+                    .any(|w| self.window_outputs(&s.space, w).contains(output)),
+            })
+            .collect()
+    }
+
+    /// Snapshot of the windows visible on, or minimized from, the given output
+    pub fn windows_info(
+        &self,
+        output: &SmithayOutput,
+        focused: Option<&desktop::Window>,
+    ) -> Vec<WindowInfo> {
+        let info = |window: &desktop::Window, workspace: &str, minimized: bool| {
+            let window = Window::wrap(window.clone());
+            WindowInfo {
+                title: window.title().unwrap_or_default(),
+                app_id: window.app_id().unwrap_or_default(),
+                workspace: workspace.to_owned(),
+                focused: focused == Some(window.desktop_window()),
+                minimized,
+                window,
+            }
+        };
+
+        let mapped = self.spaces.iter().flat_map(|s| {
+            s.space
+                .windows()
+                .filter(move |w| self.window_outputs(&s.space, w).contains(output))
+                .map(move |w| info(w, &s.name, false))
+        });
+
+        let minimized = self
+            .minimized
+            .iter()
+            .filter(|m| m.outputs.contains(output))
+            .map(|m| info(&m.window, &m.workspace, true));
+
+        mapped.chain(minimized).collect()
+    }
+
+    /// Area a window of the active space covers, with its decorations if it has any
+    fn covered_area(&self, window: &desktop::Window) -> Option<Rectangle<i32, Logical>> {
+        let geometry = self.spaces[self.active].space.window_geometry(window)?;
+        if Window::wrap(window.clone()).server_side_decorated() {
+            Some(decoration::outer(geometry))
+        } else {
+            Some(geometry)
+        }
+    }
+
+    /// Areas drawn above the window at `index` of the active space: windows stacked above it
+    /// with their decorations, popups of it and of those windows, and top and overlay layer surfaces
+    fn occluders(
+        &self,
+        windows: &[&desktop::Window],
+        index: usize,
+    ) -> Vec<Rectangle<i32, Logical>> {
+        let space = &self.spaces[self.active].space;
+
+        let above = windows[index + 1..]
+            .iter()
+            .filter_map(|window| self.covered_area(window));
+
+        let popups = windows[index..].iter().flat_map(|window| {
+            let geometry = space.window_geometry(window);
+            window
+                .toplevel()
+                .get_surface()
+                .and_then(|surface| PopupManager::popups_for_surface(surface).ok())
+                .into_iter()
+                .flatten()
+                .filter_map(move |(popup, location)| {
+                    Some(Rectangle::from_loc_and_size(
+                        geometry?.loc + location,
+                        popup.geometry().size,
+                    ))
+                })
+        });
+
+        let layers = self.outputs.iter().flat_map(|(output, _, _)| {
+            let output_loc = space.output_geometry(output).map(|geometry| geometry.loc);
+            let map = desktop::layer_map_for_output(output);
+            let layers: Vec<_> = [WlrLayer::Top, WlrLayer::Overlay]
+                .iter()
+                .flat_map(|layer| map.layers_on(*layer))
+                .map(|layer| map.layer_geometry(layer))
+                .collect();
+            layers.into_iter().filter_map(move |mut geometry| {
+                geometry.loc += output_loc?;
+                Some(geometry)
+            })
+        });
+
+        above.chain(popups).chain(layers).collect()
+    }
+
+    /// Parts of the decorations of the window at `index` of the active space that are not covered
+    fn visible_decoration(
+        &self,
+        windows: &[&desktop::Window],
+        index: usize,
+    ) -> Vec<Rectangle<i32, Logical>> {
+        let geometry = match self.spaces[self.active]
+            .space
+            .window_geometry(windows[index])
+        {
+            Some(geometry) => geometry,
+            None => return Vec::new(),
+        };
+
+        let mut visible = border::subtract(decoration::outer(geometry), geometry);
+        for cover in self.occluders(windows, index) {
+            visible = visible
+                .into_iter()
+                .flat_map(|rect| border::subtract(rect, cover))
+                .collect();
+        }
+        visible
+    }
+
+    /// Window of the active space whose decorations are under the point,
+    /// unless something drawn above covers it
+    pub fn decoration_under(&self, point: Point<f64, Logical>) -> Option<(desktop::Window, Part)> {
+        let space = &self.spaces[self.active].space;
+        let windows: Vec<_> = space.windows().collect();
+
+        for (index, window) in windows.iter().enumerate().rev() {
+            let geometry = match space.window_geometry(window) {
+                Some(geometry) => geometry,
+                None => continue,
+            };
+
+            if Window::wrap((*window).clone()).server_side_decorated() {
+                if let Some(part) = decoration::part_at(geometry, point) {
+                    let visible = self
+                        .visible_decoration(&windows, index)
+                        .iter()
+                        .any(|rect| rect.to_f64().contains(point));
+                    return Some(((*window).clone(), part)).filter(|_| visible);
+                }
+            }
+            if geometry.to_f64().contains(point) {
+                return None;
+            }
+        }
+
+        None
+    }
+
+    /// Decorations visible on the output, bottom to top
+    pub fn decorations(
+        &self,
+        output: &SmithayOutput,
+        focused: Option<&desktop::Window>,
+        pointer: Point<f64, Logical>,
+    ) -> Vec<Decoration> {
+        let space = &self.spaces[self.active].space;
+        let output_geometry = match space.output_geometry(output) {
+            Some(output_geometry) => output_geometry,
+            None => return Vec::new(),
+        };
+        let relative = |mut rect: Rectangle<i32, Logical>| {
+            rect.loc = rect.loc - output_geometry.loc;
+            rect
+        };
+
+        let hovered = self.decoration_under(pointer);
+        let windows: Vec<_> = space.windows().collect();
+
+        windows
+            .iter()
+            .enumerate()
+            .filter_map(|(index, window)| {
+                let wrapped = Window::wrap((*window).clone());
+                if !wrapped.server_side_decorated() || self.is_animated(window) {
+                    return None;
+                }
+
+                let geometry = space.window_geometry(window)?;
+                if !decoration::outer(geometry).overlaps(output_geometry) {
+                    return None;
+                }
+
+                let visible: Vec<_> = self
+                    .visible_decoration(&windows, index)
+                    .into_iter()
+                    .filter(|rect| rect.overlaps(output_geometry))
+                    .map(relative)
+                    .collect();
+                if visible.is_empty() {
+                    return None;
+                }
+
+                Some(Decoration {
+                    geometry: relative(geometry),
+                    visible,
+                    title: wrapped.title().unwrap_or_default(),
+                    focused: focused == Some(*window),
+                    hovered: hovered
+                        .as_ref()
+                        .filter(|(w, _)| w == *window)
+                        .map(|(_, part)| *part),
+                })
+            })
+            .collect()
+    }
+
+    /// Border strips of the windows visible on the output, bottom to top and relative to it,
+    /// with the parts covered by anything drawn above cut out
+    pub fn borders(
+        &self,
+        output: &SmithayOutput,
+        focused: Option<&desktop::Window>,
+        style: impl Fn(&Window) -> BorderStyle,
+    ) -> Vec<(Rectangle<i32, Logical>, [f32; 4])> {
+        let space = &self.spaces[self.active].space;
+        let output_geometry = match space.output_geometry(output) {
+            Some(output_geometry) => output_geometry,
+            None => return Vec::new(),
+        };
+        let windows: Vec<_> = space.windows().collect();
+
+        let mut borders = Vec::new();
+        for (index, window) in windows.iter().enumerate() {
+            if self.expanded(window) == Some(Expanded::Fullscreen) || self.is_animated(window) {
+                continue;
+            }
+            let area = match self.covered_area(window) {
+                Some(area) => area,
+                None => continue,
+            };
+
+            let style = style(&Window::wrap((*window).clone()));
+            let width = style.width;
+            let outer = Rectangle::from_loc_and_size(
+                (area.loc.x - width, area.loc.y - width),
+                (area.size.w + 2 * width, area.size.h + 2 * width),
+            );
+            if width <= 0 || !outer.overlaps(output_geometry) {
+                continue;
+            }
+
+            let color = if focused == Some(*window) {
+                style.active
+            } else if self.urgent.contains(window) {
+                style.urgent
+            } else {
+                style.inactive
+            };
+
+            let mut strips = vec![
+                Rectangle::from_loc_and_size(outer.loc, (outer.size.w, width)),
+                Rectangle::from_loc_and_size(
+                    (outer.loc.x, area.loc.y + area.size.h),
+                    (outer.size.w, width),
+                ),
+                Rectangle::from_loc_and_size((outer.loc.x, area.loc.y), (width, area.size.h)),
+                Rectangle::from_loc_and_size(
+                    (area.loc.x + area.size.w, area.loc.y),
+                    (width, area.size.h),
+                ),
+            ];
+            for cover in self.occluders(&windows, index) {
+                strips = strips
+                    .into_iter()
+                    .flat_map(|strip| border::subtract(strip, cover))
+                    .collect();
+            }
+
+            borders.extend(strips.into_iter().map(|mut strip| {
+                strip.loc = strip.loc - output_geometry.loc;
+                (strip, color)
+            }));
+        }
+
+        borders
+    }
+
+    /// Outputs the window overlaps in the output layout, also for hidden spaces
+    fn window_outputs(
+        &self,
+        space: &desktop::Space,
+        window: &desktop::Window,
+    ) -> Vec<SmithayOutput> {
+        let geometry = match space.window_geometry(window) {
+            Some(geometry) => geometry,
+            None => return Vec::new(),
+        };
+        let active = &self.spaces[self.active].space;
+
+        self.outputs
+            .iter()
+            .map(|(output, _, _)| output)
+            .filter(|output| {
+                active
+                    .output_geometry(output)
+                    .map_or(false, |output_geometry| output_geometry.overlaps(geometry))
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Unmap the window until it gets restored
+    pub fn minimize(&mut self, window: &desktop::Window) {
+        let index = match self
+            .spaces
+            .iter()
+            .position(|s| s.space.windows().any(|w| w == window))
+        {
+            Some(index) => index,
+            None => return,
+        };
+        let outputs = self.window_outputs(&self.spaces[index].space, window);
+        if index == self.active {
+            leave_outputs(window, &outputs);
+        }
+        let named = &mut self.spaces[index];
+
+        let location = self
+            .offset_origins
+            .iter()
+            .find(|(w, _)| w == window)
+            .map(|(_, origin)| *origin)
+            .or_else(|| named.space.window_geometry(window).map(|geo| geo.loc))
+            .unwrap_or_default();
+
+        named.space.unmap_window(window);
+
+        self.offset_origins.retain(|(w, _)| w != window);
+        self.minimized.push(Minimized {
+            workspace: named.name.clone(),
+            window: window.clone(),
+            location,
+            outputs,
+        });
+    }
+
+    /// Map a minimized window back into its workspace, returns false if it was not minimized
+    pub fn restore(&mut self, window: &desktop::Window) -> bool {
+        let index = match self.minimized.iter().position(|m| &m.window == window) {
+            Some(index) => index,
+            None => return false,
+        };
+        let minimized = self.minimized.remove(index);
+
+        let index = match self
+            .spaces
+            .iter()
+            .position(|s| s.name == minimized.workspace)
+        {
+            Some(index) => index,
+            None => self.insert(&minimized.workspace),
+        };
+
+        self.spaces[index]
+            .space
+            .map_window(&minimized.window, minimized.location, false);
+        true
+    }
+
+    pub fn is_on_top(&self, window: &desktop::Window) -> bool {
+        self.on_top.contains(window)
+    }
+
+    pub fn set_on_top(&mut self, window: &desktop::Window, on_top: bool) {
+        self.on_top.retain(|w| w != window);
+        if on_top {
+            self.on_top.push(window.clone());
+        }
+        self.raise_on_top();
+    }
+
+    /// Raise the window in the active space, windows kept on top stay above it
+    pub fn raise_window(&mut self, window: &desktop::Window, activate: bool) {
+        self.spaces[self.active]
+            .space
+            .raise_window(window, activate);
+        self.raise_on_top();
+    }
+
+    /// Put the windows kept on top back above the others, after a window got mapped or raised
+    pub fn raise_on_top(&mut self) {
+        let space = &mut self.spaces[self.active].space;
+        for window in self.on_top.iter() {
+            if space.windows().any(|w| w == window) {
+                space.raise_window(window, false);
+            }
+        }
+    }
+
+    /// Move the window to another workspace, creating it if needed, at the same location
+    pub fn move_window_to(&mut self, window: &desktop::Window, name: &str) {
+        let from = match self
+            .spaces
+            .iter()
+            .position(|s| s.space.windows().any(|w| w == window))
+        {
+            Some(from) => from,
+            None => return,
+        };
+        if self.spaces[from].name == name {
+            return;
+        }
+
+        let location = self
+            .offset_origins
+            .iter()
+            .find(|(w, _)| w == window)
+            .map(|(_, origin)| *origin)
+            .or_else(|| {
+                self.spaces[from]
+                    .space
+                    .window_geometry(window)
+                    .map(|geo| geo.loc)
+            })
+            .unwrap_or_default();
+
+        if from == self.active {
+            leave_outputs(
+                window,
+                &self.window_outputs(&self.spaces[from].space, window),
+            );
+        }
+        self.spaces[from].space.unmap_window(window);
+        self.offset_origins.retain(|(w, _)| w != window);
+
+        let to = match self.spaces.iter().position(|s| s.name == name) {
+            Some(to) => to,
+            None => self.insert(name),
+        };
+        self.spaces[to].space.map_window(window, location, false);
+    }
+
+    pub fn expanded(&self, window: &desktop::Window) -> Option<Expanded> {
+        self.expanded
+            .iter()
+            .find(|(w, _, _)| w == window)
+            .map(|(_, expanded, _)| *expanded)
+    }
+
+    /// Mark the window as expanded, the geometry to go back to is only kept from the first call
+    pub fn set_expanded(
+        &mut self,
+        window: &desktop::Window,
+        expanded: Expanded,
+        restore: Rectangle<i32, Logical>,
+    ) {
+        match self.expanded.iter_mut().find(|(w, _, _)| w == window) {
+            Some(entry) => entry.1 = expanded,
+            None => self.expanded.push((window.clone(), expanded, restore)),
+        }
+    }
+
+    pub fn take_expanded(
+        &mut self,
+        window: &desktop::Window,
+    ) -> Option<(Expanded, Rectangle<i32, Logical>)> {
+        let index = self.expanded.iter().position(|(w, _, _)| w == window)?;
+        let (_, expanded, restore) = self.expanded.remove(index);
+        Some((expanded, restore))
+    }
+
+    /// Some window of the active space is fullscreen on the output
+    pub fn has_fullscreen(&self, output: &SmithayOutput) -> bool {
+        let space = &self.spaces[self.active].space;
+        self.expanded.iter().any(|(w, expanded, _)| {
+            *expanded == Expanded::Fullscreen && space.outputs_for_window(w).contains(output)
+        })
+    }
+
+    /// Name of the workspace next to the active one, `forward` selects the direction
+    pub fn neighbour(&self, forward: bool) -> Option<&str> {
+        let index = if forward {
+            self.active.checked_add(1)
+        } else {
+            self.active.checked_sub(1)
+        }?;
+
+        self.spaces.get(index).map(|s| s.name.as_str())
+    }
+
+    /// Name for a new workspace appended after the last numbered one
+    pub fn next_free_name(&self) -> String {
+        let last = self
+            .spaces
+            .iter()
+            .filter_map(|s| s.name.parse::<u32>().ok())
+            .max()
+            .unwrap_or(0);
+
+        format!("{}", last + 1)
+    }
+
+    /// The workspace comes after the active one, in the order workspaces are shown
+    pub fn is_after_active(&self, name: &str) -> bool {
+        workspace_order(name, self.active_name()) == std::cmp::Ordering::Greater
+    }
+
+    /// Activate the workspace with the given name, creating it if needed.
+    /// Returns false if it was already active.
+    pub fn switch_to(&mut self, name: &str) -> bool {
+        if self.active_name() == name {
+            return false;
+        }
+
+        self.reset_offset();
+
+        let index = match self.spaces.iter().position(|s| s.name == name) {
+            Some(index) => index,
+            None => self.insert(name),
+        };
+
+        // The outputs move over to the new space, windows of the old one get hidden
+        let previous = &mut self.spaces[self.active].space;
+        for window in previous.windows() {
+            leave_outputs(window, &previous.outputs_for_window(window));
+        }
+        for (output, _, _) in self.outputs.iter() {
+            previous.unmap_output(output);
+        }
+
+        let space = &mut self.spaces[index].space;
+        for (output, scale, location) in self.outputs.iter() {
+            space.map_output(output, *scale, *location);
+        }
+
+        self.active = index;
+        true
+    }
+
+    fn insert(&mut self, name: &str) -> usize {
+        let named = NamedSpace::new(name.into());
+        let active_name = self.active_name().to_owned();
+
+        let index = self
+            .spaces
+            .iter()
+            .position(|s| workspace_order(name, &s.name) == std::cmp::Ordering::Less)
+            .unwrap_or(self.spaces.len());
+        self.spaces.insert(index, named);
+
+        self.active = self
+            .spaces
+            .iter()
+            .position(|s| s.name == active_name)
+            .unwrap();
+
+        index
+    }
+
+    pub fn map_output<P: Into<Point<i32, Logical>>>(
+        &mut self,
+        output: &SmithayOutput,
+        scale: f64,
+        location: P,
+    ) {
+        let location = location.into();
+
+        self.outputs.retain(|(o, _, _)| o != output);
+        self.outputs.push((output.clone(), scale, location));
+
+        self.spaces[self.active]
+            .space
+            .map_output(output, scale, location);
+    }
+
+    pub fn unmap_output(&mut self, output: &SmithayOutput) {
+        self.outputs.retain(|(o, _, _)| o != output);
+
+        self.spaces[self.active].space.unmap_output(output);
+    }
+
+    /// Space that contains the given window, no matter if it is active or not
+    pub fn space_for_window_mut(
+        &mut self,
+        window: &desktop::Window,
+    ) -> Option<&mut desktop::Space> {
+        self.spaces
+            .iter_mut()
+            .map(|s| &mut s.space)
+            .find(|s| s.windows().any(|w| w == window))
+    }
+
+    /// Window with the given toplevel surface in any of the spaces
+    pub fn find_window(&self, surface: &WlSurface) -> Option<&desktop::Window> {
+        self.spaces
+            .iter()
+            .find_map(|s| s.space.window_for_surface(surface))
+    }
+
+    /// Name of the workspace the window is mapped in, or was minimized from
+    pub fn workspace_of_window(&self, window: &desktop::Window) -> Option<&str> {
+        self.spaces
+            .iter()
+            .find(|s| s.space.windows().any(|w| w == window))
+            .map(|s| s.name.as_str())
+            .or_else(|| {
+                self.minimized
+                    .iter()
+                    .find(|m| &m.window == window)
+                    .map(|m| m.workspace.as_str())
+            })
+    }
+
+    pub fn commit(&mut self, surface: &WlSurface) {
+        for named in self.spaces.iter() {
+            named.space.commit(surface);
+        }
+
+        let mut root = surface.clone();
+        while let Some(parent) = compositor::get_parent(&root) {
+            root = parent;
+        }
+        if let Some(window) = self.find_window(&root).cloned() {
+            self.commit_serial += 1;
+            let serial = self.commit_serial;
+            match self.commits.iter_mut().find(|(w, _)| w == &window) {
+                Some((_, last)) => *last = serial,
+                None => self.commits.push((window, serial)),
+            }
+        }
+    }
+
+    pub fn refresh(&mut self) {
+        // Hidden spaces map no outputs, refreshing them only drops their dead windows
+        for named in self.spaces.iter_mut() {
+            named.space.refresh();
+        }
+
+        self.minimized.retain(|m| m.window.toplevel().alive());
+        self.expanded.retain(|(w, _, _)| w.toplevel().alive());
+        self.on_top.retain(|w| w.toplevel().alive());
+        self.commits.retain(|(w, _)| w.toplevel().alive());
+
+        let spaces = &self.spaces;
+        self.urgent
+            .retain(|w| spaces.iter().any(|s| s.space.windows().any(|x| x == w)));
+    }
+
+    /// Shift all windows of the active space, used to show the progress of a workspace switch
+    pub fn set_offset<P: Into<Point<i32, Logical>>>(&mut self, offset: P) {
+        self.slide = None;
+        self.apply_offset(offset.into());
+    }
+
+    fn apply_offset(&mut self, offset: Point<i32, Logical>) {
+        if self.offset_origins.is_empty() {
+            let space = &self.spaces[self.active].space;
+            self.offset_origins = space
+                .windows()
+                .filter_map(|w| space.window_geometry(w).map(|geo| (w.clone(), geo.loc)))
+                .collect();
         }
+
+        self.offset = offset;
+
+        let space = &mut self.spaces[self.active].space;
+        for (window, origin) in self.offset_origins.iter() {
+            if space.windows().any(|w| w == window) {
+                space.map_window(window, *origin + offset, false);
+            }
+        }
+    }
+
+    pub fn offset(&self) -> Point<i32, Logical> {
+        self.offset
+    }
+
+    pub fn reset_offset(&mut self) {
+        let origins = std::mem::take(&mut self.offset_origins);
+        self.offset = Point::default();
+        self.slide = None;
+
+        let space = &mut self.spaces[self.active].space;
+        for (window, origin) in origins {
+            if space.windows().any(|w| w == &window) {
+                space.map_window(&window, origin, false);
+            }
+        }
+    }
+
+    /// Slide the windows of the active space in from `from`, after a workspace switch
+    pub fn slide_in<P: Into<Point<i32, Logical>>>(&mut self, from: P, now: Duration, curve: Curve) {
+        let from = from.into();
+        self.apply_offset(from);
+        self.slide = Some((Animation::new(now, curve), from));
+    }
+
+    /// Grow and fade in a window that just got mapped, from `scale` of its size
+    pub fn animate_open(
+        &mut self,
+        window: &desktop::Window,
+        now: Duration,
+        curve: Curve,
+        scale: f64,
+    ) {
+        self.start_transition(window, Motion::Open { scale }, now, curve);
+    }
+
+    /// Move and resize a window from `from` to `to`, its client resizes it meanwhile
+    pub fn animate_resize(
+        &mut self,
+        window: &desktop::Window,
+        from: Rectangle<i32, Logical>,
+        to: Rectangle<i32, Logical>,
+        now: Duration,
+        curve: Curve,
+    ) {
+        self.start_transition(window, Motion::Resize { from, to }, now, curve);
+    }
+
+    fn start_transition(
+        &mut self,
+        window: &desktop::Window,
+        motion: Motion,
+        now: Duration,
+        curve: Curve,
+    ) {
+        self.transitions.retain(|t| &t.window != window);
+        self.transitions.push(Transition {
+            window: window.clone(),
+            motion,
+            animation: Animation::new(now, curve),
+        });
+    }
+
+    /// Remember the last state of the windows of the active space, windows whose client
+    /// just destroyed them shrink and fade out from there. Has to run before `refresh`.
+    pub fn track_closing(&mut self, now: Duration, curve: Option<Curve>, scale: f64) {
+        let named = &self.spaces[self.active];
+
+        let mut seen = Vec::new();
+        for (index, window) in named.space.windows().enumerate() {
+            if window.toplevel().alive() {
+                if let Some(placement) = Placement::of(&named.space, window) {
+                    seen.push((window.clone(), placement));
+                }
+                continue;
+            }
+
+            let last = self.seen.iter().find(|(w, _)| w == window);
+            if let (Some(curve), Some((_, placement))) = (curve, last) {
+                self.transitions.retain(|t| &t.window != window);
+                self.transitions.push(Transition {
+                    window: window.clone(),
+                    motion: Motion::Close {
+                        scale,
+                        workspace: named.name.clone(),
+                        placement: *placement,
+                        index,
+                    },
+                    animation: Animation::new(now, curve),
+                });
+            }
+        }
+
+        self.seen = seen;
+    }
+
+    /// Step the animations to the given time of the frame clock
+    pub fn advance(&mut self, now: Duration) {
+        if let Some((slide, from)) = self.slide {
+            if slide.is_done(now) {
+                self.reset_offset();
+            } else {
+                let t = slide.progress(now);
+                self.apply_offset(Point::from((
+                    animation::lerp(from.x as f64, 0.0, t).round() as i32,
+                    animation::lerp(from.y as f64, 0.0, t).round() as i32,
+                )));
+            }
+        }
+
+        // Closing windows are already dead, the others stop animating once they are
+        self.transitions.retain(|t| {
+            !t.animation.is_done(now)
+                && (matches!(t.motion, Motion::Close { .. }) || t.window.toplevel().alive())
+        });
+    }
+
+    pub fn is_animated(&self, window: &desktop::Window) -> bool {
+        self.transitions.iter().any(|t| &t.window == window)
+    }
+
+    /// Windows closing on the active space, whose snapshots are still drawn
+    pub fn closing(&self) -> Vec<desktop::Window> {
+        self.transitions
+            .iter()
+            .filter(|t| matches!(t.motion, Motion::Close { .. }))
+            .map(|t| t.window.clone())
+            .collect()
+    }
+
+    /// Windows of the active space shown on the output, for the output to keep snapshots of
+    pub fn snapshot_sources(&self, output: &SmithayOutput) -> Vec<SnapshotSource> {
+        let space = &self.spaces[self.active].space;
+        let output_geometry = match space.output_geometry(output) {
+            Some(output_geometry) => output_geometry,
+            None => return Vec::new(),
+        };
+
+        space
+            .windows()
+            .filter_map(|window| {
+                let placement = Placement::of(space, window)?;
+                let mut area = border::intersection(placement.bbox, output_geometry)?;
+                area.loc = area.loc - output_geometry.loc;
+
+                let surface = window.toplevel().get_surface()?.clone();
+                let serial = self
+                    .commits
+                    .iter()
+                    .find(|(w, _)| w == window)
+                    .map_or(0, |(_, serial)| *serial);
+                Some(SnapshotSource {
+                    window: window.clone(),
+                    surface,
+                    area,
+                    surface_loc: placement.surface_loc - output_geometry.loc,
+                    serial,
+                })
+            })
+            .collect()
+    }
+
+    /// The window stack of the active space as it has to be drawn on the output at the given
+    /// time, bottom to top. `None` when nothing on the output is animated, so the space can draw it.
+    pub fn stack(&self, output: &SmithayOutput, now: Duration) -> Option<Vec<Layered>> {
+        let space = &self.spaces[self.active].space;
+        let output_geometry = space.output_geometry(output)?;
+
+        let mut samples = self.samples(output_geometry, now);
+        let animated_here = self.transitions.iter().any(|t| {
+            Placement::of(space, &t.window)
+                .map_or(false, |placement| placement.bbox.overlaps(output_geometry))
+        });
+        if samples.is_empty() && !animated_here {
+            return None;
+        }
+
+        let windows: Vec<_> = space.windows().collect();
+        let mut stack = Vec::new();
+
+        for (index, window) in windows.iter().enumerate() {
+            // Closed windows go back where they were, below the window that took their place
+            let (closed, rest): (Vec<_>, Vec<_>) = samples
+                .into_iter()
+                .partition(|(position, _)| *position == Position::Closed(index));
+            samples = rest;
+            stack.extend(
+                closed
+                    .into_iter()
+                    .map(|(_, sample)| Layered::Transition(sample)),
+            );
+
+            if let Some(i) = samples
+                .iter()
+                .position(|(position, _)| *position == Position::Mapped(index))
+            {
+                stack.push(Layered::Transition(samples.remove(i).1));
+            } else if !self.is_animated(window) {
+                if let Some(placement) = Placement::of(space, window) {
+                    stack.push(Layered::Window(
+                        (*window).clone(),
+                        placement.surface_loc - output_geometry.loc,
+                    ));
+                }
+            }
+        }
+
+        // Whatever is left closed at the top of the stack
+        stack.extend(
+            samples
+                .into_iter()
+                .map(|(_, sample)| Layered::Transition(sample)),
+        );
+
+        Some(stack)
+    }
+
+    /// Animated windows as they have to be drawn on the output at the given time,
+    /// with their place in the stack
+    fn samples(
+        &self,
+        output_geometry: Rectangle<i32, Logical>,
+        now: Duration,
+    ) -> Vec<(Position, Sample)> {
+        let named = &self.spaces[self.active];
+        let output_area = Rectangle::from_loc_and_size((0, 0), output_geometry.size);
+        let windows: Vec<_> = named.space.windows().collect();
+
+        self.transitions
+            .iter()
+            .filter_map(|transition| {
+                let t = transition.animation.progress(now);
+                let mapped = || {
+                    let index = windows.iter().position(|w| *w == &transition.window)?;
+                    let surface = transition.window.toplevel().get_surface()?.clone();
+                    let placement = Placement::of(&named.space, &transition.window)?;
+                    Some((
+                        Position::Mapped(index),
+                        Content::Surface(surface),
+                        placement,
+                    ))
+                };
+
+                let (position, content, placement, target, alpha) = match &transition.motion {
+                    Motion::Open { scale } => {
+                        let (position, content, placement) = mapped()?;
+                        let geometry = placement.geometry.to_f64();
+                        let from = animation::scale_rect(geometry, *scale);
+                        let target = animation::lerp_rect(from, geometry, t);
+                        (position, content, placement, target, t)
+                    }
+                    Motion::Close {
+                        scale,
+                        workspace,
+                        placement,
+                        index,
+                    } => {
+                        if workspace != &named.name {
+                            return None;
+                        }
+                        let geometry = placement.geometry.to_f64();
+                        let to = animation::scale_rect(geometry, *scale);
+                        (
+                            Position::Closed(*index),
+                            Content::Snapshot(transition.window.clone()),
+                            *placement,
+                            animation::lerp_rect(geometry, to, t),
+                            1.0 - t,
+                        )
+                    }
+                    Motion::Resize { from, to } => {
+                        let (position, content, placement) = mapped()?;
+                        let target = animation::lerp_rect(from.to_f64(), to.to_f64(), t);
+                        (position, content, placement, target, 1.0)
+                    }
+                };
+
+                let sample = Sample::new(
+                    content,
+                    placement,
+                    target,
+                    alpha as f32,
+                    output_geometry.loc,
+                );
+                Some((position, sample)).filter(|(_, sample)| sample.damage().overlaps(output_area))
+            })
+            .collect()
+    }
+}
+
+/// Place of an animated window in the stack of its space
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Position {
+    /// Still mapped, at this index
+    Mapped(usize),
+    /// Gone, it was at this index
+    Closed(usize),
+}
+
+/// Send leave events for the surface tree of a window that stops being shown on the outputs
+fn leave_outputs(window: &desktop::Window, outputs: &[SmithayOutput]) {
+    if let Some(surface) = window.toplevel().get_surface() {
+        compositor::with_surface_tree_downward(
+            surface,
+            (),
+            |_, _, _| TraversalAction::DoChildren(()),
+            |surface, _, _| {
+                for output in outputs {
+                    output.leave(surface);
+                }
+            },
+            |_, _, _| true,
+        );
+    }
+}
+
+/// Numbered workspaces are sorted numerically and come before named ones
+fn workspace_order(a: &str, b: &str) -> std::cmp::Ordering {
+    match (a.parse::<u32>(), b.parse::<u32>()) {
+        (Ok(a), Ok(b)) => a.cmp(&b),
+        (Ok(_), Err(_)) => std::cmp::Ordering::Less,
+        (Err(_), Ok(_)) => std::cmp::Ordering::Greater,
+        (Err(_), Err(_)) => a.cmp(b),
     }
 }
 
@@ -23,12 +1168,12 @@ impl std::ops::Deref for Workspace {
     type Target = desktop::Space;
 
     fn deref(&self) -> &Self::Target {
-        &self.space
+        &self.spaces[self.active].space
     }
 }
 
 impl std::ops::DerefMut for Workspace {
     fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.space
+        &mut self.spaces[self.active].space
     }
 }